@@ -0,0 +1,104 @@
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use histr::{KernelDensity, StreamHist};
+
+/// Number of the values inserted in each of the benchmarks.
+const VALUES: usize = 100_000;
+
+/// Sizes of the histograms.
+const SIZES: [usize; 4] = [10, 100, 1000, 5000];
+
+/// Pseudo-random values from the uniform distribution on $[0, 1)$, the same on every run.
+fn uniform(n: usize, seed: u64) -> Vec<f64> {
+    let mut state = seed;
+    (0..n)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1_u64 << 53) as f64
+        })
+        .collect()
+}
+
+fn hist(size: usize, seed: u64) -> StreamHist {
+    let mut hist = StreamHist::with_capacity(size);
+    hist.extend(uniform(VALUES, seed));
+    hist
+}
+
+fn insert(c: &mut Criterion) {
+    let values = uniform(VALUES, 42);
+    let mut group = c.benchmark_group("insert");
+    group.throughput(Throughput::Elements(VALUES as u64));
+    for size in SIZES {
+        group.bench_with_input(BenchmarkId::new("checked", size), &size, |b, size| {
+            b.iter(|| {
+                let mut hist = StreamHist::with_capacity(*size);
+                values.iter().for_each(|x| hist.insert(*x));
+                hist
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("unchecked", size), &size, |b, size| {
+            b.iter(|| {
+                let mut hist = StreamHist::with_capacity(*size);
+                values.iter().for_each(|x| hist.insert_unchecked(*x));
+                hist
+            })
+        });
+    }
+    group.finish();
+}
+
+fn merge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merge");
+    for size in SIZES {
+        let (a, b) = (hist(size, 1), hist(size, 2));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |bench, _| {
+            bench.iter_batched(
+                || (a.clone(), b.clone()),
+                |(mut a, b)| {
+                    a.merge(b);
+                    a
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn queries(c: &mut Criterion) {
+    let mut group = c.benchmark_group("quantile");
+    for size in SIZES {
+        let hist = hist(size, 3);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &hist, |b, hist| {
+            b.iter(|| hist.quantile(black_box(0.99)))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("cdf");
+    for size in SIZES {
+        let hist = hist(size, 4);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &hist, |b, hist| {
+            b.iter(|| hist.cdf(black_box(0.42)))
+        });
+    }
+    group.finish();
+}
+
+fn density(c: &mut Criterion) {
+    let mut group = c.benchmark_group("density");
+    for size in [10, 100, 1000] {
+        let kde = KernelDensity::from(hist(size, 5));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &kde, |b, kde| {
+            b.iter(|| kde.density(black_box(0.42)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, insert, merge, queries, density);
+criterion_main!(benches);
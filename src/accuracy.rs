@@ -0,0 +1,319 @@
+use crate::hist::StreamHist;
+
+/// Upper bound for the number of bins of the histograms created by [`StreamHist::with_target_error`].
+pub const MAX_TARGET_SIZE: usize = 4096;
+
+/// Probabilities for which the accuracy of the quantiles is reported by [`StreamHist::accuracy_report`].
+const REPORTED_PROBS: [f64; 7] = [0.01, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99];
+
+/// Estimated accuracy of the quantiles and cumulative probabilities approximated by the histogram.
+///
+/// See [`StreamHist::accuracy_report`] for details.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccuracyReport {
+    /// The largest error of the cumulative probability (rank divided by the count) over the whole histogram.
+    pub max_rank_error: f64,
+    /// The accuracy of the selected quantiles.
+    pub quantiles: Vec<QuantileAccuracy>,
+}
+
+/// Estimated accuracy of a single quantile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuantileAccuracy {
+    /// Probability of the quantile.
+    pub prob: f64,
+    /// Approximate quantile, see [`StreamHist::quantile`].
+    pub value: f64,
+    /// The error of the cumulative probability around the quantile.
+    pub rank_error: f64,
+    /// Quantile of the probability lowered by the `rank_error`.
+    pub lower: f64,
+    /// Quantile of the probability raised by the `rank_error`.
+    pub upper: f64,
+}
+
+/// The accuracy kept by adjusting the size of the histogram, see [`StreamHist::with_target_error`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TargetError {
+    /// The targeted largest rank error.
+    epsilon: f64,
+    /// The count when the accuracy is checked next time.
+    next_check: u64,
+}
+
+impl StreamHist {
+    /// Initialize an empty histogram that adjusts its size to keep the largest rank error (see
+    /// [`StreamHist::accuracy_report`]) below `epsilon`.
+    ///
+    /// The histogram starts with $\lceil 1/\epsilon \rceil$ bins, which is enough when the values
+    /// are spread evenly between the bins. The accuracy is checked after each inserted batch of values as
+    /// many as the bins, so the checks take constant time per insert on average. When the error exceeds
+    /// `epsilon`, the number of bins is doubled, up to [`MAX_TARGET_SIZE`], and when it is below a quarter
+    /// of `epsilon`, the bins are merged to half of them. The bins merged before growing keep their errors,
+    /// but they become smaller relative to the total count as more values are inserted. The size is adjusted also
+    /// when merging the other histograms or extending, but only once per call, so the large batches are better
+    /// split into the smaller ones. Like the [`MergePolicy`],
+    /// the target is a setting of the histogram that is not serialized.
+    ///
+    /// [`MergePolicy`]: crate::MergePolicy
+    ///
+    /// # Panics
+    ///
+    /// The `epsilon` needs to be between `0.0` and `1.0` (exclusive), otherwise it panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let mut hist = StreamHist::with_target_error(0.05);
+    /// assert_eq!(hist.capacity(), 20);
+    ///
+    /// // the values concentrated in a few bins, with the long tail
+    /// for i in 0..10_000 {
+    ///     hist.insert(((i * 7919) % 1000) as f64 / 1000.0 + ((i % 100) as f64).exp());
+    /// }
+    /// assert!(hist.capacity() > 20);
+    /// assert!(hist.accuracy_report().max_rank_error < 0.05);
+    /// ```
+    pub fn with_target_error(epsilon: f64) -> Self {
+        assert!(
+            epsilon > 0.0 && epsilon < 1.0,
+            "epsilon {epsilon} needs to be between 0 and 1"
+        );
+        let size = ((1.0 / epsilon).ceil() as usize).clamp(2, MAX_TARGET_SIZE);
+        StreamHist {
+            target: Some(TargetError {
+                epsilon,
+                next_check: size as u64,
+            }),
+            ..StreamHist::with_capacity(size)
+        }
+    }
+
+    /// Adjust the size to keep the accuracy targeted by [`StreamHist::with_target_error`].
+    pub(crate) fn adjust_size(&mut self) {
+        let Some(target) = self.target else {
+            return;
+        };
+        if self.totals.count < target.next_check {
+            return;
+        }
+        // the errors do not depend on the size when there is space for more bins
+        if self.bins.len() == self.size {
+            let error = self.accuracy_report().max_rank_error;
+            if error > target.epsilon && self.size < MAX_TARGET_SIZE {
+                self.resize((2 * self.size).min(MAX_TARGET_SIZE));
+            } else if error < target.epsilon / 4.0 && self.size > 2 {
+                self.resize(self.size / 2);
+            }
+        }
+        self.target = Some(TargetError {
+            next_check: self.totals.count + self.size as u64,
+            ..target
+        });
+    }
+
+    /// Estimate the accuracy of the approximations made by the histogram.
+    ///
+    /// When interpolating between the neighboring bins, Ben-Haim and Tom-Tov (2010) assume that half of
+    /// the values of each bin lie on each side of its mean and are uniformly distributed between the means.
+    /// The actual positions of those values are unknown, so the rank of a value between the bins may be off by
+    /// up to half of the counts of the two bins, the bins with the count of one hold the exact values and add
+    /// no uncertainty. The errors are reported relative to the total count, as the errors of the cumulative
+    /// probabilities, together with the ranges of the quantiles corresponding to those errors for the
+    /// 1%, 5%, 25%, 50%, 75%, 95%, and 99% quantiles.
+    ///
+    /// The errors of the empty histogram are `f64::NAN` and no quantiles are reported.
+    ///
+    /// If the errors are too large, the histogram needs more bins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let mut hist = StreamHist::with_capacity(100);
+    /// for i in 0..1000 {
+    ///     hist.insert(i as f64);
+    /// }
+    ///
+    /// let report = hist.accuracy_report();
+    /// // the ranks are off by at most 2% of the count
+    /// assert!(report.max_rank_error < 0.02);
+    ///
+    /// let median = report.quantiles[3];
+    /// assert_eq!(median.prob, 0.5);
+    /// assert!(median.lower <= median.value && median.value <= median.upper);
+    /// ```
+    pub fn accuracy_report(&self) -> AccuracyReport {
+        if self.is_empty() {
+            return AccuracyReport {
+                max_rank_error: f64::NAN,
+                quantiles: Vec::new(),
+            };
+        }
+
+        let max_rank_error = (0..=self.bins.len())
+            .map(|index| self.interval_rank_error(index))
+            .fold(0.0, f64::max);
+
+        let quantiles = REPORTED_PROBS
+            .iter()
+            .map(|&prob| {
+                let (index, _) = self.find_cumulative_count_by(prob * self.count());
+                let rank_error = self.interval_rank_error(index);
+                QuantileAccuracy {
+                    prob,
+                    value: self.quantile(prob),
+                    rank_error,
+                    lower: self.quantile((prob - rank_error).max(0.0)),
+                    upper: self.quantile((prob + rank_error).min(1.0)),
+                }
+            })
+            .collect();
+
+        AccuracyReport {
+            max_rank_error,
+            quantiles,
+        }
+    }
+
+    /// The relative rank error in the interval between the bins at `index-1` and `index`.
+    fn interval_rank_error(&self, index: usize) -> f64 {
+        let (left, right) = self.neighbors(index);
+        // the values of the bins with the count of one are known exactly
+        let unknown = |count: u64| if count > 1 { count as f64 } else { 0.0 };
+        (unknown(left.count) + unknown(right.count)) / 2.0 / self.count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bins::Bin;
+    use crate::hist::StreamHist;
+
+    #[test]
+    fn empty() {
+        let report = StreamHist::default().accuracy_report();
+        assert!(report.max_rank_error.is_nan());
+        assert!(report.quantiles.is_empty());
+    }
+
+    #[test]
+    fn exact() {
+        // all the values are stored exactly
+        let report = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0]).accuracy_report();
+        assert_eq!(report.max_rank_error, 0.0);
+        for q in report.quantiles {
+            assert_eq!(q.rank_error, 0.0);
+            assert_eq!(q.lower, q.value);
+            assert_eq!(q.upper, q.value);
+        }
+    }
+
+    #[test]
+    fn rank_errors() {
+        let hist = StreamHist::from(vec![Bin::new(1.0, 2), Bin::new(2.0, 1), Bin::new(4.0, 5)]);
+        assert_eq!(hist.interval_rank_error(0), 1.0 / 8.0);
+        assert_eq!(hist.interval_rank_error(1), 1.0 / 8.0);
+        assert_eq!(hist.interval_rank_error(2), 2.5 / 8.0);
+        assert_eq!(hist.interval_rank_error(3), 2.5 / 8.0);
+
+        let report = hist.accuracy_report();
+        assert_eq!(report.max_rank_error, 2.5 / 8.0);
+        assert_eq!(report.quantiles.len(), 7);
+        for q in report.quantiles {
+            assert!(q.rank_error > 0.0);
+            assert!(q.lower <= q.value && q.value <= q.upper);
+        }
+    }
+
+    #[test]
+    fn target_error() {
+        let mut hist = StreamHist::with_target_error(0.02);
+        assert_eq!(hist.capacity(), 50);
+        let mut fixed = StreamHist::with_capacity(50);
+        // most of the values are in a narrow range, and a few are spread far from it
+        for i in 0..50_000 {
+            let x = ((i * 7919) % 1000) as f64 / 1000.0;
+            let x = if i % 10 == 0 { x * 1e6 } else { x };
+            hist.insert(x);
+            fixed.insert(x);
+        }
+        assert!(hist.capacity() > 50);
+        assert!(hist.capacity() <= super::MAX_TARGET_SIZE);
+        assert!(fixed.accuracy_report().max_rank_error > 0.02);
+        assert!(hist.accuracy_report().max_rank_error < 0.02);
+
+        // the evenly spread values need about the initial number of bins
+        let mut hist = StreamHist::with_target_error(0.02);
+        for i in 0..50_000 {
+            hist.insert(((i * 7919) % 1000) as f64);
+        }
+        assert!(hist.capacity() <= 100, "{}", hist.capacity());
+        assert!(hist.accuracy_report().max_rank_error < 0.02);
+
+        // the bins are merged back when the error is much smaller than the target
+        let mut hist = StreamHist::with_target_error(0.1);
+        hist.resize(100);
+        for i in 0..1000 {
+            hist.insert(((i * 7919) % 1000) as f64);
+        }
+        assert!(hist.capacity() < 100);
+        assert!(hist.accuracy_report().max_rank_error < 0.1);
+    }
+
+    #[test]
+    fn target_error_merge_and_extend() {
+        let skewed = |i: i32| {
+            let x = ((i * 7919) % 1000) as f64 / 1000.0;
+            if i % 10 == 0 {
+                x * 1e6
+            } else {
+                x
+            }
+        };
+
+        let mut hist = StreamHist::with_target_error(0.02);
+        hist.extend((0..50_000).map(skewed));
+        assert!(hist.capacity() > 50);
+
+        let mut hist = StreamHist::with_target_error(0.02);
+        for batch in 0..500 {
+            hist.extend((batch * 100..(batch + 1) * 100).map(skewed));
+        }
+        assert!(hist.capacity() > 50);
+        assert!(hist.accuracy_report().max_rank_error < 0.02);
+
+        let mut hist = StreamHist::with_target_error(0.02);
+        for batch in 0..500 {
+            let other = StreamHist::from(
+                (batch * 100..(batch + 1) * 100)
+                    .map(skewed)
+                    .collect::<Vec<_>>(),
+            );
+            hist.merge(other);
+        }
+        assert!(hist.capacity() > 50);
+        assert!(hist.accuracy_report().max_rank_error < 0.02);
+    }
+
+    #[test]
+    #[should_panic]
+    fn target_error_invalid() {
+        StreamHist::with_target_error(0.0);
+    }
+
+    #[test]
+    fn more_bins_are_more_accurate() {
+        let mut small = StreamHist::with_capacity(5);
+        let mut large = StreamHist::with_capacity(50);
+        for i in 0..1000 {
+            let x = ((i * 7919) % 1000) as f64;
+            small.insert(x);
+            large.insert(x);
+        }
+        assert!(large.accuracy_report().max_rank_error < small.accuracy_report().max_rank_error);
+    }
+}
@@ -0,0 +1,130 @@
+//! Approximate comparisons of the bins and histograms using the [`approx`] crate.
+//!
+//! The means, `min`, and `max` are compared approximately, while the counts and sizes need to be exactly equal.
+//!
+//! # Examples
+//!
+//! ```
+//! use approx::assert_relative_eq;
+//! use histr::StreamHist;
+//!
+//! let hist = StreamHist::from(vec![0.1 + 0.2, 1.0]);
+//! assert_relative_eq!(hist, StreamHist::from(vec![0.3, 1.0]));
+//! ```
+
+use crate::bins::Bin;
+use crate::hist::StreamHist;
+use approx::{AbsDiffEq, RelativeEq};
+use std::iter::zip;
+
+impl AbsDiffEq for Bin {
+    type Epsilon = f64;
+
+    fn default_epsilon() -> Self::Epsilon {
+        f64::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        self.count == other.count && self.mean.abs_diff_eq(&other.mean, epsilon)
+    }
+}
+
+impl RelativeEq for Bin {
+    fn default_max_relative() -> Self::Epsilon {
+        f64::default_max_relative()
+    }
+
+    fn relative_eq(
+        &self,
+        other: &Self,
+        epsilon: Self::Epsilon,
+        max_relative: Self::Epsilon,
+    ) -> bool {
+        self.count == other.count && self.mean.relative_eq(&other.mean, epsilon, max_relative)
+    }
+}
+
+impl AbsDiffEq for StreamHist {
+    type Epsilon = f64;
+
+    fn default_epsilon() -> Self::Epsilon {
+        f64::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        self.same_shape(other)
+            && zip(&self.bins, &other.bins).all(|(a, b)| a.abs_diff_eq(b, epsilon))
+            && nan_or(self.min, other.min, |a, b| a.abs_diff_eq(&b, epsilon))
+            && nan_or(self.max, other.max, |a, b| a.abs_diff_eq(&b, epsilon))
+    }
+}
+
+impl RelativeEq for StreamHist {
+    fn default_max_relative() -> Self::Epsilon {
+        f64::default_max_relative()
+    }
+
+    fn relative_eq(
+        &self,
+        other: &Self,
+        epsilon: Self::Epsilon,
+        max_relative: Self::Epsilon,
+    ) -> bool {
+        let eq = |a: f64, b: f64| a.relative_eq(&b, epsilon, max_relative);
+        self.same_shape(other)
+            && zip(&self.bins, &other.bins).all(|(a, b)| a.relative_eq(b, epsilon, max_relative))
+            && nan_or(self.min, other.min, eq)
+            && nan_or(self.max, other.max, eq)
+    }
+}
+
+impl StreamHist {
+    /// Check if the histograms have the same sizes and numbers of bins.
+    fn same_shape(&self, other: &Self) -> bool {
+        self.size == other.size && self.bins.len() == other.bins.len()
+    }
+}
+
+/// Both values are `f64::NAN` (the histograms are empty) or they are equal according to `eq`.
+fn nan_or(a: f64, b: f64, eq: impl Fn(f64, f64) -> bool) -> bool {
+    (a.is_nan() && b.is_nan()) || eq(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bins::Bin;
+    use crate::hist::StreamHist;
+    use approx::{abs_diff_eq, assert_abs_diff_eq, assert_relative_eq, assert_relative_ne};
+
+    #[test]
+    fn bins() {
+        assert_relative_eq!(Bin::new(0.1 + 0.2, 3), Bin::new(0.3, 3));
+        assert_relative_ne!(Bin::new(0.3, 2), Bin::new(0.3, 3));
+        assert_abs_diff_eq!(Bin::new(1.0, 1), Bin::new(1.05, 1), epsilon = 0.1);
+        assert!(!abs_diff_eq!(
+            Bin::new(1.0, 1),
+            Bin::new(1.5, 1),
+            epsilon = 0.1
+        ));
+    }
+
+    #[test]
+    fn histograms() {
+        assert_relative_eq!(StreamHist::default(), StreamHist::default());
+        assert_relative_ne!(StreamHist::default(), StreamHist::with_capacity(5));
+
+        let hist = StreamHist::from(vec![1.0, 2.0, 3.0]);
+        assert_relative_eq!(
+            hist,
+            StreamHist::from(vec![1.0, 2.0 + 1e-15, 3.0]),
+            max_relative = 1e-12
+        );
+        assert_relative_ne!(hist, StreamHist::from(vec![1.0, 2.0]));
+        assert_relative_ne!(hist, StreamHist::from(vec![1.0, 2.1, 3.0]));
+
+        let mut other = hist.clone();
+        other.max = 3.1;
+        assert_relative_ne!(hist, other);
+        assert_abs_diff_eq!(hist, other, epsilon = 0.5);
+    }
+}
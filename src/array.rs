@@ -0,0 +1,260 @@
+use crate::{bins::Bin, hist::StreamHist, sample::IntoSample, stats};
+
+/// Streaming histogram storing at most `N` bins in a fixed-size array, with no heap allocations.
+///
+/// It uses the same update procedure as [`StreamHist::insert`], so inserting the same values gives the same bins,
+/// and the same procedures for calculating the statistics. Since it has the fixed size, it can be initialized
+/// in the `const` context, e.g. as a `static` variable guarded by a mutex. It can be converted to [`StreamHist`]
+/// for the features that are not available for it.
+///
+/// # Examples
+///
+/// ```
+/// use histr::{ArrayStreamHist, StreamHist};
+///
+/// let mut hist = ArrayStreamHist::<5>::new();
+/// for i in 1..=10 {
+///     hist.insert(i as f64);
+/// }
+/// assert_eq!(hist.count(), 10.0);
+/// assert_eq!(hist.bins().len(), 5);
+/// assert_eq!(hist.quantile(0.5), 5.5);
+///
+/// let converted = StreamHist::from(&hist);
+/// assert_eq!(converted.capacity(), 5);
+/// assert_eq!(converted.bins(), hist.bins());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArrayStreamHist<const N: usize> {
+    /// Sorted bins, only the first `len` are used.
+    bins: [Bin; N],
+    len: usize,
+    min: f64,
+    max: f64,
+}
+
+impl<const N: usize> ArrayStreamHist<N> {
+    /// Initialize an empty histogram.
+    pub const fn new() -> Self {
+        ArrayStreamHist {
+            bins: [Bin {
+                mean: 0.0,
+                count: 0,
+            }; N],
+            len: 0,
+            min: f64::NAN,
+            max: f64::NAN,
+        }
+    }
+
+    /// Insert a new point to the histogram, see [`StreamHist::insert`].
+    ///
+    /// # Panics
+    ///
+    /// The `value` needs to be a number. It will panic on `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    pub fn insert<T: IntoSample>(&mut self, value: T) {
+        self.insert_bin(Bin::from(value.into_sample()))
+    }
+
+    /// Merge the bins of the other histogram, inserting them one by one using the update procedure.
+    ///
+    /// Unlike [`StreamHist::merge`], it does not combine all the bins before trimming them, since it would
+    /// need to allocate the space for them, so the result can slightly differ.
+    pub fn merge<const M: usize>(&mut self, other: &ArrayStreamHist<M>) {
+        other.bins().iter().for_each(|bin| self.insert_bin(*bin));
+    }
+
+    fn insert_bin(&mut self, bin: Bin) {
+        if N == 0 || bin.count == 0 {
+            return;
+        }
+        self.min = self.min.min(bin.mean);
+        self.max = self.max.max(bin.mean);
+
+        // Algorithm 1: Update Procedure from Ben-Haim & Tom-Tov (2010), p. 851
+        let idx = self.bins().partition_point(|x| x.mean < bin.mean);
+        if idx < self.len && self.bins[idx].mean == bin.mean {
+            self.bins[idx].count += bin.count;
+            return;
+        }
+        if self.len < N {
+            self.insert_at(idx, bin);
+            return;
+        }
+
+        // there is no space for the new bin, so find the closest pair of bins in the sequence
+        // including it, and merge them before storing it
+        let get = |k: usize| match k.cmp(&idx) {
+            std::cmp::Ordering::Less => self.bins[k],
+            std::cmp::Ordering::Equal => bin,
+            std::cmp::Ordering::Greater => self.bins[k - 1],
+        };
+        let k = (0..N)
+            .map(|k| get(k + 1).mean - get(k).mean)
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(k, _)| k);
+
+        if k + 1 == idx {
+            self.bins[k] = bin + self.bins[k];
+        } else if k == idx {
+            self.bins[k] = self.bins[k] + bin;
+        } else {
+            // the pair of existing bins at the positions `j` and `j + 1`
+            let j = if k < idx { k } else { k - 1 };
+            self.bins[j] = self.bins[j + 1] + self.bins[j];
+            self.remove_at(j + 1);
+            self.insert_at(if k < idx { idx - 1 } else { idx }, bin);
+        }
+    }
+
+    #[inline]
+    fn insert_at(&mut self, index: usize, bin: Bin) {
+        self.bins.copy_within(index..self.len, index + 1);
+        self.bins[index] = bin;
+        self.len += 1;
+    }
+
+    #[inline]
+    fn remove_at(&mut self, index: usize) {
+        self.bins.copy_within(index + 1..self.len, index);
+        self.len -= 1;
+    }
+
+    /// Sorted bins of the histogram.
+    pub fn bins(&self) -> &[Bin] {
+        &self.bins[..self.len]
+    }
+
+    /// Returns `true` if the histogram contains no data.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Smallest observed value, `f64::NAN` for an empty histogram.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Largest observed value, `f64::NAN` for an empty histogram.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// The total count of all the values used to create the histogram.
+    pub fn count(&self) -> f64 {
+        self.bins().iter().map(|bin| bin.count).sum::<u64>() as f64
+    }
+
+    /// Approximate mean of the data, see [`StreamHist::mean`].
+    pub fn mean(&self) -> f64 {
+        self.bins()
+            .iter()
+            .fold(0.0, |acc, bin| acc + bin.mean * bin.count as f64)
+            / self.count()
+    }
+
+    /// Approximate count of the number of values since the `value`, see [`StreamHist::count_by`].
+    pub fn count_by(&self, value: f64) -> f64 {
+        stats::count_by(self.bins(), self.min, self.max, self.count(), value)
+    }
+
+    /// Approximate empirical cumulative distribution function, see [`StreamHist::cdf`].
+    pub fn cdf(&self, value: f64) -> f64 {
+        self.count_by(value) / self.count()
+    }
+
+    /// Approximate sample quantile, see [`StreamHist::quantile`].
+    ///
+    /// # Panics
+    ///
+    /// `prob` needs to be a probability value between `0.0` and `1.0` (inclusive),
+    /// otherwise it panics.
+    pub fn quantile(&self, prob: f64) -> f64 {
+        assert!(
+            (0.0..=1.0).contains(&prob),
+            "{prob} is not a valid probability"
+        );
+        stats::quantile(
+            self.bins(),
+            self.min,
+            self.max,
+            self.count(),
+            prob,
+            Default::default(),
+        )
+    }
+}
+
+impl<const N: usize> Default for ArrayStreamHist<N> {
+    fn default() -> Self {
+        ArrayStreamHist::new()
+    }
+}
+
+impl<const N: usize> From<&ArrayStreamHist<N>> for StreamHist {
+    /// Convert to the [`StreamHist`] with the size equal to `N`.
+    fn from(hist: &ArrayStreamHist<N>) -> Self {
+        if hist.is_empty() {
+            return StreamHist::with_capacity(N);
+        }
+        StreamHist::from_parts(hist.bins().to_vec(), hist.min, hist.max, N)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArrayStreamHist;
+    use crate::hist::StreamHist;
+
+    #[test]
+    fn same_as_stream_hist() {
+        let values: Vec<f64> = (0..500).map(|i| ((i * 7919) % 613) as f64 / 7.0).collect();
+        let mut hist = ArrayStreamHist::<10>::new();
+        let mut expected = StreamHist::with_capacity(10);
+        for value in values {
+            hist.insert(value);
+            expected.insert(value);
+            assert_eq!(hist.bins(), expected.bins());
+        }
+        assert_eq!(StreamHist::from(&hist), expected);
+        assert_eq!(hist.count(), expected.count());
+        assert!((hist.mean() - expected.mean()).abs() < 1e-9);
+        for prob in [0.0, 0.1, 0.5, 0.9, 1.0] {
+            assert_eq!(hist.quantile(prob), expected.quantile(prob));
+        }
+        for value in [-1.0, 10.0, 50.0, 100.0] {
+            assert_eq!(hist.cdf(value), expected.cdf(value));
+        }
+    }
+
+    #[test]
+    fn merge() {
+        let mut hist = ArrayStreamHist::<3>::new();
+        let mut other = ArrayStreamHist::<5>::new();
+        [1.0, 2.0, 3.0].into_iter().for_each(|x| hist.insert(x));
+        [2.0, 10.0, 11.0].into_iter().for_each(|x| other.insert(x));
+        hist.merge(&other);
+        assert_eq!(hist.count(), 6.0);
+        assert_eq!(hist.bins().len(), 3);
+        assert_eq!((hist.min(), hist.max()), (1.0, 11.0));
+    }
+
+    #[test]
+    fn empty() {
+        let hist = ArrayStreamHist::<3>::default();
+        assert!(hist.is_empty());
+        assert!(hist.quantile(0.5).is_nan());
+        assert_eq!(StreamHist::from(&hist), StreamHist::with_capacity(3));
+
+        let mut hist = ArrayStreamHist::<0>::new();
+        hist.insert(1.0);
+        assert!(hist.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_nan() {
+        ArrayStreamHist::<3>::new().insert(f64::NAN);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::bins::Bin;
+use crate::hist::StreamHist;
+use arrow_array::{Array, ArrayRef, Float64Array, RecordBatch, UInt64Array};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+impl StreamHist {
+    /// Insert the values from the [Apache Arrow] array to the histogram, the null values are skipped.
+    ///
+    /// [Apache Arrow]: https://arrow.apache.org/
+    ///
+    /// # Panics
+    ///
+    /// All the non-null values need to be a numbers. It will panic on any `f64::NAN`, `f64::INFINITY`,
+    /// or `f64::NEG_INFINITY`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arrow_array::Float64Array;
+    /// use histr::StreamHist;
+    ///
+    /// let array = Float64Array::from(vec![Some(1.0), None, Some(2.0), Some(3.0)]);
+    /// let mut hist = StreamHist::with_capacity(5);
+    /// hist.extend_from_arrow(&array);
+    ///
+    /// assert_eq!(hist.count(), 3.0);
+    /// ```
+    pub fn extend_from_arrow(&mut self, array: &Float64Array) {
+        if array.null_count() == 0 {
+            array.values().iter().for_each(|value| self.insert(*value));
+        } else {
+            array.iter().flatten().for_each(|value| self.insert(value));
+        }
+    }
+
+    /// Export the means and counts of the bins as [Apache Arrow] arrays.
+    ///
+    /// [Apache Arrow]: https://arrow.apache.org/
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arrow_array::{Float64Array, UInt64Array};
+    /// use histr::{Bin, StreamHist};
+    ///
+    /// let hist = StreamHist::from(vec![Bin::new(1.0, 3), Bin::new(2.0, 4)]);
+    /// let (means, counts) = hist.to_arrow_arrays();
+    ///
+    /// assert_eq!(means, Float64Array::from(vec![1.0, 2.0]));
+    /// assert_eq!(counts, UInt64Array::from(vec![3, 4]));
+    /// ```
+    pub fn to_arrow_arrays(&self) -> (Float64Array, UInt64Array) {
+        let (means, counts): (Vec<f64>, Vec<u64>) = self.iter().map(|bin| bin.into()).unzip();
+        (Float64Array::from(means), UInt64Array::from(counts))
+    }
+
+    /// Export the histogram as the [Apache Arrow] record batch with the `"mean"` and `"count"` columns.
+    ///
+    /// The `min`, `max`, and `size` of the histogram are stored in the metadata of the schema,
+    /// so the histogram can be restored using [`StreamHist::from_arrow`].
+    ///
+    /// [Apache Arrow]: https://arrow.apache.org/
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 5.0]);
+    /// let batch = hist.to_arrow();
+    ///
+    /// assert_eq!(batch.num_rows(), 3);
+    /// assert_eq!(StreamHist::from_arrow(&batch).unwrap(), hist);
+    /// ```
+    pub fn to_arrow(&self) -> RecordBatch {
+        let (means, counts) = self.to_arrow_arrays();
+        let metadata = HashMap::from([
+            ("min".to_string(), self.min.to_string()),
+            ("max".to_string(), self.max.to_string()),
+            ("size".to_string(), self.size.to_string()),
+        ]);
+        let schema = Schema::new(vec![
+            Field::new("mean", DataType::Float64, false),
+            Field::new("count", DataType::UInt64, false),
+        ])
+        .with_metadata(metadata);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(means) as ArrayRef, Arc::new(counts) as ArrayRef],
+        )
+        .expect("the columns match the schema")
+    }
+
+    /// Read the histogram from the [Apache Arrow] record batch with the `"mean"` and `"count"` columns.
+    ///
+    /// The `min`, `max`, and `size` are read from the metadata of the schema, as written by
+    /// [`StreamHist::to_arrow`]. When they are missing, the `min` and `max` are the smallest and largest
+    /// means, and the `size` is the number of the rows. The bins do not need to be sorted.
+    ///
+    /// [Apache Arrow]: https://arrow.apache.org/
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if any of the columns is missing, has a different type, contains nulls,
+    /// or the histogram is not valid, see [`StreamHist::try_from_parts`].
+    pub fn from_arrow(batch: &RecordBatch) -> Result<Self, ArrowError> {
+        let means: &Float64Array = column(batch, "mean")?;
+        let counts: &UInt64Array = column(batch, "count")?;
+        if means.null_count() > 0 || counts.null_count() > 0 {
+            return Err(ArrowError::InvalidArgumentError(
+                "the bins cannot be null".to_string(),
+            ));
+        }
+        if means.values().iter().any(|mean| !mean.is_finite()) {
+            return Err(ArrowError::InvalidArgumentError(
+                "the means of the bins are not numbers".to_string(),
+            ));
+        }
+        let mut bins: Vec<Bin> = means
+            .values()
+            .iter()
+            .zip(counts.values().iter())
+            .map(|(mean, count)| Bin::new(*mean, *count))
+            .collect();
+        bins.sort();
+
+        let metadata = batch.schema_ref().metadata();
+        let parse = |key: &str| metadata.get(key).map(|value| value.parse::<f64>());
+        let invalid = || ArrowError::ParseError("invalid histogram metadata".to_string());
+        let min = match parse("min") {
+            Some(min) => min.map_err(|_| invalid())?,
+            None => bins.first().map_or(f64::NAN, |bin| bin.mean),
+        };
+        let max = match parse("max") {
+            Some(max) => max.map_err(|_| invalid())?,
+            None => bins.last().map_or(f64::NAN, |bin| bin.mean),
+        };
+        let size = match metadata.get("size") {
+            Some(size) => size.parse().map_err(|_| invalid())?,
+            None => bins.len(),
+        };
+        StreamHist::try_from_parts(bins, min, max, size)
+            .map_err(|err| ArrowError::InvalidArgumentError(err.to_string()))
+    }
+}
+
+/// The column of the `batch` with the `name`, downcast to the array of type `T`.
+fn column<'a, T: Array + 'static>(batch: &'a RecordBatch, name: &str) -> Result<&'a T, ArrowError> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| ArrowError::SchemaError(format!("column {name} is missing")))?
+        .as_any()
+        .downcast_ref::<T>()
+        .ok_or_else(|| ArrowError::SchemaError(format!("column {name} has invalid type")))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bins::Bin;
+    use crate::hist::StreamHist;
+    use arrow_array::{ArrayRef, Float64Array, RecordBatch, UInt64Array};
+    use std::sync::Arc;
+
+    #[test]
+    fn extend_from_arrow() {
+        let mut hist = StreamHist::with_capacity(5);
+        hist.extend_from_arrow(&Float64Array::from(vec![3.0, 1.0, 2.0]));
+        hist.extend_from_arrow(&Float64Array::from(vec![None, Some(4.0), None]));
+        hist.extend_from_arrow(&Float64Array::from(Vec::<f64>::new()));
+
+        let mut expected = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0]);
+        expected.resize(5);
+        assert_eq!(hist, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn extend_from_arrow_nan() {
+        StreamHist::with_capacity(5).extend_from_arrow(&Float64Array::from(vec![f64::NAN]));
+    }
+
+    #[test]
+    fn to_arrow_arrays() {
+        let (means, counts) = StreamHist::default().to_arrow_arrays();
+        assert!(means.is_empty());
+        assert!(counts.is_empty());
+
+        let mut hist = StreamHist::from(vec![1.0, 1.0, 2.0, 5.0]);
+        hist.resize(2);
+        let (means, counts) = hist.to_arrow_arrays();
+        assert_eq!(means, Float64Array::from(vec![4.0 / 3.0, 5.0]));
+        assert_eq!(counts, UInt64Array::from(vec![3, 1]));
+    }
+
+    #[test]
+    fn to_from_arrow() {
+        for hist in [
+            StreamHist::try_from_parts(vec![Bin::new(1.5, 2), Bin::new(4.0, 1)], 0.5, 4.0, 5)
+                .unwrap(),
+            StreamHist::with_capacity(3),
+        ] {
+            let batch = hist.to_arrow();
+            assert_eq!(StreamHist::from_arrow(&batch).unwrap(), hist);
+        }
+    }
+
+    #[test]
+    fn from_arrow_without_metadata() {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "mean",
+                Arc::new(Float64Array::from(vec![3.0, 1.0])) as ArrayRef,
+            ),
+            ("count", Arc::new(UInt64Array::from(vec![2, 5])) as ArrayRef),
+        ])
+        .unwrap();
+        assert_eq!(
+            StreamHist::from_arrow(&batch).unwrap(),
+            StreamHist::from(vec![Bin::new(1.0, 5), Bin::new(3.0, 2)])
+        );
+    }
+
+    #[test]
+    fn from_arrow_invalid() {
+        let means = Arc::new(Float64Array::from(vec![1.0, 2.0])) as ArrayRef;
+        for batch in [
+            RecordBatch::try_from_iter(vec![("mean", means.clone())]).unwrap(),
+            RecordBatch::try_from_iter(vec![("mean", means.clone()), ("count", means.clone())])
+                .unwrap(),
+            RecordBatch::try_from_iter(vec![
+                ("mean", means.clone()),
+                (
+                    "count",
+                    Arc::new(UInt64Array::from(vec![Some(1), None])) as ArrayRef,
+                ),
+            ])
+            .unwrap(),
+            RecordBatch::try_from_iter(vec![
+                ("mean", means.clone()),
+                ("count", Arc::new(UInt64Array::from(vec![1, 0])) as ArrayRef),
+            ])
+            .unwrap(),
+            RecordBatch::try_from_iter(vec![
+                (
+                    "mean",
+                    Arc::new(Float64Array::from(vec![1.0, f64::NAN])) as ArrayRef,
+                ),
+                ("count", Arc::new(UInt64Array::from(vec![1, 1])) as ArrayRef),
+            ])
+            .unwrap(),
+        ] {
+            assert!(StreamHist::from_arrow(&batch).is_err());
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::hist::StreamHist;
+use std::io;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+impl StreamHist {
+    /// Asynchronously read the values from the `reader` line by line and insert them to the histogram.
+    ///
+    /// The same as [`StreamHist::read_field`] using the first field of each line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let mut hist = StreamHist::with_capacity(5);
+    /// let input: &[u8] = b"1.0\n2.0\nfoo\n3.0\n";
+    /// let inserted = hist.read_values(input).await.unwrap();
+    ///
+    /// assert_eq!(inserted, 3);
+    /// assert_eq!(hist.mean(), 2.0);
+    /// # });
+    /// ```
+    pub async fn read_values<R>(&mut self, reader: R) -> io::Result<usize>
+    where
+        R: AsyncBufRead + Unpin,
+    {
+        self.read_field(reader, 0).await
+    }
+
+    /// Asynchronously read the values from the `reader` line by line and insert them to the histogram.
+    ///
+    /// The lines are parsed with [`parse_field`], the lines that cannot be parsed are skipped.
+    /// It returns the number of the inserted values.
+    ///
+    /// # Errors
+    ///
+    /// It returns the I/O errors of the `reader`.
+    pub async fn read_field<R>(&mut self, reader: R, index: usize) -> io::Result<usize>
+    where
+        R: AsyncBufRead + Unpin,
+    {
+        let mut lines = reader.lines();
+        let mut inserted = 0;
+        while let Some(line) = lines.next_line().await? {
+            if let Some(value) = parse_field(&line, index) {
+                self.insert(value);
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+}
+
+/// Parse the field at `index` position of the `line` as a number, where the fields are whitespace separated.
+///
+/// It returns `None` if there is no such field, it could not be parsed as `f64`,
+/// or the parsed value is `f64::NAN` or infinite.
+///
+/// # Examples
+///
+/// ```
+/// use histr::parse_field;
+///
+/// assert_eq!(parse_field("3.14 25.13 31 42", 3), Some(42.0));
+/// assert_eq!(parse_field("3.14 25.13 31 42", 4), None);
+/// assert_eq!(parse_field("NaN", 0), None);
+/// ```
+pub fn parse_field(line: &str, index: usize) -> Option<f64> {
+    line.split_whitespace()
+        .nth(index)?
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_field;
+    use crate::hist::StreamHist;
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_field("0.00001", 0), Some(0.00001));
+        assert_eq!(parse_field("1.5 25.13 31 42", 0), Some(1.5));
+        assert_eq!(parse_field("", 0), None);
+        assert_eq!(parse_field("1 2 3", 5), None);
+        assert_eq!(parse_field("inf", 0), None);
+        assert_eq!(parse_field("1 2 3efg7", 2), None);
+    }
+
+    #[tokio::test]
+    async fn read_field() {
+        let input: &[u8] = b"a 1\nb 2\nc\nd x\ne 3\n";
+        let mut hist = StreamHist::with_capacity(5);
+        assert_eq!(hist.read_field(input, 1).await.unwrap(), 3);
+
+        let mut expected = StreamHist::from(vec![1.0, 2.0, 3.0]);
+        expected.resize(5);
+        assert_eq!(hist, expected);
+    }
+}
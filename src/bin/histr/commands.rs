@@ -0,0 +1,111 @@
+#![cfg(feature = "build-binary")]
+
+use crate::{format_value, is_json, print_statistics_table, read_histogram};
+use clap::Subcommand;
+use histr::{distance, SizePolicy, StreamHist};
+use std::error::Error;
+use std::fs::{self, File};
+
+/// The quantiles compared by the `diff` command.
+const DIFF_QUANTILES: [f64; 7] = [0.01, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99];
+
+/// Commands working on the saved histograms, instead of reading the input.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Compare two saved histograms (MessagePack unless the file extension is .json): print their statistics
+    /// side by side, the differences between the quantiles, and the distances between the distributions
+    Diff {
+        /// The first histogram
+        first: String,
+        /// The second histogram, the differences are calculated as second minus first
+        second: String,
+    },
+    /// Merge the saved histograms (MessagePack unless the file extension is .json, the formats can be mixed)
+    /// and save the result
+    Merge {
+        /// Save the merged histogram to a file at the given path (MessagePack unless the file extension is .json)
+        output: String,
+        /// The histograms to merge
+        #[arg(required = true)]
+        inputs: Vec<String>,
+        /// The number of bins of the merged histogram, by default the largest of the merged histograms
+        #[arg(short = 'b', long, value_name = "NUMBER")]
+        number_of_bins: Option<usize>,
+    },
+}
+
+impl Command {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        match self {
+            Command::Diff { first, second } => diff(first, second),
+            Command::Merge {
+                output,
+                inputs,
+                number_of_bins,
+            } => merge(output, inputs, *number_of_bins),
+        }
+    }
+}
+
+/// Print the comparison of the histograms saved at the paths.
+fn diff(first: &str, second: &str) -> Result<(), Box<dyn Error>> {
+    let a = load(first)?;
+    let b = load(second)?;
+
+    print_statistics_table(&[(first, &a), (second, &b)]);
+    println!();
+
+    println!(
+        "{:8} {:>12} {:>12} {:>12}",
+        "Quantile", "first", "second", "difference"
+    );
+    for prob in DIFF_QUANTILES {
+        let (x, y) = (a.quantile(prob), b.quantile(prob));
+        println!(
+            "{:8} {:>12} {:>12} {:>12}",
+            format!("{}%", prob * 100.0),
+            format_value(x),
+            format_value(y),
+            format_value(y - x)
+        );
+    }
+    println!();
+
+    println!(
+        "{:18} {}",
+        "Kolmogorov-Smirnov",
+        format_value(distance::kolmogorov_smirnov(&a, &b))
+    );
+    println!(
+        "{:18} {}",
+        "Wasserstein",
+        format_value(distance::wasserstein(&a, &b))
+    );
+    Ok(())
+}
+
+/// Merge the histograms saved at the `inputs` paths and save the result at the `output` path.
+fn merge(output: &str, inputs: &[String], size: Option<usize>) -> Result<(), Box<dyn Error>> {
+    let policy = size.map_or(SizePolicy::KeepLarger, SizePolicy::Fixed);
+    let mut merged = StreamHist::with_capacity(size.unwrap_or(0));
+    for path in inputs {
+        merged.merge_with(load(path)?, policy);
+    }
+
+    // write to a temporary file first, so the output is not left truncated on failure
+    let tmp_path = format!("{}.tmp", output);
+    let file = &mut File::create(&tmp_path).map_err(Box::new)?;
+    if is_json(output) {
+        merged.write_json(file)?;
+    } else {
+        merged.write_msgpack(file)?;
+    }
+    file.sync_all().map_err(Box::new)?;
+    fs::rename(&tmp_path, output).map_err(Box::new)?;
+    Ok(())
+}
+
+/// Read the histogram, see [`read_histogram`], adding the path to the error message.
+fn load(path: &str) -> Result<StreamHist, Box<dyn Error>> {
+    read_histogram(path).map_err(|err| format!("failed to read {}: {}", path, err).into())
+}
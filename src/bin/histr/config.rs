@@ -0,0 +1,216 @@
+#![cfg(feature = "build-binary")]
+
+use crate::Args;
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+/// Defaults for the command line arguments read from a TOML file.
+///
+/// The arguments given explicitly in the command line take precedence over the ones from the file.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The number of bins
+    pub bins: Option<usize>,
+    /// Maximal width of the histogram bars when displayed
+    pub width: Option<u32>,
+    /// Use the nth field (column) of the input
+    pub field: Option<usize>,
+    /// Print the quantiles for the probabilities
+    #[serde(default, deserialize_with = "deserialize_probabilities")]
+    pub quantiles: Option<Vec<f64>>,
+    /// The field delimiter for the CSV input
+    pub delimiter: Option<char>,
+    /// Use the columns of the CSV input with the labels given in the header row
+    #[serde(alias = "labels")]
+    pub columns: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Read the config from the TOML file.
+    pub fn read(path: &str) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path).map_err(Box::new)?;
+        let config = toml::from_str(&content).map_err(Box::new)?;
+        Ok(config)
+    }
+
+    /// Read the config from the default location (`$XDG_CONFIG_HOME/histr/config.toml` or
+    /// `$HOME/.config/histr/config.toml`), use an empty config if the file does not exist.
+    pub fn read_default() -> Result<Self, Box<dyn Error>> {
+        match default_path() {
+            Some(path) if path.is_file() => Config::read(&path.to_string_lossy()),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// Overwrite the arguments that were not given explicitly in the command line with the values from the config.
+    pub fn apply(&self, args: &mut Args, matches: &ArgMatches) {
+        if let Some(bins) = self.bins.filter(|_| is_default(matches, "number_of_bins")) {
+            args.number_of_bins = bins;
+        }
+        if let Some(width) = self.width.filter(|_| is_default(matches, "width")) {
+            args.width = width;
+        }
+        if let Some(field) = self.field.filter(|_| is_default(matches, "field")) {
+            args.field = vec![field];
+        }
+        if let Some(ref quantiles) = self.quantiles {
+            if is_default(matches, "quantile") {
+                args.quantile.clone_from(quantiles);
+            }
+        }
+        if let Some(delimiter) = self.delimiter.filter(|_| is_default(matches, "delimiter")) {
+            args.delimiter = delimiter;
+        }
+        // the columns are used only for the CSV input, and instead of the fields given in the command line
+        if let Some(ref columns) = self.columns {
+            if args.csv
+                && is_default(matches, "column")
+                && is_default(matches, "field")
+                && is_default(matches, "all_fields")
+            {
+                args.column.clone_from(columns);
+            }
+        }
+    }
+}
+
+/// Deserialize the probabilities, checking that they are between 0 and 1.
+fn deserialize_probabilities<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<f64>>, D::Error> {
+    let probs = Option::<Vec<f64>>::deserialize(deserializer)?;
+    if let Some(prob) = probs
+        .iter()
+        .flatten()
+        .find(|prob| !(0.0..=1.0).contains(*prob))
+    {
+        return Err(D::Error::custom(format!(
+            "{prob} is not a valid probability"
+        )));
+    }
+    Ok(probs)
+}
+
+/// The argument was not given in the command line.
+fn is_default(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) != Some(ValueSource::CommandLine)
+}
+
+fn default_path() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("histr").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+    use crate::Args;
+    use clap::{CommandFactory, FromArgMatches};
+
+    #[test]
+    fn parse() {
+        assert_eq!(toml::from_str::<Config>("").unwrap(), Config::default());
+        assert_eq!(
+            toml::from_str::<Config>("bins = 20\nwidth = 30\nfield = 2").unwrap(),
+            Config {
+                bins: Some(20),
+                width: Some(30),
+                field: Some(2),
+                ..Config::default()
+            }
+        );
+        assert_eq!(
+            toml::from_str::<Config>(
+                "quantiles = [0.5, 0.99]\ndelimiter = \";\"\nlabels = [\"latency\"]"
+            )
+            .unwrap(),
+            Config {
+                quantiles: Some(vec![0.5, 0.99]),
+                delimiter: Some(';'),
+                columns: Some(vec![String::from("latency")]),
+                ..Config::default()
+            }
+        );
+        assert!(toml::from_str::<Config>("unknown = 1").is_err());
+        assert!(toml::from_str::<Config>("bins = -1").is_err());
+        assert!(toml::from_str::<Config>("quantiles = [1.5]").is_err());
+        assert!(toml::from_str::<Config>("delimiter = \"ab\"").is_err());
+    }
+
+    #[test]
+    fn apply() {
+        let config = Config {
+            bins: Some(20),
+            width: Some(30),
+            ..Config::default()
+        };
+        let matches = Args::command().get_matches_from(["histr", "-b", "5"]);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        config.apply(&mut args, &matches);
+
+        // given explicitly
+        assert_eq!(args.number_of_bins, 5);
+        // from the config
+        assert_eq!(args.width, 30);
+        // the default
+        assert_eq!(args.field, vec![1]);
+    }
+
+    #[test]
+    fn apply_csv() {
+        let config = Config {
+            quantiles: Some(vec![0.5, 0.99]),
+            delimiter: Some(';'),
+            columns: Some(vec![String::from("latency")]),
+            ..Config::default()
+        };
+        let apply = |argv: &[&str]| {
+            let matches = Args::command().get_matches_from(argv);
+            let mut args = Args::from_arg_matches(&matches).unwrap();
+            config.apply(&mut args, &matches);
+            args
+        };
+
+        // from the config
+        let args = apply(&["histr", "--csv"]);
+        assert_eq!(args.quantile, vec![0.5, 0.99]);
+        assert_eq!(args.delimiter, ';');
+        assert_eq!(args.column, vec![String::from("latency")]);
+
+        // given explicitly
+        let args = apply(&[
+            "histr",
+            "--csv",
+            "--quantile",
+            "0.9",
+            "--delimiter",
+            "|",
+            "--column",
+            "size",
+        ]);
+        assert_eq!(args.quantile, vec![0.9]);
+        assert_eq!(args.delimiter, '|');
+        assert_eq!(args.column, vec![String::from("size")]);
+
+        // the fields given explicitly are used instead of the columns
+        let args = apply(&["histr", "--csv", "-f", "2"]);
+        assert!(args.column.is_empty());
+        assert_eq!(args.field, vec![2]);
+        let args = apply(&["histr", "--csv", "--all-fields"]);
+        assert!(args.column.is_empty());
+
+        // the columns are used only for the CSV input
+        let args = apply(&["histr"]);
+        assert!(args.column.is_empty());
+    }
+}
@@ -1,24 +1,57 @@
 #![cfg(feature = "build-binary")]
+mod commands;
+mod config;
 mod parse;
+mod stats;
 
-use crate::parse::parse;
+use crate::commands::Command;
+use crate::config::Config;
+use crate::parse::{
+    find_columns, parse_duration, parse_fields, parse_frequency, parse_probability, parse_record,
+    Frequency, ParsedFields, ParsingError, Transform, Validation,
+};
+use crate::stats::{write_json_file, Drift, Queries, Report, Statistics};
 use clap::error::ErrorKind;
-use clap::{CommandFactory, Parser};
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
 use float_pretty_print::PrettyPrintFloat;
+use histr::{Bin, CategoryHist, KernelDensity, StreamHist};
+use plotters::prelude::{ChartBuilder, IntoDrawingArea, SVGBackend, WHITE};
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::error::Error;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
-use histr::{Bin, StreamHist};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::time::{Duration, Instant};
 
 const IO_ERROR_CODE: i32 = 74;
+const DRIFT_EXIT_CODE: i32 = 1;
+/// How long to wait for the new data at the end of the followed file.
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// The input path for reading from stdin.
+const STDIN: &str = "-";
+/// ANSI escape codes for clearing the terminal and moving the cursor to the top.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+/// The number of rows of the printed kernel density.
+const DENSITY_ROWS: usize = 20;
+/// The partial blocks used for drawing the density bars with the resolution of 1/8 of a character.
+const PARTIAL_BLOCKS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
 
 /// Streaming histogram
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The number of bins
     #[arg(short = 'b', long, default_value_t = 10, value_name = "NUMBER")]
     number_of_bins: usize,
 
+    /// The number of the distinct values counted for the non-numeric fields, when there are more of them
+    /// only the most frequent ones are kept
+    #[arg(long, default_value_t = 100, value_name = "NUMBER")]
+    max_categories: usize,
+
     /// Do a forced resize of the histogram to the number of bins given by the `-n` argument
     #[arg(short = 'r', long, default_value_t = false)]
     force_resize: bool,
@@ -31,9 +64,69 @@ struct Args {
     #[arg(short, long, value_name = "PATH")]
     output_file: Option<String>,
 
-    /// Use the nth field (column) of the input, where the fields are assumed to be separated with whitespaces
-    #[arg(short, long, default_value_t = 1, value_name = "NUMBER")]
-    field: usize,
+    /// Periodically save the histogram to a file at the given path while reading the input
+    #[arg(long, value_name = "PATH")]
+    checkpoint: Option<String>,
+
+    /// How often to save the checkpoint, e.g. 30s, 5m, or 1h
+    #[arg(long, default_value = "60s", value_name = "DURATION", value_parser = parse_duration)]
+    checkpoint_interval: Duration,
+
+    /// Save the checkpoint every N lines, or when the duration with a unit passed (e.g. 500ms, 30s, 5m, or 1h),
+    /// to the --checkpoint file or else to the --output-file. A bare whole number is always the number of lines,
+    /// the durations need the unit
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = parse_frequency,
+        conflicts_with = "checkpoint_interval"
+    )]
+    checkpoint_every: Option<Frequency>,
+
+    /// Use the nth field (column) of the input, where the fields are assumed to be separated with whitespaces.
+    /// Several fields can be given separated with commas (e.g. 2,5,7), with a histogram per each of them
+    #[arg(
+        short,
+        long,
+        default_value = "1",
+        value_name = "NUMBERS",
+        value_delimiter = ','
+    )]
+    field: Vec<usize>,
+
+    /// Use all the fields (columns) of the input, with a histogram per each of them
+    #[arg(long, default_value_t = false, conflicts_with = "field")]
+    all_fields: bool,
+
+    /// Multiply the values by the number before inserting them to the histogram
+    #[arg(long, default_value_t = 1.0, value_name = "NUMBER")]
+    scale: f64,
+
+    /// Add the number to the values (after scaling them) before inserting them to the histogram
+    #[arg(long, default_value_t = 0.0, value_name = "NUMBER")]
+    offset: f64,
+
+    /// Transform the values (after scaling and shifting them) before inserting them to the histogram,
+    /// the values that are not valid after the transformation (e.g. the logarithm of zero) are skipped
+    #[arg(long, value_enum, value_name = "FUNCTION")]
+    transform: Option<Transform>,
+
+    /// Read the input as CSV, where the fields are separated by the delimiter and can be quoted
+    #[arg(long, default_value_t = false, conflicts_with = "follow")]
+    csv: bool,
+
+    /// The field delimiter for the CSV input
+    #[arg(long, default_value_t = ',', value_name = "CHAR", requires = "csv")]
+    delimiter: char,
+
+    /// Use the column of the CSV input with the name given in the header row, can be used several times
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "csv",
+        conflicts_with_all = ["field", "all_fields"]
+    )]
+    column: Vec<String>,
 
     /// Print JSON of the histogram
     #[arg(short, long, default_value_t = false)]
@@ -43,28 +136,214 @@ struct Args {
     #[arg(short, long, default_value_t = false)]
     statistics: bool,
 
+    /// Print the quantiles for the probabilities separated with commas (e.g. 0.5,0.95,0.99),
+    /// with --json they are printed as JSON instead of the histogram
+    #[arg(long, value_name = "PROBS", value_delimiter = ',', value_parser = parse_probability)]
+    quantile: Vec<f64>,
+
+    /// Print the values of the cumulative distribution function at the points separated with commas (e.g. 100,500),
+    /// with --json they are printed as JSON instead of the histogram
+    #[arg(long, value_name = "VALUES", value_delimiter = ',')]
+    cdf: Vec<f64>,
+
+    /// Write the statistics as a JSON to a file at the given path
+    #[arg(long, value_name = "PATH")]
+    stats_file: Option<String>,
+
+    /// Compare the histogram to the reference histogram from the file (MessagePack unless the file extension is .json)
+    #[arg(long, value_name = "PATH")]
+    baseline: Option<String>,
+
+    /// Exit with an error code when the population stability index (PSI) compared to the baseline exceeds the threshold,
+    /// or cannot be computed because any of the histograms is empty
+    #[arg(long, default_value_t = 0.2, value_name = "NUMBER")]
+    psi_threshold: f64,
+
+    /// The number of buckets used for calculating the population stability index (PSI)
+    #[arg(long, default_value_t = 10, value_name = "NUMBER")]
+    psi_buckets: usize,
+
     /// Don't print the summary of the histogram
     #[arg(short, long, default_value_t = false)]
     no_summary: bool,
 
+    /// How to print the bins of the histogram: as a table with the bars, or in the machine-readable formats
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table, value_name = "FORMAT")]
+    output_format: OutputFormat,
+
+    /// Print the kernel density estimated from the histogram
+    #[arg(long, default_value_t = false)]
+    density: bool,
+
+    /// Save the plot of the histogram and the kernel density as an SVG file at the given path
+    #[arg(long, value_name = "PATH")]
+    svg: Option<String>,
+
     /// Maximal width of the histogram bars when displayed
     #[arg(short, long, default_value_t = 10, value_name = "NUMBER")]
     width: u32,
 
+    /// Read the defaults for the number of bins, width, field, quantiles, and the CSV delimiter and columns
+    /// from the TOML file (by default $XDG_CONFIG_HOME/histr/config.toml if it exists)
+    #[arg(long, value_name = "PATH")]
+    config: Option<String>,
+
     /// Don't update the histogram (ignore FILE and stdin)
     #[arg(short, long, default_value_t = false)]
     ignore_input: bool,
 
-    /// Input data file, if not given, the input is read from stdin
-    file: Option<String>,
+    /// Only parse the input and report how many lines would be accepted or skipped, without creating a histogram
+    #[arg(long, default_value_t = false)]
+    validate: bool,
+
+    /// Keep reading the input as the data arrives (like `tail -f`), periodically re-drawing the histogram
+    #[arg(long, default_value_t = false)]
+    follow: bool,
+
+    /// How often to re-draw the histogram in the follow mode, e.g. 500ms, 1s, or 1m
+    #[arg(long, default_value = "1s", value_name = "DURATION", value_parser = parse_duration)]
+    refresh_interval: Duration,
+
+    /// Input data files read one after another, if not given (or `-`), the input is read from stdin
+    files: Vec<String>,
+}
+
+/// Format of the printed bins.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    /// Table with the bars
+    Table,
+    /// Comma-separated values with a header
+    Csv,
+    /// Tab-separated values with a header
+    Tsv,
+    /// JSON object per line
+    Jsonl,
+}
+
+/// Histograms of the fields of the input, indexed by the field numbers (starting at 1).
+///
+/// When a single field is used, the files contain the histogram, otherwise the maps
+/// of the field numbers to the histograms.
+type Histograms = BTreeMap<usize, StreamHist>;
+
+/// Counts of the non-numeric values of the fields, indexed by the field numbers (starting at 1).
+///
+/// The field is treated as categorical when more of its values failed to parse as numbers than
+/// were parsed, see [`is_categorical`].
+type Categories = BTreeMap<usize, CategoryHist<String>>;
+
+/// The field has more of the non-numeric values than the numeric ones.
+fn is_categorical(field: usize, hists: &Histograms, cats: &Categories) -> bool {
+    let numeric = hists.get(&field).map_or(0.0, StreamHist::count);
+    cats.get(&field)
+        .is_some_and(|cat| cat.count() as f64 > numeric)
+}
+
+/// Keep either the histogram or the counts of the categories per each field, see [`is_categorical`].
+fn split_categories(hists: &mut Histograms, cats: &mut Categories) {
+    let categorical: Vec<usize> = cats
+        .keys()
+        .copied()
+        .filter(|field| is_categorical(*field, hists, cats))
+        .collect();
+    hists.retain(|field, _| !categorical.contains(field));
+    cats.retain(|field, _| categorical.contains(field));
+}
+
+/// The histogram or the counts of the categories of a field, as written to the files.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Summary<'a> {
+    Numeric(&'a StreamHist),
+    Categorical(&'a CategoryHist<String>),
+}
+
+/// The summaries of all the fields, see [`is_categorical`].
+fn summaries<'a>(hists: &'a Histograms, cats: &'a Categories) -> BTreeMap<usize, Summary<'a>> {
+    let numeric = hists
+        .iter()
+        .filter(|(field, _)| !is_categorical(**field, hists, cats))
+        .map(|(field, hist)| (*field, Summary::Numeric(hist)));
+    let categorical = cats
+        .iter()
+        .filter(|(field, _)| is_categorical(**field, hists, cats))
+        .map(|(field, cat)| (*field, Summary::Categorical(cat)));
+    numeric.chain(categorical).collect()
 }
 
-/// Initialize the histogram based on the provided arguments: fresh or from a file.
-fn initialize_histogram(args: &Args) -> Result<StreamHist, Box<dyn Error>> {
+impl Args {
+    /// Whether there is a histogram per each of the multiple fields.
+    fn multiple_fields(&self) -> bool {
+        self.all_fields || self.field.len() > 1 || self.column.len() > 1
+    }
+
+    /// The quantiles or the values of the cumulative distribution function were requested.
+    fn has_queries(&self) -> bool {
+        !self.quantile.is_empty() || !self.cdf.is_empty()
+    }
+
+    /// Scale, shift, and transform the parsed value, see `--scale`, `--offset`, and `--transform`.
+    fn transform(&self, value: f64) -> Result<f64, ParsingError> {
+        let value = value * self.scale + self.offset;
+        let value = self.transform.map_or(value, |t| t.apply(value));
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(ParsingError::NotANumber(value))
+        }
+    }
+
+    /// Path of the file where the checkpoints are saved, if enabled.
+    fn checkpoint_path(&self) -> Option<&str> {
+        match self.checkpoint {
+            Some(ref path) => Some(path),
+            None => self.checkpoint_every.and(self.output_file.as_deref()),
+        }
+    }
+
+    /// Paths of the input files, `-` for stdin.
+    fn input_paths(&self) -> Vec<&str> {
+        if self.files.is_empty() {
+            return vec![STDIN];
+        }
+        self.files.iter().map(String::as_str).collect()
+    }
+
+    /// The numbers of the fields to use, or `None` for all the fields.
+    fn fields(&self) -> Option<Vec<usize>> {
+        (!self.all_fields).then(|| self.field.clone())
+    }
+}
+
+/// Initialize the histograms based on the provided arguments: fresh or from a file.
+///
+/// With `--all-fields`, the histograms are created when the fields are found in the input.
+fn initialize_histograms(args: &Args) -> Result<Histograms, Box<dyn Error>> {
     if let Some(ref from) = args.load_from {
-        return read_histogram(from);
+        if args.multiple_fields() {
+            return read_histograms(from);
+        }
+        return Ok(Histograms::from([(args.field[0], read_histogram(from)?)]));
+    }
+    if args.all_fields {
+        return Ok(Histograms::new());
+    }
+    Ok(args
+        .field
+        .iter()
+        .map(|field| (*field, StreamHist::with_capacity(args.number_of_bins)))
+        .collect())
+}
+
+/// Read the histograms of multiple fields from a file, see [`read_histogram`].
+fn read_histograms(path: &str) -> Result<Histograms, Box<dyn Error>> {
+    let file = BufReader::new(File::open(path).map_err(Box::new)?);
+    if is_json(path) {
+        Ok(serde_json::from_reader(file).map_err(Box::new)?)
+    } else {
+        Ok(rmp_serde::from_read(file).map_err(Box::new)?)
     }
-    Ok(StreamHist::with_capacity(args.number_of_bins))
 }
 
 /// Read histogram from a file:
@@ -79,43 +358,452 @@ fn read_histogram(path: &str) -> Result<StreamHist, Box<dyn Error>> {
     }
 }
 
-/// Read the data from a file (if provided) or stdin and use it to update the histogram.
-fn read_data(hist: &mut StreamHist, args: &Args) -> io::Result<()> {
-    // A file or stdin
-    let input: Box<dyn Read> = match &args.file {
-        Some(path) => Box::new(File::open(path)?),
-        None => Box::new(io::stdin()),
-    };
-    for (index, line) in BufReader::new(input).lines().enumerate() {
-        match parse(line?, args.field - 1) {
-            Ok(value) => hist.insert(value),
-            // on parsing failure ignore this line and print warning to stderr
-            Err(err) => eprintln!("line {}: {}", index + 1, err),
+/// The input file (or stdin).
+struct Input {
+    /// Path of the file, `-` for stdin.
+    path: String,
+    reader: Reader,
+}
+
+/// Reader of the lines with whitespace separated fields, or the CSV records.
+enum Reader {
+    Lines(BufReader<Box<dyn Read>>),
+    Csv(csv::Reader<Box<dyn Read>>),
+}
+
+impl Input {
+    /// Open the input file at the `path`, or stdin for `-`.
+    fn open(path: &str, args: &Args) -> Result<Self, Box<dyn Error>> {
+        let input: Box<dyn Read> = if path == STDIN {
+            Box::new(io::stdin())
+        } else {
+            Box::new(File::open(path).map_err(|err| format!("{}: {}", path, err))?)
+        };
+        let reader = if args.csv {
+            Reader::Csv(
+                csv::ReaderBuilder::new()
+                    .delimiter(args.delimiter as u8)
+                    .has_headers(!args.column.is_empty())
+                    .flexible(true)
+                    .trim(csv::Trim::All)
+                    .from_reader(input),
+            )
+        } else {
+            Reader::Lines(BufReader::new(input))
+        };
+        Ok(Input {
+            path: path.to_string(),
+            reader,
+        })
+    }
+
+    /// Read the header of the CSV input and find the numbers of the fields for the column `names`.
+    fn columns(&mut self, names: &[String]) -> Result<Vec<usize>, Box<dyn Error>> {
+        match self.reader {
+            Reader::Csv(ref mut reader) => {
+                let header = reader
+                    .headers()
+                    .map_err(|err| format!("{}: {}", self.path, err))?;
+                Ok(find_columns(header, names)?)
+            }
+            Reader::Lines(_) => unreachable!("the columns are used only for CSV"),
+        }
+    }
+
+    /// Iterate over the parsed fields of the lines (or records), paired with the line indexes.
+    fn records(self, args: &Args) -> Box<dyn Iterator<Item = io::Result<(usize, ParsedFields)>>> {
+        let fields = args.fields();
+        match self.reader {
+            Reader::Lines(reader) => Box::new(
+                reader
+                    .lines()
+                    .enumerate()
+                    .map(move |(index, line)| Ok((index, parse_fields(&line?, fields.as_deref())))),
+            ),
+            Reader::Csv(reader) => {
+                Box::new(
+                    reader
+                        .into_records()
+                        .enumerate()
+                        .map(move |(index, record)| {
+                            let record = record?;
+                            // the records can span multiple lines, or follow the header
+                            let index = record
+                                .position()
+                                .map_or(index, |pos| pos.line() as usize - 1);
+                            Ok((index, parse_record(&record, fields.as_deref())))
+                        }),
+                )
+            }
+        }
+    }
+}
+
+/// Open the first input, for the CSV input with the columns selected by names, read the header
+/// and set the fields to the numbers of the columns.
+fn open_first_input(args: &mut Args) -> Result<Input, Box<dyn Error>> {
+    let mut input = Input::open(args.input_paths()[0], args)?;
+    if !args.column.is_empty() {
+        args.field = input.columns(&args.column)?;
+    }
+    Ok(input)
+}
+
+/// The already opened `first` input followed by the remaining inputs, opened when they are needed.
+///
+/// The columns of the CSV inputs need to be at the same positions as in the first input.
+fn inputs(first: Input, args: &Args) -> impl Iterator<Item = Result<Input, Box<dyn Error>>> + '_ {
+    let rest = args.input_paths().into_iter().skip(1).map(|path| {
+        let mut input = Input::open(path, args)?;
+        if !args.column.is_empty() && input.columns(&args.column)? != args.field {
+            return Err(format!("{}: the columns are at different positions", path).into());
+        }
+        Ok(input)
+    });
+    std::iter::once(Ok(first)).chain(rest)
+}
+
+/// Read the data from the inputs and use it to update the histograms.
+fn read_data(
+    hists: &mut Histograms,
+    cats: &mut Categories,
+    first: Input,
+    args: &Args,
+) -> Result<(), Box<dyn Error>> {
+    let mut checkpoint = args
+        .checkpoint_path()
+        .map(|path| Checkpoint::new(path, args));
+    for input in inputs(first, args) {
+        let input = input?;
+        let path = input.path.clone();
+        for record in input.records(args) {
+            let (index, fields) = record.map_err(|err| format!("{}: {}", path, err))?;
+            insert_fields(hists, cats, &path, index, fields, args);
+            if let Some(ref mut checkpoint) = checkpoint {
+                checkpoint.update(hists, cats);
+            }
+        }
+    }
+    if let Some(ref mut checkpoint) = checkpoint {
+        checkpoint.save(hists, cats);
+    }
+    Ok(())
+}
+
+/// Read the data as it arrives and periodically re-draw the histogram, see [`read_data`].
+///
+/// The file is followed until the process is stopped, while stdin until it is closed.
+fn follow_data(
+    hists: &mut Histograms,
+    cats: &mut Categories,
+    path: &str,
+    mut input: BufReader<Box<dyn Read>>,
+    args: &Args,
+) -> io::Result<()> {
+    let stdin = path == STDIN;
+    let fields = args.fields();
+    let mut checkpoint = args
+        .checkpoint_path()
+        .map(|path| Checkpoint::new(path, args));
+    let mut last_render = Instant::now();
+    let mut updated = false;
+    let mut line = String::new();
+    let mut index = 0;
+    loop {
+        if input.read_line(&mut line)? == 0 {
+            if stdin {
+                break;
+            }
+            // wait for the rest of the data to be written to the file
+            if updated {
+                render(hists, cats, args);
+                (last_render, updated) = (Instant::now(), false);
+            }
+            std::thread::sleep(FOLLOW_POLL_INTERVAL);
+            continue;
+        }
+        if !line.ends_with('\n') && !stdin {
+            // the line is not fully written yet
+            continue;
+        }
+        insert_fields(
+            hists,
+            cats,
+            path,
+            index,
+            parse_fields(&line, fields.as_deref()),
+            args,
+        );
+        line.clear();
+        index += 1;
+        updated = true;
+
+        if let Some(ref mut checkpoint) = checkpoint {
+            checkpoint.update(hists, cats);
         }
+        if last_render.elapsed() >= args.refresh_interval {
+            render(hists, cats, args);
+            (last_render, updated) = (Instant::now(), false);
+        }
+    }
+    if let Some(ref mut checkpoint) = checkpoint {
+        checkpoint.save(hists, cats);
     }
     Ok(())
 }
 
-/// Write the histogram to a file:
+/// Insert the parsed fields of the line to the histograms, on parsing failure ignore the field
+/// and print warning to stderr.
+///
+/// The values that are not numbers are counted as the categories, and the warnings are not printed
+/// for the categorical fields, see [`is_categorical`].
+fn insert_fields(
+    hists: &mut Histograms,
+    cats: &mut Categories,
+    path: &str,
+    index: usize,
+    fields: ParsedFields,
+    args: &Args,
+) {
+    for (field, result) in fields {
+        match result.and_then(|value| args.transform(value)) {
+            Ok(value) => hists
+                .entry(field)
+                .or_insert_with(|| StreamHist::with_capacity(args.number_of_bins))
+                .insert(value),
+            Err(ParsingError::Failed(value)) => {
+                cats.entry(field)
+                    .or_insert_with(|| CategoryHist::with_capacity(args.max_categories))
+                    .insert(value.clone());
+                if !is_categorical(field, hists, cats) {
+                    print_parsing_error(path, index, field, &ParsingError::Failed(value), args);
+                }
+            }
+            Err(err) => print_parsing_error(path, index, field, &err, args),
+        }
+    }
+}
+
+/// Print the warning about the field that was not parsed to stderr, with the file `path` for multiple files.
+fn print_parsing_error(path: &str, index: usize, field: usize, err: &ParsingError, args: &Args) {
+    let mut location = format!("line {}", index + 1);
+    if args.files.len() > 1 {
+        location = format!("{}, {}", path, location);
+    }
+    if args.multiple_fields() {
+        location = format!("{}, field {}", location, field);
+    }
+    eprintln!("{}: {}", location, err);
+}
+
+/// Re-draw the histograms and the statistics in place of the previous ones.
+fn render(hists: &Histograms, cats: &Categories, args: &Args) {
+    print!("{}", CLEAR_SCREEN);
+    print_summaries(hists, cats, args);
+    let _ = io::stdout().flush();
+}
+
+/// Print the histograms and the statistics (if enabled), with a block per field for multiple fields,
+/// followed by the table of the statistics with a row per field.
+///
+/// In the machine-readable output formats, the bins of all the histograms are printed together
+/// (with the field numbers for multiple fields), before the statistics. The categorical fields
+/// are always printed as the tables of the most frequent values.
+fn print_summaries(hists: &Histograms, cats: &Categories, args: &Args) {
+    let summaries = summaries(hists, cats);
+    let table = args.output_format == OutputFormat::Table;
+    if !table && !args.no_summary {
+        print_bins_header(args.output_format, args.multiple_fields());
+        for (field, summary) in &summaries {
+            if let Summary::Numeric(hist) = summary {
+                let field = args.multiple_fields().then_some(*field);
+                print_bins(hist, field, args.output_format);
+            }
+        }
+    }
+    let queries = args.has_queries() && !args.json;
+    let has_categories = summaries
+        .values()
+        .any(|summary| matches!(summary, Summary::Categorical(_)));
+    // for multiple fields, the statistics are printed as a single table after the blocks of the fields
+    let statistics_table = args.statistics && args.multiple_fields();
+    let blocks = (table || has_categories) && !args.no_summary
+        || args.density
+        || args.statistics && !statistics_table
+        || queries;
+    if blocks {
+        for (i, (field, summary)) in summaries.iter().enumerate() {
+            if args.multiple_fields() {
+                if i > 0 {
+                    println!();
+                }
+                println!("Field {}", field);
+            }
+            let hist = match summary {
+                Summary::Numeric(hist) => hist,
+                Summary::Categorical(cat) => {
+                    if !args.no_summary {
+                        print_categories(cat, args.width);
+                    }
+                    continue;
+                }
+            };
+            if table && !args.no_summary {
+                print_histogram(hist, args.width);
+            }
+            if args.density {
+                print_density(hist, args.width);
+            }
+            if args.statistics && !statistics_table {
+                print_statistics(hist);
+            }
+            if queries {
+                print_queries(hist, args);
+            }
+        }
+    }
+    if statistics_table {
+        if blocks {
+            println!();
+        }
+        let rows: Vec<(String, &StreamHist)> = summaries
+            .iter()
+            .filter_map(|(field, summary)| match summary {
+                Summary::Numeric(hist) => Some((format!("Field {}", field), *hist)),
+                Summary::Categorical(_) => None,
+            })
+            .collect();
+        let rows: Vec<(&str, &StreamHist)> = rows
+            .iter()
+            .map(|(label, hist)| (label.as_str(), *hist))
+            .collect();
+        print_statistics_table(&rows);
+    }
+}
+
+/// Parse the data and count the accepted and skipped lines.
+fn validate_data(first: Input, args: &Args) -> Result<Validation, Box<dyn Error>> {
+    let mut validation = Validation::default();
+    for input in inputs(first, args) {
+        let input = input?;
+        let path = input.path.clone();
+        for record in input.records(args) {
+            let (index, fields) = record.map_err(|err| format!("{}: {}", path, err))?;
+            for (field, result) in fields {
+                let result = result.and_then(|value| args.transform(value));
+                if let Err(ref err) = result {
+                    print_parsing_error(&path, index, field, err, args);
+                }
+                validation.update(&result);
+            }
+        }
+    }
+    Ok(validation)
+}
+
+/// Periodically saves the histograms to a file while the input is being read.
+struct Checkpoint<'a> {
+    path: &'a str,
+    every: Frequency,
+    multiple: bool,
+    last_saved: Instant,
+    /// The number of lines read since the histograms were last saved.
+    lines: usize,
+}
+
+impl<'a> Checkpoint<'a> {
+    fn new(path: &'a str, args: &Args) -> Self {
+        Checkpoint {
+            path,
+            every: args
+                .checkpoint_every
+                .unwrap_or(Frequency::Interval(args.checkpoint_interval)),
+            multiple: args.multiple_fields(),
+            last_saved: Instant::now(),
+            lines: 0,
+        }
+    }
+
+    /// Count the line that was read and save the histograms if it is time for the next checkpoint.
+    fn update(&mut self, hists: &Histograms, cats: &Categories) {
+        self.lines += 1;
+        let due = match self.every {
+            Frequency::Lines(lines) => self.lines >= lines,
+            Frequency::Interval(interval) => self.last_saved.elapsed() >= interval,
+        };
+        if due {
+            self.save(hists, cats);
+        }
+    }
+
+    /// Save the histograms, on failure print a warning to stderr and keep going.
+    fn save(&mut self, hists: &Histograms, cats: &Categories) {
+        if let Err(err) = write(hists, cats, self.path, self.multiple) {
+            eprintln!("failed to save the checkpoint: {}", err);
+        }
+        self.last_saved = Instant::now();
+        self.lines = 0;
+    }
+}
+
+/// Write the histograms to a file:
 /// * when the file extension is .json (case-insensitive) as a JSON,
 /// * otherwise as a MessagePack.
-fn write(hist: &StreamHist, path: &str) -> Result<(), Box<dyn Error>> {
-    let file = &mut File::create(path).map_err(Box::new)?;
-    if is_json(path) {
-        hist.write_json(file)
+///
+/// The histograms are written to a temporary file that is renamed to `path` afterwards,
+/// so that the file at `path` is never left partially written.
+fn write(
+    hists: &Histograms,
+    cats: &Categories,
+    path: &str,
+    multiple: bool,
+) -> Result<(), Box<dyn Error>> {
+    let tmp_path = format!("{}.tmp", path);
+    let file = &mut File::create(&tmp_path).map_err(Box::new)?;
+    write_to(hists, cats, file, is_json(path), multiple)?;
+    file.sync_all().map_err(Box::new)?;
+    fs::rename(&tmp_path, path).map_err(Box::new)?;
+    Ok(())
+}
+
+/// Write the histogram (or the counts of the categories) of the single field, or the map of them
+/// for `multiple` fields.
+fn write_to<W>(
+    hists: &Histograms,
+    cats: &Categories,
+    writer: &mut W,
+    json: bool,
+    multiple: bool,
+) -> Result<(), Box<dyn Error>>
+where
+    W: Write,
+{
+    let summaries = summaries(hists, cats);
+    if !multiple {
+        return match summaries.values().next() {
+            Some(Summary::Numeric(hist)) if json => hist.write_json(writer),
+            Some(Summary::Numeric(hist)) => hist.write_msgpack(writer),
+            Some(Summary::Categorical(cat)) if json => cat.write_json(writer),
+            Some(Summary::Categorical(cat)) => cat.write_msgpack(writer),
+            None => unreachable!("the histogram of the field is initialized"),
+        };
+    }
+    if json {
+        serde_json::to_writer(writer, &summaries).map_err(Box::new)?;
     } else {
-        hist.write_msgpack(file)
+        rmp_serde::encode::write(writer, &summaries).map_err(Box::new)?;
     }
+    Ok(())
 }
 
 fn is_json(path: &str) -> bool {
     path.to_lowercase().ends_with(".json")
 }
 
-/// Print JSON for the histogram.
-fn print_json(hist: &StreamHist) -> Result<(), Box<dyn Error>> {
+/// Print JSON for the histograms.
+fn print_json(hists: &Histograms, cats: &Categories, multiple: bool) -> Result<(), Box<dyn Error>> {
     let stdout = &mut io::stdout().lock();
-    hist.write_json(stdout)
+    write_to(hists, cats, stdout, true, multiple)
 }
 
 /// Format the bin mean, count, and histogram bar as a string.
@@ -133,6 +821,27 @@ fn bin_to_string(bin: &Bin, max_count: u64, width: u32) -> String {
     format!("{:8.3} {}\t{}", PrettyPrintFloat(mean), count, bar)
 }
 
+/// Print the counts of the most frequent values of the categorical field as a text plot.
+fn print_categories(cat: &CategoryHist<String>, width: u32) {
+    let mut top = cat.top(cat.len());
+    // the ties are sorted by the values, so the output is reproducible
+    top.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.cmp(b)));
+    let max_count = top.first().map_or(0, |(_, count)| *count);
+
+    println!("value\tcount");
+    for (value, count) in top {
+        let relative_count = count as f32 / max_count as f32;
+        let bar = "■".repeat((relative_count * width as f32).round() as usize);
+        println!("{:>8} {}\t{}", value, count, bar);
+    }
+    if !cat.is_exact() {
+        println!(
+            "(approximate counts of the {} most frequent values)",
+            cat.len()
+        );
+    }
+}
+
 /// Print the histogram as text plot.
 fn print_histogram(hist: &StreamHist, width: u32) {
     let max_count = hist.iter().fold(0, |acc, bin| {
@@ -147,37 +856,294 @@ fn print_histogram(hist: &StreamHist, width: u32) {
     }
 }
 
+/// The range where the kernel density is evaluated: the range of the values extended by the bandwidth,
+/// or `None` if the density cannot be calculated (e.g. for an empty histogram).
+fn density_range(hist: &StreamHist, kde: &KernelDensity) -> Option<(f64, f64)> {
+    let (from, to) = (hist.min() - kde.bandwidth, hist.max() + kde.bandwidth);
+    (from.is_finite() && to.is_finite() && from < to).then_some((from, to))
+}
+
+/// Print the kernel density as a curve, with the bars showing the density at the equally spaced points.
+fn print_density(hist: &StreamHist, width: u32) {
+    let kde = KernelDensity::from(hist.clone());
+    let Some((from, to)) = density_range(hist, &kde) else {
+        return;
+    };
+    let grid = kde.evaluate_grid(from, to, DENSITY_ROWS);
+    let max_density = grid.iter().fold(0.0, |acc: f64, (_, y)| acc.max(*y));
+
+    println!("value\tdensity");
+    for (x, y) in grid {
+        // the bar width in 1/8 of a character
+        let eighths = (y / max_density * width as f64 * 8.0).round() as usize;
+        let mut bar = "█".repeat(eighths / 8);
+        if let Some(block) = (eighths % 8).checked_sub(1) {
+            bar.push(PARTIAL_BLOCKS[block]);
+        }
+        println!(
+            "{:8.3} {:<8.3}\t{}",
+            PrettyPrintFloat(x),
+            PrettyPrintFloat(y),
+            bar
+        );
+    }
+}
+
+/// Draw the histogram with the kernel density on top of it and save the plot as an SVG file.
+fn write_svg(hist: &StreamHist, path: &str) -> Result<(), Box<dyn Error>> {
+    let kde = KernelDensity::from(hist.clone());
+    let (from, to) = density_range(hist, &kde).ok_or("cannot plot an empty histogram")?;
+    let max_count = hist.iter().fold(0, |acc, bin| {
+        let (_, count) = bin.into();
+        acc.max(count)
+    });
+    let max_density = kde
+        .evaluate_grid(from, to, DENSITY_ROWS * 10)
+        .iter()
+        .fold(0.0, |acc: f64, (_, y)| acc.max(*y));
+
+    let root = SVGBackend::new(path, (640, 480)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(40)
+        .build_cartesian_2d(from..to, 0.0..max_count as f64 * 1.1)?;
+    chart.configure_mesh().draw()?;
+    hist.draw(&mut chart)?;
+
+    // the density is drawn on the same area, but scaled to its own range
+    let mut chart = ChartBuilder::on(&root)
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(40)
+        .build_cartesian_2d(from..to, 0.0..max_density * 1.1)?;
+    kde.draw(&mut chart)?;
+    root.present()?;
+    Ok(())
+}
+
+/// The bin printed as a JSON line, with the field number for multiple fields.
+#[derive(Serialize)]
+struct BinRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<usize>,
+    mean: f64,
+    count: u64,
+}
+
+/// Print the header of the bins in the machine-readable format, see [`print_bins`].
+fn print_bins_header(format: OutputFormat, multiple: bool) {
+    let sep = match format {
+        OutputFormat::Csv => ",",
+        OutputFormat::Tsv => "\t",
+        OutputFormat::Table | OutputFormat::Jsonl => return,
+    };
+    if multiple {
+        print!("field{}", sep);
+    }
+    println!("mean{}count", sep);
+}
+
+/// Print the bins in the machine-readable format, a row per bin, starting with the `field` number if given.
+fn print_bins(hist: &StreamHist, field: Option<usize>, format: OutputFormat) {
+    let sep = match format {
+        OutputFormat::Csv => ",",
+        OutputFormat::Tsv => "\t",
+        OutputFormat::Table => unreachable!("the table is printed by print_histogram"),
+        OutputFormat::Jsonl => {
+            for bin in hist.iter() {
+                let (mean, count) = bin.into();
+                let record = BinRecord { field, mean, count };
+                println!(
+                    "{}",
+                    serde_json::to_string(&record).expect("bin can be serialized")
+                );
+            }
+            return;
+        }
+    };
+    for bin in hist.iter() {
+        let (mean, count) = bin.into();
+        if let Some(field) = field {
+            print!("{}{}", field, sep);
+        }
+        println!("{}{}{}", mean, sep, count);
+    }
+}
+
 /// Print the summary statistics.
 fn print_statistics(hist: &StreamHist) {
-    for (name, value) in [
-        ("Mean", hist.mean()),
-        ("StDev", hist.stdev()),
-        ("Min", hist.min),
-        ("25% quantile", hist.quantile(0.25)),
-        ("Median", hist.median()),
-        ("75% quantile", hist.quantile(0.75)),
-        ("Max", hist.max),
-    ] {
+    let stats = Statistics::from(hist);
+    for (name, value) in stats.named() {
         println!("{:14} {:<8.3}", name, PrettyPrintFloat(value));
     }
-    println!("{:14} {:<8.0}", "Sample size", hist.count());
+    println!("{:14} {:<8.0}", "Sample size", stats.count);
+}
+
+/// Print the requested quantiles and the values of the cumulative distribution function.
+fn print_queries(hist: &StreamHist, args: &Args) {
+    let queries = Queries::new(hist, &args.quantile, &args.cdf);
+    for (name, value) in queries.named() {
+        println!("{:14} {:<8.3}", name, PrettyPrintFloat(value));
+    }
+}
+
+/// Print JSON for the requested quantiles and the values of the cumulative distribution function,
+/// for multiple fields as a map of the field numbers to the results.
+fn print_queries_json(hists: &Histograms, args: &Args) -> Result<(), Box<dyn Error>> {
+    let stdout = io::stdout().lock();
+    let queries = |hist| Queries::new(hist, &args.quantile, &args.cdf);
+    if args.multiple_fields() {
+        let queries: BTreeMap<usize, Queries> = hists
+            .iter()
+            .map(|(field, hist)| (*field, queries(hist)))
+            .collect();
+        serde_json::to_writer(stdout, &queries).map_err(Box::new)?;
+    } else {
+        let hist = &hists[&args.field[0]];
+        serde_json::to_writer(stdout, &queries(hist)).map_err(Box::new)?;
+    }
+    Ok(())
+}
+
+/// Print the summary statistics of several histograms as a table with a row per histogram,
+/// e.g. per each of the multiple fields, or for the input and the baseline.
+fn print_statistics_table(rows: &[(&str, &StreamHist)]) {
+    let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+
+    let mut header = format!("{:label_width$}", "");
+    for (name, _) in Statistics::from(&StreamHist::default()).named() {
+        header.push_str(&format!(" {:>12}", name));
+    }
+    println!("{} {:>12}", header, "Sample size");
+
+    for (label, hist) in rows {
+        let stats = Statistics::from(*hist);
+        let mut line = format!("{:label_width$}", label);
+        for (_, value) in stats.named() {
+            line.push_str(&format!(" {:>12}", format_value(value)));
+        }
+        println!("{} {:>12}", line, stats.count);
+    }
+}
+
+/// Format the value for the tables, with the same precision as the statistics.
+fn format_value(value: f64) -> String {
+    format!("{:8.3}", PrettyPrintFloat(value))
+        .trim()
+        .to_string()
 }
 
 /// Parse and validate the CLI arguments
 fn parse_args() -> Args {
-    let args = Args::parse();
-    if args.field < 1 {
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+    let config = match args.config {
+        Some(ref path) => Config::read(path),
+        None => Config::read_default(),
+    };
+    match config {
+        Ok(config) => config.apply(&mut args, &matches),
+        Err(err) => {
+            let mut cmd = Args::command();
+            cmd.error(ErrorKind::Io, format!("failed to read the config: {}", err))
+                .exit();
+        }
+    }
+
+    if args.field.iter().any(|field| *field < 1) {
         let mut cmd = Args::command();
         cmd.error(ErrorKind::InvalidValue, "field index needs to start at 1")
             .exit();
     }
+    if args.baseline.is_some() && args.multiple_fields() {
+        let mut cmd = Args::command();
+        cmd.error(
+            ErrorKind::ArgumentConflict,
+            "baseline can be used only with a single field",
+        )
+        .exit();
+    }
+    if args.checkpoint_every.is_some() && args.checkpoint_path().is_none() {
+        let mut cmd = Args::command();
+        cmd.error(
+            ErrorKind::MissingRequiredArgument,
+            "checkpoint needs --checkpoint or --output-file path",
+        )
+        .exit();
+    }
+    if args.follow && args.files.len() > 1 {
+        let mut cmd = Args::command();
+        cmd.error(
+            ErrorKind::ArgumentConflict,
+            "only a single file can be followed",
+        )
+        .exit();
+    }
+    if args.svg.is_some() && args.multiple_fields() {
+        let mut cmd = Args::command();
+        cmd.error(
+            ErrorKind::ArgumentConflict,
+            "SVG plot can be saved only for a single field",
+        )
+        .exit();
+    }
+    if !args.delimiter.is_ascii() {
+        let mut cmd = Args::command();
+        cmd.error(
+            ErrorKind::InvalidValue,
+            "delimiter needs to be an ASCII character",
+        )
+        .exit();
+    }
+    if args.psi_buckets < 1 {
+        let mut cmd = Args::command();
+        cmd.error(
+            ErrorKind::InvalidValue,
+            "number of PSI buckets needs to be positive",
+        )
+        .exit();
+    }
     args
 }
 
 fn main() {
-    let args = parse_args();
+    let mut args = parse_args();
+
+    if let Some(ref command) = args.command {
+        if let Err(err) = command.run() {
+            eprintln!("{}", err);
+            std::process::exit(IO_ERROR_CODE);
+        }
+        return;
+    }
+
+    // the input is opened before initializing the histograms, since for the CSV input
+    // the fields can be given by the column names from the header
+    let input = (args.validate || !args.ignore_input).then(|| {
+        open_first_input(&mut args)
+            .map_err(|err| {
+                eprintln!("failed to read the input: {}", err);
+                std::process::exit(IO_ERROR_CODE);
+            })
+            .unwrap()
+    });
 
-    let mut hist = initialize_histogram(&args)
+    if args.validate {
+        let input = input.expect("the input is opened for validation");
+        match validate_data(input, &args) {
+            Ok(validation) => println!("{}", validation),
+            Err(err) => {
+                eprintln!("failed to read the input: {}", err);
+                std::process::exit(IO_ERROR_CODE);
+            }
+        }
+        return;
+    }
+
+    let mut hists = initialize_histograms(&args)
         .map_err(|err| {
             eprintln!("failed to initialize the histogram: {}", err);
             std::process::exit(IO_ERROR_CODE);
@@ -185,34 +1151,144 @@ fn main() {
         .unwrap();
 
     if args.force_resize {
-        hist.resize(args.number_of_bins);
+        hists
+            .values_mut()
+            .for_each(|hist| hist.resize(args.number_of_bins));
     }
 
-    if !&args.ignore_input {
-        // Skip a histogram update regardless of the input
-        if let Err(err) = read_data(&mut hist, &args) {
+    // Skip a histogram update regardless of the input
+    let mut cats = Categories::new();
+    if let Some(input) = input {
+        let result = match input {
+            Input {
+                path,
+                reader: Reader::Lines(reader),
+            } if args.follow => {
+                follow_data(&mut hists, &mut cats, &path, reader, &args).map_err(Box::from)
+            }
+            input => read_data(&mut hists, &mut cats, input, &args),
+        };
+        if let Err(err) = result {
             eprintln!("failed to read the input: {}", err);
             std::process::exit(IO_ERROR_CODE);
         }
     }
+    split_categories(&mut hists, &mut cats);
+
+    let numeric_only = args.baseline.is_some()
+        || args.svg.is_some()
+        || args.stats_file.is_some()
+        || args.has_queries() && args.json;
+    if numeric_only && !args.multiple_fields() && !cats.is_empty() {
+        eprintln!(
+            "field {} is not numeric, it cannot be compared, plotted, or summarized by the statistics",
+            args.field[0]
+        );
+        std::process::exit(IO_ERROR_CODE);
+    }
 
+    // the baseline can be used only with a single field
+    let baseline = args.baseline.as_ref().map(|path| {
+        let baseline = read_histogram(path)
+            .map_err(|err| {
+                eprintln!("failed to read the baseline histogram: {}", err);
+                std::process::exit(IO_ERROR_CODE);
+            })
+            .unwrap();
+        (&hists[&args.field[0]], baseline)
+    });
+    let drift = baseline
+        .as_ref()
+        .map(|(hist, baseline)| Drift::new(hist, baseline, args.psi_buckets, args.psi_threshold));
+
+    if args.follow {
+        // the final results replace the last re-drawn histogram
+        print!("{}", CLEAR_SCREEN);
+    }
     if args.json {
-        if let Err(err) = print_json(&hist) {
+        let result = if args.has_queries() {
+            print_queries_json(&hists, &args)
+        } else {
+            print_json(&hists, &cats, args.multiple_fields())
+        };
+        if let Err(err) = result {
             eprintln!("failed to print JSON: {}", err);
             std::process::exit(IO_ERROR_CODE);
         }
     }
-    if !args.no_summary {
-        print_histogram(&hist, args.width);
+    match baseline {
+        Some((hist, ref baseline)) => {
+            if !args.no_summary {
+                if args.output_format == OutputFormat::Table {
+                    print_histogram(hist, args.width);
+                } else {
+                    print_bins_header(args.output_format, false);
+                    print_bins(hist, None, args.output_format);
+                }
+            }
+            if args.statistics {
+                print_statistics_table(&[("input", hist), ("baseline", baseline)]);
+                if let Some(ref drift) = drift {
+                    println!("PSI {:.3}", PrettyPrintFloat(drift.psi));
+                }
+            }
+            if args.density {
+                print_density(hist, args.width);
+            }
+            if args.has_queries() && !args.json {
+                print_queries(hist, &args);
+            }
+        }
+        None => print_summaries(&hists, &cats, &args),
+    }
+
+    let exceeded = match drift {
+        Some(ref drift) if drift.psi.is_nan() => {
+            eprintln!("PSI cannot be computed, the histogram or the baseline is empty");
+            true
+        }
+        Some(ref drift) if drift.exceeded => {
+            eprintln!(
+                "PSI {:.3} exceeds the threshold {}",
+                PrettyPrintFloat(drift.psi),
+                drift.threshold
+            );
+            true
+        }
+        _ => false,
+    };
+
+    if let Some(ref path) = args.stats_file {
+        let result = if args.multiple_fields() {
+            let reports: BTreeMap<usize, Report> = hists
+                .iter()
+                .map(|(field, hist)| (*field, Report::new(hist, None)))
+                .collect();
+            write_json_file(&reports, path)
+        } else {
+            Report::new(&hists[&args.field[0]], drift).write(path)
+        };
+        if let Err(err) = result {
+            eprintln!("failed to write the statistics: {}", err);
+            std::process::exit(IO_ERROR_CODE);
+        }
     }
-    if args.statistics {
-        print_statistics(&hist);
+
+    if let Some(ref path) = args.svg {
+        if let Err(err) = write_svg(&hists[&args.field[0]], path) {
+            eprintln!("failed to save the plot: {}", err);
+            std::process::exit(IO_ERROR_CODE);
+        }
     }
 
-    if let Some(path) = args.output_file {
-        if let Err(err) = write(&hist, &path) {
+    if let Some(ref path) = args.output_file {
+        if let Err(err) = write(&hists, &cats, path, args.multiple_fields()) {
             eprintln!("failed to write the output: {}", err);
             std::process::exit(IO_ERROR_CODE);
         }
     }
+
+    if exceeded {
+        std::process::exit(DRIFT_EXIT_CODE);
+    }
 }
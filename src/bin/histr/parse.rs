@@ -1,12 +1,16 @@
 #![cfg(feature = "build-binary")]
 
+use clap::ValueEnum;
+use csv::StringRecord;
 use std::fmt;
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub enum ParsingError {
     NotANumber(f64),
     Failed(String),
     Missing,
+    UnknownColumn(String),
 }
 
 impl fmt::Display for ParsingError {
@@ -18,6 +22,7 @@ impl fmt::Display for ParsingError {
                 write!(f, "parsing {} failed", line)
             }
             Missing => write!(f, "nothing to read"),
+            UnknownColumn(name) => write!(f, "column {} not found in the header", name),
         }
     }
 }
@@ -25,26 +30,72 @@ impl fmt::Display for ParsingError {
 impl PartialEq for ParsingError {
     fn eq(&self, other: &Self) -> bool {
         use ParsingError::*;
-        matches!(
-            (self, other),
-            (NotANumber(_), NotANumber(_)) | (Missing, Missing) | (Failed(_), Failed(_))
-        )
+        match (self, other) {
+            (UnknownColumn(a), UnknownColumn(b)) => a == b,
+            _ => matches!(
+                (self, other),
+                (NotANumber(_), NotANumber(_)) | (Missing, Missing) | (Failed(_), Failed(_))
+            ),
+        }
     }
 }
 
-/// Parse the value at `index` position as a double.
-///
-/// # Arguments
-/// * `line` - String input to be parser
-/// * `index` - Index of the field in `line`, where the fields are whitespace separated
+impl std::error::Error for ParsingError {}
+
+/// The parsed values of the fields, paired with the field numbers.
+pub type ParsedFields = Vec<(usize, Result<f64, ParsingError>)>;
+
+/// Parse the values of the `fields` (indexed from one) of the `line`, where the fields are whitespace separated.
+/// When `fields` is `None`, all the fields are parsed.
 ///
 /// # Errors
 ///
-/// It will throw error in two cases:
+/// The results for the fields are errors in three cases:
+/// * The field is missing.
 /// * It was not able to parse the string as a `f64` number.
 /// * The parsed value is `f64::NAN` or infinite.
-pub fn parse(line: String, index: usize) -> Result<f64, ParsingError> {
-    if let Some(field) = line.split_whitespace().nth(index) {
+pub fn parse_fields(line: &str, fields: Option<&[usize]>) -> ParsedFields {
+    let values: Vec<&str> = line.split_whitespace().collect();
+    parse_values(&values, fields)
+}
+
+/// Parse the values of the `fields` (indexed from one) of the CSV `record`, see [`parse_fields`].
+pub fn parse_record(record: &StringRecord, fields: Option<&[usize]>) -> ParsedFields {
+    let values: Vec<&str> = record.iter().collect();
+    parse_values(&values, fields)
+}
+
+fn parse_values(values: &[&str], fields: Option<&[usize]>) -> ParsedFields {
+    match fields {
+        Some(fields) => fields
+            .iter()
+            .map(|field| (*field, parse_field(values.get(field - 1).copied())))
+            .collect(),
+        None => values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (index + 1, parse_field(Some(value))))
+            .collect(),
+    }
+}
+
+/// Find the numbers of the fields (indexed from one) for the column `names` in the CSV `header`.
+pub fn find_columns(header: &StringRecord, names: &[String]) -> Result<Vec<usize>, ParsingError> {
+    names
+        .iter()
+        .map(|name| {
+            header
+                .iter()
+                .position(|column| column == name)
+                .map(|index| index + 1)
+                .ok_or_else(|| ParsingError::UnknownColumn(name.clone()))
+        })
+        .collect()
+}
+
+/// Parse the field as a double, see [`parse_fields`]. The empty fields (e.g. in CSV) are treated as missing.
+fn parse_field(field: Option<&str>) -> Result<f64, ParsingError> {
+    if let Some(field) = field.filter(|field| !field.is_empty()) {
         match field.parse::<f64>() {
             Ok(value) => {
                 if value.is_nan() || value.is_infinite() {
@@ -59,9 +110,145 @@ pub fn parse(line: String, index: usize) -> Result<f64, ParsingError> {
     }
 }
 
+/// Transformation applied to the parsed values before inserting them to the histogram.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    /// Natural logarithm
+    Log,
+    /// Base 10 logarithm
+    Log10,
+    /// Base 2 logarithm
+    Log2,
+    /// Absolute value
+    Abs,
+    /// Square root
+    Sqrt,
+}
+
+impl Transform {
+    pub fn apply(self, value: f64) -> f64 {
+        use Transform::*;
+        match self {
+            Log => value.ln(),
+            Log10 => value.log10(),
+            Log2 => value.log2(),
+            Abs => value.abs(),
+            Sqrt => value.sqrt(),
+        }
+    }
+}
+
+/// Counts of the accepted and skipped input lines.
+#[derive(Debug, Default, PartialEq)]
+pub struct Validation {
+    pub accepted: usize,
+    pub not_a_number: usize,
+    pub failed: usize,
+    pub missing: usize,
+}
+
+impl Validation {
+    /// Count the result of parsing a line.
+    pub fn update(&mut self, result: &Result<f64, ParsingError>) {
+        use ParsingError::*;
+        match result {
+            Ok(_) => self.accepted += 1,
+            Err(NotANumber(_)) => self.not_a_number += 1,
+            Err(Failed(_)) => self.failed += 1,
+            Err(Missing) => self.missing += 1,
+            Err(UnknownColumn(_)) => unreachable!("columns are found before parsing"),
+        }
+    }
+
+    /// The total number of lines read.
+    pub fn lines(&self) -> usize {
+        self.accepted + self.skipped()
+    }
+
+    /// The number of lines that would be ignored.
+    pub fn skipped(&self) -> usize {
+        self.not_a_number + self.failed + self.missing
+    }
+}
+
+impl fmt::Display for Validation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:20} {}", "Lines read", self.lines())?;
+        writeln!(f, "{:20} {}", "Accepted", self.accepted)?;
+        writeln!(f, "{:20} {}", "Skipped", self.skipped())?;
+        writeln!(f, "{:20} {}", "  not a number", self.not_a_number)?;
+        writeln!(f, "{:20} {}", "  failed to parse", self.failed)?;
+        write!(f, "{:20} {}", "  missing field", self.missing)
+    }
+}
+
+/// Parse duration given as a number followed by an optional unit: `ms`, `s` (default), `m`, or `h`.
+pub fn parse_duration(arg: &str) -> Result<Duration, String> {
+    let arg = arg.trim();
+    let split = arg
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(arg.len());
+    let (number, unit) = arg.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("{} is not a valid duration", arg))?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return Err(format!("unknown time unit {}", unit)),
+    };
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// How often to do something while reading the input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    /// Every given number of lines.
+    Lines(usize),
+    /// When the time passed.
+    Interval(Duration),
+}
+
+/// Parse the frequency given as a whole number of lines, or a duration with a unit (see [`parse_duration`]).
+///
+/// Unlike for [`parse_duration`], the unit is required, so that a number like `1.5` is not silently
+/// read as seconds rather than lines.
+pub fn parse_frequency(arg: &str) -> Result<Frequency, String> {
+    let arg = arg.trim();
+    match arg.parse::<usize>() {
+        Ok(0) => Err(String::from("number of lines needs to be positive")),
+        Ok(lines) => Ok(Frequency::Lines(lines)),
+        Err(_) if arg.ends_with(|c: char| c.is_ascii_digit() || c == '.') => Err(format!(
+            "{} is neither a whole number of lines nor a duration with a unit (e.g. 5s)",
+            arg
+        )),
+        Err(_) => parse_duration(arg).map(Frequency::Interval),
+    }
+}
+
+/// Parse the probability value between `0.0` and `1.0` (inclusive).
+pub fn parse_probability(arg: &str) -> Result<f64, String> {
+    match arg.trim().parse::<f64>() {
+        Ok(prob) if (0.0..=1.0).contains(&prob) => Ok(prob),
+        _ => Err(format!("{} is not a valid probability", arg)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{parse, ParsingError};
+    use super::{
+        find_columns, parse_duration, parse_fields, parse_frequency, parse_probability,
+        parse_record, Frequency, ParsingError, Transform, Validation,
+    };
+    use csv::StringRecord;
+    use std::time::Duration;
+
+    /// Parse the value at `index` position.
+    fn parse(line: String, index: usize) -> Result<f64, ParsingError> {
+        parse_fields(&line, Some(&[index + 1])).remove(0).1
+    }
 
     #[test]
     fn parse_ok() {
@@ -88,4 +275,122 @@ mod tests {
             Err(ParsingError::Failed(String::from("3efg7")))
         );
     }
+
+    #[test]
+    fn parse_many() {
+        assert_eq!(
+            parse_fields("1 abc 3", Some(&[3, 1, 5])),
+            vec![(3, Ok(3.0)), (1, Ok(1.0)), (5, Err(ParsingError::Missing))]
+        );
+        assert_eq!(
+            parse_fields("1 abc 3", None),
+            vec![
+                (1, Ok(1.0)),
+                (2, Err(ParsingError::Failed(String::from("abc")))),
+                (3, Ok(3.0))
+            ]
+        );
+        assert_eq!(parse_fields("", None), vec![]);
+    }
+
+    #[test]
+    fn parse_csv() {
+        let header = StringRecord::from(vec!["name", "value, in ms", "other"]);
+        let fields = find_columns(&header, &[String::from("value, in ms")]).unwrap();
+        assert_eq!(fields, vec![2]);
+        assert_eq!(
+            find_columns(&header, &[String::from("value")]),
+            Err(ParsingError::UnknownColumn(String::from("value")))
+        );
+
+        let record = StringRecord::from(vec!["a b", "1.5", ""]);
+        assert_eq!(parse_record(&record, Some(&fields)), vec![(2, Ok(1.5))]);
+        assert_eq!(
+            parse_record(&record, Some(&[1, 3])),
+            vec![
+                (1, Err(ParsingError::Failed(String::from("a b")))),
+                (3, Err(ParsingError::Missing))
+            ]
+        );
+    }
+
+    #[test]
+    fn transform() {
+        assert_eq!(Transform::Log.apply(1.0), 0.0);
+        assert_eq!(Transform::Log10.apply(1000.0), 3.0);
+        assert_eq!(Transform::Log2.apply(8.0), 3.0);
+        assert_eq!(Transform::Abs.apply(-2.5), 2.5);
+        assert_eq!(Transform::Sqrt.apply(16.0), 4.0);
+        assert_eq!(Transform::Log.apply(0.0), f64::NEG_INFINITY);
+        assert!(Transform::Sqrt.apply(-1.0).is_nan());
+    }
+
+    #[test]
+    fn validation() {
+        let mut validation = Validation::default();
+        for line in ["1.0", "2 3", "", "NaN", "inf", "abc", "4"] {
+            validation.update(&parse(String::from(line), 0));
+        }
+        assert_eq!(
+            validation,
+            Validation {
+                accepted: 3,
+                not_a_number: 2,
+                failed: 1,
+                missing: 1,
+            }
+        );
+        assert_eq!(validation.lines(), 7);
+        assert_eq!(validation.skipped(), 4);
+    }
+
+    #[test]
+    fn parse_duration_ok() {
+        assert_eq!(parse_duration("60"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_duration("60s"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1.5m"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn parse_duration_err() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("10 days").is_err());
+        assert!(parse_duration("-5s").is_err());
+    }
+
+    #[test]
+    fn parse_frequency_ok() {
+        assert_eq!(parse_frequency("1000"), Ok(Frequency::Lines(1000)));
+        assert_eq!(
+            parse_frequency("30s"),
+            Ok(Frequency::Interval(Duration::from_secs(30)))
+        );
+        assert_eq!(
+            parse_frequency("1.5s"),
+            Ok(Frequency::Interval(Duration::from_millis(1500)))
+        );
+        assert_eq!(
+            parse_frequency("500ms"),
+            Ok(Frequency::Interval(Duration::from_millis(500)))
+        );
+        // the durations need the unit
+        assert!(parse_frequency("1.5").is_err());
+        assert!(parse_frequency("5.0").is_err());
+        assert!(parse_frequency("0").is_err());
+        assert!(parse_frequency("often").is_err());
+    }
+
+    #[test]
+    fn parse_probability_ok() {
+        assert_eq!(parse_probability("0"), Ok(0.0));
+        assert_eq!(parse_probability("0.95"), Ok(0.95));
+        assert_eq!(parse_probability("1"), Ok(1.0));
+        assert!(parse_probability("1.5").is_err());
+        assert!(parse_probability("-0.1").is_err());
+        assert!(parse_probability("NaN").is_err());
+        assert!(parse_probability("half").is_err());
+    }
 }
@@ -0,0 +1,192 @@
+#![cfg(feature = "build-binary")]
+
+use histr::StreamHist;
+use serde::{Serialize, Serializer};
+use std::error::Error;
+use std::fs::File;
+
+/// Summary statistics of the histogram.
+#[derive(Debug, Serialize)]
+pub struct Statistics {
+    pub mean: f64,
+    pub stdev: f64,
+    pub min: f64,
+    pub q25: f64,
+    pub median: f64,
+    pub q75: f64,
+    pub max: f64,
+    pub count: f64,
+}
+
+impl From<&StreamHist> for Statistics {
+    fn from(hist: &StreamHist) -> Self {
+        Statistics {
+            mean: hist.mean(),
+            stdev: hist.stdev(),
+            min: hist.min(),
+            q25: hist.quantile(0.25),
+            median: hist.median(),
+            q75: hist.quantile(0.75),
+            max: hist.max(),
+            count: hist.count(),
+        }
+    }
+}
+
+impl Statistics {
+    /// Pairs of the human-readable names and the values of the statistics, excluding the sample size.
+    pub fn named(&self) -> [(&'static str, f64); 7] {
+        [
+            ("Mean", self.mean),
+            ("StDev", self.stdev),
+            ("Min", self.min),
+            ("25% quantile", self.q25),
+            ("Median", self.median),
+            ("75% quantile", self.q75),
+            ("Max", self.max),
+        ]
+    }
+}
+
+/// Comparison of the histogram to the baseline histogram.
+#[derive(Debug, Serialize)]
+pub struct Drift {
+    /// Population stability index.
+    pub psi: f64,
+    pub threshold: f64,
+    /// PSI exceeds the threshold, or it cannot be computed, e.g. for the empty histograms.
+    pub exceeded: bool,
+}
+
+impl Drift {
+    pub fn new(hist: &StreamHist, baseline: &StreamHist, buckets: usize, threshold: f64) -> Self {
+        let psi = hist.psi(baseline, buckets);
+        Drift {
+            psi,
+            threshold,
+            exceeded: psi.is_nan() || psi > threshold,
+        }
+    }
+}
+
+/// The quantiles and the values of the cumulative distribution function requested in the command line.
+#[derive(Debug, Serialize)]
+pub struct Queries {
+    /// Pairs of the probabilities and the quantiles.
+    #[serde(serialize_with = "serialize_pairs")]
+    pub quantiles: Vec<(f64, f64)>,
+    /// Pairs of the values and the cumulative probabilities.
+    #[serde(serialize_with = "serialize_pairs")]
+    pub cdf: Vec<(f64, f64)>,
+}
+
+impl Queries {
+    pub fn new(hist: &StreamHist, probs: &[f64], values: &[f64]) -> Self {
+        Queries {
+            quantiles: probs.iter().map(|p| (*p, hist.quantile(*p))).collect(),
+            cdf: values.iter().map(|x| (*x, hist.cdf(*x))).collect(),
+        }
+    }
+
+    /// Pairs of the human-readable names and the values of the queries.
+    pub fn named(&self) -> Vec<(String, f64)> {
+        let quantiles = self
+            .quantiles
+            .iter()
+            .map(|(p, q)| (format!("Quantile {}", p), *q));
+        let cdf = self.cdf.iter().map(|(x, p)| (format!("CDF {}", x), *p));
+        quantiles.chain(cdf).collect()
+    }
+}
+
+/// Serialize the pairs as a map, with the keys converted to strings.
+fn serialize_pairs<S: Serializer>(pairs: &[(f64, f64)], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(pairs.iter().map(|(key, value)| (key.to_string(), value)))
+}
+
+/// The content of the statistics file.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub statistics: Statistics,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drift: Option<Drift>,
+}
+
+impl Report {
+    pub fn new(hist: &StreamHist, drift: Option<Drift>) -> Self {
+        Report {
+            statistics: Statistics::from(hist),
+            drift,
+        }
+    }
+
+    /// Write the report as a JSON file, the missing values (NaNs) are written as nulls.
+    pub fn write(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        write_json_file(self, path)
+    }
+}
+
+/// Write the reports (see [`Report::write`]) or other values as a JSON file.
+pub fn write_json_file<T: Serialize>(value: &T, path: &str) -> Result<(), Box<dyn Error>> {
+    let file = File::create(path).map_err(Box::new)?;
+    serde_json::to_writer_pretty(file, value).map_err(Box::new)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Drift, Queries, Report};
+    use histr::StreamHist;
+
+    #[test]
+    fn report_to_json() {
+        let report = Report::new(&StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]), None);
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"statistics":{"mean":3.0,"stdev":1.4142135623730951,"min":1.0,"q25":1.75,"median":3.0,"q75":4.25,"max":5.0,"count":5.0}}"#
+        );
+
+        let report = Report::new(&StreamHist::default(), None);
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"statistics":{"mean":null,"stdev":null,"min":null,"q25":null,"median":null,"q75":null,"max":null,"count":0.0}}"#
+        );
+    }
+
+    #[test]
+    fn queries() {
+        let queries = Queries::new(
+            &StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]),
+            &[0.5, 1.0],
+            &[3.0],
+        );
+        assert_eq!(
+            serde_json::to_string(&queries).unwrap(),
+            r#"{"quantiles":{"0.5":3.0,"1":5.0},"cdf":{"3":0.5}}"#
+        );
+        assert_eq!(
+            queries.named(),
+            vec![
+                (String::from("Quantile 0.5"), 3.0),
+                (String::from("Quantile 1"), 5.0),
+                (String::from("CDF 3"), 0.5)
+            ]
+        );
+    }
+
+    #[test]
+    fn drift() {
+        let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        let drift = Drift::new(&hist, &hist, 10, 0.2);
+        assert_eq!(drift.psi, 0.0);
+        assert!(!drift.exceeded);
+
+        let shifted = StreamHist::from(vec![11.0, 12.0, 13.0, 14.0, 15.0]);
+        assert!(Drift::new(&shifted, &hist, 10, 0.2).exceeded);
+
+        // empty histograms cannot be compared, so they are reported as the drift
+        let drift = Drift::new(&StreamHist::default(), &hist, 10, 0.2);
+        assert!(drift.psi.is_nan());
+        assert!(drift.exceeded);
+    }
+}
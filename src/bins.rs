@@ -1,3 +1,4 @@
+use crate::error::InsertError;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::ops;
@@ -5,8 +6,10 @@ use std::ops;
 /// Bin of a [`StreamHist`](crate::hist::StreamHist) histogram.
 ///
 /// The fields of `Bin` are private, it can be initialized using [`Bin::new`] or [`Bin::from<f64>`] functions.
-/// Bins support the `+` operation for merging them.
+/// Bins support the `+` operation for merging them. When deserializing, the mean is validated the same
+/// as in [`Bin::new`], but it returns an error rather than panicking.
 #[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(try_from = "BinParts")]
 pub struct Bin {
     /// Mean (value) of the bin. It needs to be a number (not `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`).
     pub(crate) mean: f64,
@@ -14,6 +17,22 @@ pub struct Bin {
     pub(crate) count: u64,
 }
 
+/// The serialized fields of the [`Bin`], validated after deserializing.
+#[derive(Deserialize)]
+struct BinParts {
+    mean: f64,
+    count: u64,
+}
+
+impl TryFrom<BinParts> for Bin {
+    type Error = InsertError;
+
+    fn try_from(parts: BinParts) -> Result<Self, Self::Error> {
+        InsertError::check(parts.mean)?;
+        Ok(Bin::new(parts.mean, parts.count))
+    }
+}
+
 impl Bin {
     /// Initialize new `Bin`.
     ///
@@ -34,7 +53,11 @@ impl Bin {
     #[inline]
     pub fn new(mean: f64, count: u64) -> Self {
         assert!(!mean.is_nan() && mean.is_finite(), "{mean} is not a number");
-        Bin { mean, count }
+        // adding zero turns the negative zero to positive, so they compare as equal in the total ordering
+        Bin {
+            mean: mean + 0.0,
+            count,
+        }
     }
 }
 
@@ -81,10 +104,10 @@ impl From<&Bin> for (f64, u64) {
     /// let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
     ///
     /// // extract means and counts of all the bins
-    /// let (means, counts): (Vec<f64>, Vec<u64>) = hist.bins.iter().map(|bin| bin.into()).unzip();
+    /// let (means, counts): (Vec<f64>, Vec<u64>) = hist.bins().iter().map(|bin| bin.into()).unzip();
     ///
-    /// assert_eq!(means.len(), hist.bins.len());
-    /// assert_eq!(counts.len(), hist.bins.len());
+    /// assert_eq!(means.len(), hist.bins().len());
+    /// assert_eq!(counts.len(), hist.bins().len());
     /// ```
     fn from(bin: &Bin) -> Self {
         (bin.mean, bin.count)
@@ -94,7 +117,7 @@ impl From<&Bin> for (f64, u64) {
 impl PartialEq for Bin {
     /// Compare the means of the bins.
     fn eq(&self, other: &Self) -> bool {
-        self.mean == other.mean
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -111,17 +134,19 @@ impl PartialEq for Bin {
 impl Eq for Bin {}
 
 impl PartialOrd for Bin {
-    #[allow(clippy::incorrect_partial_ord_impl_on_ord_type)]
     /// Compare the means of the bins.
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.mean.partial_cmp(&other.mean)
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Bin {
     /// Compare the means of the bins.
+    ///
+    /// It uses the [`f64::total_cmp`] ordering, so even the invalid means (e.g. `f64::NAN`)
+    /// would be sorted consistently.
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+        self.mean.total_cmp(&other.mean)
     }
 }
 
@@ -164,6 +189,7 @@ pub(crate) fn sum_counts(bins: &[Bin]) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::Bin;
+    use std::cmp::Ordering;
     use test_case::test_case;
 
     #[test_case(f64::NAN ; "NaN")]
@@ -195,6 +221,38 @@ mod tests {
         assert!(Bin::new(0.0, 0) < Bin::new(1.0, 0));
         assert!(Bin::new(-1.0, 0) > Bin::new(-2.0, 0));
         assert!(Bin::new(0.0, 0) <= Bin::new(1.0, 0));
+        assert_eq!(Bin::new(-0.0, 0), Bin::new(0.0, 0));
+        assert_eq!(Bin::new(-0.0, 0).cmp(&Bin::new(0.0, 0)), Ordering::Equal);
+    }
+
+    #[test]
+    fn sort_invalid() {
+        // the invalid bins cannot be created using the public API
+        let mut bins = [
+            Bin::new(2.0, 1),
+            Bin {
+                mean: f64::NAN,
+                count: 1,
+            },
+            Bin::new(1.0, 1),
+            Bin {
+                mean: f64::NEG_INFINITY,
+                count: 1,
+            },
+        ];
+        bins.sort();
+        let means: Vec<f64> = bins.iter().map(|bin| bin.mean).collect();
+        assert_eq!(means[..3], [f64::NEG_INFINITY, 1.0, 2.0]);
+        assert!(means[3].is_nan());
+    }
+
+    #[test]
+    fn deserialize() {
+        let bin: Bin = serde_json::from_str(r#"{"mean":-0.0,"count":2}"#).unwrap();
+        assert_eq!(<(f64, u64)>::from(&bin), (0.0, 2));
+
+        let invalid = rmp_serde::to_vec(&(f64::NAN, 1_u64)).unwrap();
+        assert!(rmp_serde::from_slice::<Bin>(&invalid).is_err());
     }
 
     #[test]
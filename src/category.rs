@@ -0,0 +1,503 @@
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::error::Error;
+use std::hash::Hash;
+use std::io::{Read, Write};
+use std::iter::zip;
+
+/// Version of the serialization format of the [`CategoryHist`].
+const FORMAT_VERSION: u32 = 1;
+
+/// Streaming histogram of the categorical values, e.g. the names or the status codes, a companion
+/// of the [`StreamHist`](crate::StreamHist) for the non-numeric data.
+///
+/// It counts the values exactly as long as there are at most `size` distinct values. When a new value
+/// arrives and there is no space for it, it uses the [SpaceSaving] algorithm by Metwally et al. (2005):
+/// the value with the smallest count is replaced by the new one, which inherits its count. Such counts
+/// are overestimated at most by the inherited counts, reported as the errors, but all the values more
+/// frequent than $N / \mathrm{size}$ are guaranteed to be kept, so it finds the heavy hitters.
+///
+/// [SpaceSaving]: https://doi.org/10.1007/978-3-540-30570-5_27
+///
+/// # Examples
+///
+/// ```
+/// use histr::CategoryHist;
+///
+/// let mut hist = CategoryHist::with_capacity(2);
+/// for value in ["GET", "GET", "POST", "GET", "PUT", "GET"] {
+///     hist.insert(value);
+/// }
+/// assert_eq!(hist.count(), 6);
+/// assert_eq!(hist.top(1), vec![(&"GET", 4)]);
+/// assert_eq!(hist.get(&"PUT"), Some(2));
+/// assert_eq!(hist.error(&"PUT"), Some(1));
+/// assert!(!hist.is_exact());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryHist<T: Hash + Eq> {
+    counters: HashMap<T, Counter>,
+    /// Upper bound for the number of the distinct values.
+    size: usize,
+    /// The total count of the inserted values.
+    total: u64,
+}
+
+/// The estimated count of a value, and by how much it can be overestimated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Counter {
+    count: u64,
+    error: u64,
+}
+
+impl<T: Hash + Eq> CategoryHist<T> {
+    /// Initialize an empty histogram counting at most `size` distinct values.
+    pub fn with_capacity(size: usize) -> Self {
+        CategoryHist {
+            counters: HashMap::with_capacity(size),
+            size,
+            total: 0,
+        }
+    }
+
+    /// Insert a new value to the histogram.
+    pub fn insert(&mut self, value: T) {
+        self.insert_with_count(value, 1)
+    }
+
+    /// Insert a `value` that was observed `count` times to the histogram.
+    pub fn insert_with_count(&mut self, value: T, count: u64) {
+        if count == 0 {
+            return;
+        }
+        self.total += count;
+        self.add(value, count, 0);
+    }
+
+    /// Add the counts to the counter of the `value`, replacing the value with the smallest count if needed.
+    fn add(&mut self, value: T, count: u64, error: u64) {
+        if let Some(counter) = self.counters.get_mut(&value) {
+            counter.count += count;
+            counter.error += error;
+            return;
+        }
+        if self.counters.len() < self.size {
+            self.counters.insert(value, Counter { count, error });
+            return;
+        }
+        let Some(min) = self.counters.values().map(|c| c.count).min() else {
+            return;
+        };
+        // the new value inherits the count of the replaced one
+        let mut replaced = None;
+        self.counters.retain(|_, counter| {
+            if replaced.is_none() && counter.count == min {
+                replaced = Some(*counter);
+                return false;
+            }
+            true
+        });
+        self.counters.insert(
+            value,
+            Counter {
+                count: min + count,
+                error: min + error,
+            },
+        );
+    }
+
+    /// Merge the other histogram, the size of this histogram is preserved.
+    ///
+    /// It uses the merging procedure by Agarwal et al. (2012): the values missing in one of the full histograms
+    /// could have been counted up to its smallest count, so they get it added to their counts and errors,
+    /// and then the values with the largest counts are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::CategoryHist;
+    ///
+    /// let mut hist = CategoryHist::with_capacity(5);
+    /// hist.extend(["a", "b", "a"]);
+    /// let mut other = CategoryHist::with_capacity(5);
+    /// other.extend(["a", "c"]);
+    ///
+    /// hist.merge(other);
+    /// assert_eq!(hist.count(), 5);
+    /// assert_eq!(hist.top(1), vec![(&"a", 3)]);
+    /// assert_eq!((hist.get(&"b"), hist.get(&"c")), (Some(1), Some(1)));
+    /// ```
+    pub fn merge(&mut self, other: Self) {
+        let floor = |hist: &Self| {
+            if hist.counters.len() < hist.size {
+                0
+            } else {
+                hist.counters.values().map(|c| c.count).min().unwrap_or(0)
+            }
+        };
+        let (self_floor, other_floor) = (floor(self), floor(&other));
+        let mut counters = std::mem::take(&mut self.counters);
+        for counter in counters.values_mut() {
+            counter.count += other_floor;
+            counter.error += other_floor;
+        }
+        for (value, counter) in other.counters {
+            match counters.get_mut(&value) {
+                // the floor of the other histogram was added to all the values above
+                Some(c) => {
+                    c.count = c.count - other_floor + counter.count;
+                    c.error = c.error - other_floor + counter.error;
+                }
+                None => {
+                    counters.insert(
+                        value,
+                        Counter {
+                            count: counter.count + self_floor,
+                            error: counter.error + self_floor,
+                        },
+                    );
+                }
+            }
+        }
+
+        let mut counters: Vec<(T, Counter)> = counters.into_iter().collect();
+        counters.sort_by_key(|(_, counter)| Reverse(counter.count));
+        counters.truncate(self.size);
+        self.counters = counters.into_iter().collect();
+        self.total += other.total;
+    }
+
+    /// The estimated count of the `value`, or `None` if it is not counted.
+    ///
+    /// The count is exact when [`CategoryHist::error`] is zero, otherwise it is overestimated at most by the error.
+    pub fn get<Q>(&self, value: &Q) -> Option<u64>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.counters.get(value).map(|counter| counter.count)
+    }
+
+    /// The largest possible overestimation of the count of the `value`, or `None` if it is not counted.
+    pub fn error<Q>(&self, value: &Q) -> Option<u64>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.counters.get(value).map(|counter| counter.error)
+    }
+
+    /// The `k` values with the largest estimated counts, in the decreasing order of the counts,
+    /// the ties are in an arbitrary order.
+    pub fn top(&self, k: usize) -> Vec<(&T, u64)> {
+        let mut values: Vec<(&T, u64)> = self.iter().collect();
+        values.sort_by_key(|(_, count)| Reverse(*count));
+        values.truncate(k);
+        values
+    }
+
+    /// Iterate over the counted values and their estimated counts, in an arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, u64)> {
+        self.counters
+            .iter()
+            .map(|(value, counter)| (value, counter.count))
+    }
+
+    /// The total count of the inserted values.
+    pub fn count(&self) -> u64 {
+        self.total
+    }
+
+    /// The number of the counted distinct values.
+    pub fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Returns `true` if the histogram contains no data.
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Upper bound for the number of the counted distinct values.
+    pub fn capacity(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if all the counts are exact, since none of the values was replaced.
+    pub fn is_exact(&self) -> bool {
+        self.counters.values().all(|counter| counter.error == 0)
+            && self.counters.values().map(|c| c.count).sum::<u64>() == self.total
+    }
+}
+
+impl<T: Hash + Eq> Extend<T> for CategoryHist<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|value| self.insert(value));
+    }
+}
+
+/// The serialization format of the [`CategoryHist`], with the values in the decreasing order of the counts.
+#[derive(Serialize, Deserialize)]
+struct CategoryRepr<T> {
+    version: u32,
+    values: Vec<T>,
+    counts: Vec<u64>,
+    errors: Vec<u64>,
+    total: u64,
+    size: usize,
+}
+
+impl<T: Hash + Eq + Serialize> Serialize for CategoryHist<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut counters: Vec<(&T, &Counter)> = self.counters.iter().collect();
+        counters.sort_by_key(|(_, counter)| Reverse(counter.count));
+        CategoryRepr {
+            version: FORMAT_VERSION,
+            values: counters.iter().map(|(value, _)| *value).collect(),
+            counts: counters.iter().map(|(_, c)| c.count).collect(),
+            errors: counters.iter().map(|(_, c)| c.error).collect(),
+            total: self.total,
+            size: self.size,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: Hash + Eq + Deserialize<'de>> Deserialize<'de> for CategoryHist<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = CategoryRepr::<T>::deserialize(deserializer)?;
+        if repr.version != FORMAT_VERSION {
+            return Err(D::Error::custom("unsupported format version"));
+        }
+        let n = repr.values.len();
+        if repr.counts.len() != n || repr.errors.len() != n {
+            return Err(D::Error::custom(
+                "the values, counts, and errors have different lengths",
+            ));
+        }
+        if n > repr.size {
+            return Err(D::Error::custom("there are more values than the size"));
+        }
+        // the counts without the errors are observed for sure, so they cannot exceed the total,
+        // while the overestimated counts of the merged histograms can
+        let guaranteed = zip(&repr.counts, &repr.errors)
+            .try_fold(0u64, |acc, (count, error)| {
+                acc.checked_add(count.saturating_sub(*error))
+            })
+            .ok_or_else(|| D::Error::custom("the sum of the counts overflows"))?;
+        if guaranteed > repr.total {
+            return Err(D::Error::custom(
+                "the total is smaller than the sum of the counts",
+            ));
+        }
+        // the size comes from an untrusted input, so only the memory for the values is reserved
+        let mut counters = HashMap::with_capacity(n);
+        for ((value, count), error) in repr.values.into_iter().zip(repr.counts).zip(repr.errors) {
+            if count == 0 || error > count {
+                return Err(D::Error::custom(
+                    "the counts are zero or smaller than the errors",
+                ));
+            }
+            if counters.insert(value, Counter { count, error }).is_some() {
+                return Err(D::Error::custom("the values are not unique"));
+            }
+        }
+        Ok(CategoryHist {
+            counters,
+            size: repr.size,
+            total: repr.total,
+        })
+    }
+}
+
+impl<T: Hash + Eq + Serialize + DeserializeOwned> CategoryHist<T> {
+    /// Parse the histogram from a JSON string.
+    ///
+    /// The JSON contains the `values` in the decreasing order of their `counts`, the `errors` of the counts,
+    /// the `total` count, and the `size`, see [`CategoryHist::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the JSON is malformed, does not match the format, or the histogram is not valid,
+    /// e.g. the values are not unique.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Transform the histogram to a JSON string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::CategoryHist;
+    ///
+    /// let mut hist = CategoryHist::with_capacity(3);
+    /// hist.extend(["a", "b", "a"].map(String::from));
+    /// let json = hist.to_json();
+    /// assert_eq!(
+    ///     json,
+    ///     r#"{"version":1,"values":["a","b"],"counts":[2,1],"errors":[0,0],"total":3,"size":3}"#
+    /// );
+    /// assert_eq!(CategoryHist::from_json(&json).unwrap(), hist);
+    /// ```
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Read histogram from JSON using a reader, see [`CategoryHist::from_json`].
+    pub fn read_json<R>(reader: R) -> Result<Self, Box<dyn Error>>
+    where
+        R: Read,
+    {
+        let hist = serde_json::from_reader(reader).map_err(Box::new)?;
+        Ok(hist)
+    }
+
+    /// Write histogram to JSON using a writer, see [`CategoryHist::to_json`].
+    pub fn write_json<W>(&self, writer: &mut W) -> Result<(), Box<dyn Error>>
+    where
+        W: Write,
+    {
+        write!(writer, "{}", self.to_json()).map_err(Box::new)?;
+        Ok(())
+    }
+
+    /// Read histogram from a [MessagePack] format using a reader.
+    ///
+    /// [MessagePack]: https://msgpack.org/
+    pub fn read_msgpack<R>(reader: R) -> Result<Self, Box<dyn Error>>
+    where
+        R: Read,
+    {
+        let hist = rmp_serde::from_read(reader).map_err(Box::new)?;
+        Ok(hist)
+    }
+
+    /// Write histogram to [MessagePack] format using a writer.
+    ///
+    /// [MessagePack]: https://msgpack.org/
+    pub fn write_msgpack<W>(&self, writer: &mut W) -> Result<(), Box<dyn Error>>
+    where
+        W: Write,
+    {
+        rmp_serde::encode::write(writer, self).map_err(Box::new)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CategoryHist;
+
+    #[test]
+    fn exact() {
+        let mut hist = CategoryHist::with_capacity(3);
+        hist.extend([1, 2, 1, 3, 1, 2]);
+        assert!(hist.is_exact());
+        assert_eq!(hist.count(), 6);
+        assert_eq!(hist.len(), 3);
+        assert_eq!(hist.top(3), vec![(&1, 3), (&2, 2), (&3, 1)]);
+        assert_eq!(hist.get(&4), None);
+        assert_eq!(hist.error(&3), Some(0));
+    }
+
+    #[test]
+    fn heavy_hitters() {
+        let mut hist = CategoryHist::with_capacity(10);
+        for i in 0..10_000 {
+            // every third value is the heavy hitter, the rest are distinct
+            let value = if i % 3 == 0 { 0 } else { i };
+            hist.insert(value);
+        }
+        assert!(!hist.is_exact());
+        assert_eq!(hist.count(), 10_000);
+        assert_eq!(hist.len(), 10);
+        let (value, count) = hist.top(1)[0];
+        assert_eq!(*value, 0);
+        let error = hist.error(&0).unwrap();
+        assert!(count - error <= 3334 && 3334 <= count);
+        // the sum of the counts is equal to the total
+        assert_eq!(hist.iter().map(|(_, count)| count).sum::<u64>(), 10_000);
+    }
+
+    #[test]
+    fn merge() {
+        let mut a = CategoryHist::with_capacity(2);
+        a.extend(["x", "x", "x", "y"]);
+        let mut b = CategoryHist::with_capacity(3);
+        b.extend(["z", "z", "x"]);
+        a.merge(b);
+        assert_eq!(a.count(), 7);
+        assert_eq!(a.len(), 2);
+        assert_eq!(a.get("x"), Some(4));
+        // "z" could have been counted in the full histogram `a` up to its smallest count
+        assert_eq!(a.get("z"), Some(3));
+        assert_eq!(a.error("z"), Some(1));
+
+        // the other histogram is full, and the shared value is counted exactly
+        let mut a = CategoryHist::with_capacity(2);
+        a.insert("x");
+        let mut b = CategoryHist::with_capacity(2);
+        b.extend(["x", "x", "x", "x", "x", "y", "y", "y"]);
+        a.merge(b);
+        assert_eq!(a.count(), 9);
+        assert_eq!((a.get("x"), a.error("x")), (Some(6), Some(0)));
+        assert_eq!((a.get("y"), a.error("y")), (Some(3), Some(0)));
+    }
+
+    #[test]
+    fn serialization() {
+        let mut hist = CategoryHist::with_capacity(2);
+        hist.extend(["a", "b", "a", "c"].map(String::from));
+        let json = hist.to_json();
+        assert_eq!(CategoryHist::<String>::from_json(&json).unwrap(), hist);
+
+        let mut buffer = Vec::new();
+        hist.write_msgpack(&mut buffer).unwrap();
+        assert_eq!(
+            CategoryHist::<String>::read_msgpack(buffer.as_slice()).unwrap(),
+            hist
+        );
+
+        for json in [
+            r#"{"version":1,"values":["a"],"counts":[1,2],"errors":[0],"total":1,"size":1}"#,
+            r#"{"version":1,"values":["a","a"],"counts":[1,1],"errors":[0,0],"total":2,"size":2}"#,
+            r#"{"version":1,"values":["a","b"],"counts":[1,1],"errors":[0,0],"total":2,"size":1}"#,
+            r#"{"version":1,"values":["a"],"counts":[1],"errors":[2],"total":1,"size":1}"#,
+            r#"{"version":2,"values":["a"],"counts":[1],"errors":[0],"total":1,"size":1}"#,
+            r#"{"version":1,"values":["a","b"],"counts":[18446744073709551615,1],"errors":[0,0],"total":18446744073709551615,"size":2}"#,
+            r#"{"version":1,"values":["a","b"],"counts":[2,1],"errors":[0,0],"total":2,"size":2}"#,
+        ] {
+            assert!(CategoryHist::<String>::from_json(json).is_err(), "{json}");
+        }
+
+        // the huge size is not reserved upfront
+        let json = format!(
+            r#"{{"version":1,"values":["a"],"counts":[1],"errors":[0],"total":1,"size":{}}}"#,
+            usize::MAX
+        );
+        let hist = CategoryHist::<String>::from_json(&json).unwrap();
+        assert_eq!(hist.capacity(), usize::MAX);
+
+        // the merged counts are overestimated
+        let mut merged = CategoryHist::with_capacity(2);
+        merged.extend(["x", "x", "y"].map(String::from));
+        let mut other = CategoryHist::with_capacity(2);
+        other.extend(["z", "z", "w"].map(String::from));
+        merged.merge(other);
+        let json = merged.to_json();
+        assert_eq!(CategoryHist::<String>::from_json(&json).unwrap(), merged);
+    }
+
+    #[test]
+    fn no_capacity() {
+        let mut hist = CategoryHist::with_capacity(0);
+        hist.insert("a");
+        assert_eq!(hist.count(), 1);
+        assert_eq!(hist.len(), 0);
+        assert!(!hist.is_exact());
+    }
+}
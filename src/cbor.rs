@@ -0,0 +1,70 @@
+use crate::hist::StreamHist;
+use std::error::Error;
+use std::io::{Read, Write};
+
+impl StreamHist {
+    /// Read histogram from a [CBOR] format using a reader.
+    ///
+    /// It uses the same format as [`StreamHist::from_json`], and validates the histogram the same way.
+    ///
+    /// [CBOR]: https://cbor.io/
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![2.0, 5.0, 1.0, 3.0, 4.0, 1.0, 2.5]);
+    /// let mut buffer = Vec::new();
+    /// hist.write_cbor(&mut buffer).unwrap();
+    ///
+    /// assert_eq!(StreamHist::read_cbor(buffer.as_slice()).unwrap(), hist);
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err)
+    )]
+    pub fn read_cbor<R>(reader: R) -> Result<Self, Box<dyn Error>>
+    where
+        R: Read,
+    {
+        let hist = ciborium::from_reader(reader).map_err(Box::new)?;
+        Ok(hist)
+    }
+
+    /// Write histogram to [CBOR] format using a writer.
+    ///
+    /// [CBOR]: https://cbor.io/
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(bins = self.bins.len()))
+    )]
+    pub fn write_cbor<W>(&self, writer: &mut W) -> Result<(), Box<dyn Error>>
+    where
+        W: Write,
+    {
+        ciborium::into_writer(self, writer).map_err(Box::new)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hist::StreamHist;
+
+    #[test]
+    fn write_read_cbor() {
+        for hist in [
+            StreamHist::from(vec![2.0, 5.0, 1.0, 3.0, 4.0, 1.0, 2.5]),
+            StreamHist::with_capacity(5),
+        ] {
+            let mut buffer = Vec::new();
+            hist.write_cbor(&mut buffer).expect("failed writing");
+            assert_eq!(
+                StreamHist::read_cbor(buffer.as_slice()).expect("failed reading"),
+                hist
+            );
+        }
+        assert!(StreamHist::read_cbor([0xff, 0x00].as_slice()).is_err());
+    }
+}
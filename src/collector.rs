@@ -0,0 +1,224 @@
+//! Non-blocking collection of the values from many threads.
+//!
+//! The [`Recorder`] handles send the values over a channel to the [`Aggregator`], which owns the [`StreamHist`]
+//! and updates it on a background thread, so recording the values never waits for the histogram updates.
+//! The aggregator can periodically pass the snapshots of the histogram to a callback.
+//!
+//! # Examples
+//!
+//! ```
+//! use histr::collector::Aggregator;
+//! use histr::StreamHist;
+//! use std::time::Duration;
+//!
+//! let (recorder, aggregator) = Aggregator::spawn(
+//!     StreamHist::with_capacity(10),
+//!     Duration::from_secs(60),
+//!     |hist| println!("mean = {}", hist.mean()),
+//! );
+//!
+//! let threads: Vec<_> = (0..4)
+//!     .map(|i| {
+//!         let recorder = recorder.clone();
+//!         std::thread::spawn(move || recorder.record(i as f64))
+//!     })
+//!     .collect();
+//! threads.into_iter().for_each(|t| t.join().unwrap());
+//!
+//! let hist = aggregator.finish();
+//! assert_eq!(hist.count(), 4.0);
+//! ```
+
+use crate::hist::StreamHist;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+enum Message {
+    Value(f64),
+    Stop,
+}
+
+/// Lightweight handle for sending the values to the [`Aggregator`], it can be cheaply cloned and
+/// shared between the threads.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    sender: Sender<Message>,
+}
+
+impl Recorder {
+    /// Send the `value` to the aggregator without waiting for it to be processed.
+    ///
+    /// The values that are not numbers (`f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`) are ignored,
+    /// as are the values recorded after the aggregator was finished.
+    pub fn record(&self, value: f64) {
+        if value.is_finite() {
+            // the aggregator was finished, nothing to do
+            let _ = self.sender.send(Message::Value(value));
+        }
+    }
+}
+
+/// Background thread owning the [`StreamHist`] and updating it with the values sent by the [`Recorder`]s.
+#[derive(Debug)]
+pub struct Aggregator {
+    sender: Sender<Message>,
+    thread: JoinHandle<StreamHist>,
+}
+
+impl Aggregator {
+    /// Start the aggregation thread updating the `hist`, and return the recorder for sending the values to it.
+    ///
+    /// Every `interval` the current state of the histogram is passed to the `on_snapshot` callback, which
+    /// is called on the aggregation thread, so it should return quickly.
+    pub fn spawn<F>(hist: StreamHist, interval: Duration, on_snapshot: F) -> (Recorder, Self)
+    where
+        F: FnMut(&StreamHist) + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            let mut hist = hist;
+            let mut on_snapshot = on_snapshot;
+            let mut deadline = Instant::now() + interval;
+            loop {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                match receiver.recv_timeout(timeout) {
+                    Ok(Message::Value(value)) => hist.insert(value),
+                    Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => return hist,
+                    Err(RecvTimeoutError::Timeout) => (),
+                }
+                // under a sustained traffic the channel never times out, so the deadline is checked
+                // after each of the values as well
+                let now = Instant::now();
+                if now >= deadline {
+                    on_snapshot(&hist);
+                    deadline = now + interval;
+                }
+            }
+        });
+        let recorder = Recorder {
+            sender: sender.clone(),
+        };
+        (recorder, Aggregator { sender, thread })
+    }
+
+    /// Create another recorder sending the values to this aggregator.
+    pub fn recorder(&self) -> Recorder {
+        Recorder {
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Process all the values recorded so far, stop the aggregation thread, and return the histogram.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the aggregation thread panicked, e.g. when the `on_snapshot` callback panicked.
+    pub fn finish(self) -> StreamHist {
+        // the values are processed in order, so all the values sent before are processed before stopping
+        self.sender
+            .send(Message::Stop)
+            .expect("aggregation thread stopped");
+        self.thread.join().expect("aggregation thread panicked")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Aggregator;
+    use crate::hist::StreamHist;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn aggregate() {
+        let (recorder, aggregator) = Aggregator::spawn(
+            StreamHist::with_capacity(5),
+            Duration::from_secs(60),
+            |_| {},
+        );
+
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let recorder = if i % 2 == 0 {
+                    recorder.clone()
+                } else {
+                    aggregator.recorder()
+                };
+                thread::spawn(move || {
+                    for j in 0..100 {
+                        recorder.record((i * 100 + j) as f64);
+                    }
+                    recorder.record(f64::NAN);
+                })
+            })
+            .collect();
+        threads.into_iter().for_each(|t| t.join().unwrap());
+
+        // the recorder is still alive, but finishing does not wait for it
+        let hist = aggregator.finish();
+        assert_eq!(hist.count(), 400.0);
+        assert_eq!(hist.bins.len(), 5);
+        assert_eq!(hist.min, 0.0);
+        assert_eq!(hist.max, 399.0);
+
+        // recording after finishing is ignored
+        recorder.record(1.0);
+    }
+
+    #[test]
+    fn snapshots() {
+        let (sender, receiver) = mpsc::channel();
+        let (recorder, aggregator) = Aggregator::spawn(
+            StreamHist::with_capacity(5),
+            Duration::from_millis(1),
+            move |hist| {
+                let _ = sender.send(hist.count());
+            },
+        );
+
+        recorder.record(1.0);
+        recorder.record(2.0);
+        // eventually the snapshot contains all the values
+        while receiver.recv().unwrap() < 2.0 {}
+
+        assert_eq!(aggregator.finish().count(), 2.0);
+    }
+
+    #[test]
+    fn snapshots_under_traffic() {
+        let (sender, receiver) = mpsc::channel();
+        let (recorder, aggregator) = Aggregator::spawn(
+            StreamHist::with_capacity(5),
+            Duration::from_millis(5),
+            move |hist| {
+                let _ = sender.send(hist.count());
+            },
+        );
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let producer = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    recorder.record(1.0);
+                }
+            })
+        };
+        // the values keep arriving past the interval, the snapshots taken before them are skipped
+        let snapshot = loop {
+            match receiver.recv_timeout(Duration::from_secs(10)) {
+                Ok(0.0) => continue,
+                result => break result,
+            }
+        };
+        stop.store(true, Ordering::Relaxed);
+        producer.join().unwrap();
+
+        assert!(snapshot.is_ok(), "no snapshot under the traffic");
+        assert!(aggregator.finish().count() > 0.0);
+    }
+}
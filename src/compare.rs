@@ -0,0 +1,209 @@
+use crate::distance::probabilities;
+use crate::hist::StreamHist;
+
+/// The smallest proportion used for empty buckets, so that the logarithms in [`StreamHist::psi`] stay finite.
+const MIN_PROPORTION: f64 = 1e-4;
+
+impl StreamHist {
+    /// [Population stability index] (PSI) of the histogram compared to the `reference` histogram.
+    ///
+    /// The values are grouped into `buckets` with the boundaries at the equally spaced quantiles of the `reference`
+    /// histogram, and the proportions $a_i$ and $e_i$ of the values in each bucket, estimated with
+    /// [`StreamHist::counts_for_bounds`], are compared as
+    ///
+    /// $$
+    /// \mathrm{PSI} = \sum_i (a_i - e_i) \ln \frac{a_i}{e_i}
+    /// $$
+    ///
+    /// The proportions of the empty buckets are replaced with a small positive number to avoid division by zero.
+    /// As a rule of thumb, PSI below 0.1 means no significant change, between 0.1 and 0.2 a moderate change,
+    /// and above 0.2 a significant change of the distribution.
+    ///
+    /// It returns `f64::NAN` if any of the histograms is empty.
+    ///
+    /// [Population stability index]: https://www.listendata.com/2015/05/population-stability-index.html
+    ///
+    /// # Panics
+    ///
+    /// It panics when `buckets` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let reference = StreamHist::from((0..100).map(|x| x as f64).collect::<Vec<_>>());
+    /// let same = StreamHist::from((0..100).map(|x| x as f64).collect::<Vec<_>>());
+    /// let shifted = StreamHist::from((50..150).map(|x| x as f64).collect::<Vec<_>>());
+    ///
+    /// assert!(same.psi(&reference, 10) < 0.1);
+    /// assert!(shifted.psi(&reference, 10) > 0.2);
+    /// ```
+    pub fn psi(&self, reference: &StreamHist, buckets: usize) -> f64 {
+        assert!(buckets > 0, "number of buckets needs to be positive");
+        if self.is_empty() || reference.is_empty() {
+            return f64::NAN;
+        }
+
+        // the repeated quantiles, e.g. for the discrete data, would give the empty buckets in both histograms
+        let probs: Vec<f64> = (1..buckets).map(|i| i as f64 / buckets as f64).collect();
+        let mut bounds = reference.quantiles(&probs);
+        bounds.dedup();
+
+        let proportions = |hist: &StreamHist| {
+            let total = hist.count();
+            hist.counts_for_bounds(&bounds)
+                .into_iter()
+                .map(move |count| (count / total).max(MIN_PROPORTION))
+        };
+        proportions(self)
+            .zip(proportions(reference))
+            .map(|(a, e)| (a - e) * (a / e).ln())
+            .sum()
+    }
+
+    /// [Kullback-Leibler divergence] of the `other` histogram from this histogram, in nats.
+    ///
+    /// Both histograms are discretized on a common grid of the intervals between their bin means and extremes,
+    /// and the probabilities $p_i$ and $q_i$ of the intervals are compared as
+    ///
+    /// $$
+    /// D_{KL}(P \parallel Q) = \sum_i p_i \ln \frac{p_i}{q_i}
+    /// $$
+    ///
+    /// The intervals where this histogram has zero probability do not contribute to the sum. The divergence is
+    /// `f64::INFINITY` when this histogram has non-zero probability where the `other` has none, e.g. when
+    /// its values spread beyond the range of the `other` histogram. Use [`StreamHist::js_divergence`] when
+    /// a finite, symmetric measure is needed.
+    ///
+    /// It returns `f64::NAN` if any of the histograms is empty.
+    ///
+    /// [Kullback-Leibler divergence]: https://en.wikipedia.org/wiki/Kullback%E2%80%93Leibler_divergence
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let narrow = StreamHist::from((25..75).map(|x| x as f64).collect::<Vec<_>>());
+    /// let wide = StreamHist::from((0..100).map(|x| x as f64).collect::<Vec<_>>());
+    ///
+    /// assert!((narrow.kl_divergence(&wide) - 2.0f64.ln()).abs() < 0.05);
+    /// assert_eq!(wide.kl_divergence(&narrow), f64::INFINITY);
+    /// ```
+    pub fn kl_divergence(&self, other: &StreamHist) -> f64 {
+        if self.is_empty() || other.is_empty() {
+            return f64::NAN;
+        }
+        probabilities(self, other).map(|(p, q)| kl_term(p, q)).sum()
+    }
+
+    /// [Jensen-Shannon divergence] between the histograms, in nats.
+    ///
+    /// It is the average of the Kullback-Leibler divergences (see [`StreamHist::kl_divergence`]) of both histograms
+    /// from their mixture $M = \tfrac{1}{2}(P + Q)$, so it is symmetric and always finite, ranging from `0.0` for
+    /// the same distributions to $\ln 2$ for the distributions with disjoint supports.
+    ///
+    /// It returns `f64::NAN` if any of the histograms is empty.
+    ///
+    /// [Jensen-Shannon divergence]: https://en.wikipedia.org/wiki/Jensen%E2%80%93Shannon_divergence
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let training = StreamHist::from((0..100).map(|x| x as f64).collect::<Vec<_>>());
+    /// let serving = StreamHist::from((200..300).map(|x| x as f64).collect::<Vec<_>>());
+    ///
+    /// assert_eq!(training.js_divergence(&training), 0.0);
+    /// assert!((training.js_divergence(&serving) - 2.0f64.ln()).abs() < 1e-9);
+    /// ```
+    pub fn js_divergence(&self, other: &StreamHist) -> f64 {
+        if self.is_empty() || other.is_empty() {
+            return f64::NAN;
+        }
+        probabilities(self, other)
+            .map(|(p, q)| {
+                let m = (p + q) / 2.0;
+                (kl_term(p, m) + kl_term(q, m)) / 2.0
+            })
+            .sum()
+    }
+}
+
+/// Single term of the Kullback-Leibler divergence, with the convention that $0 \ln \frac{0}{q} = 0$.
+fn kl_term(p: f64, q: f64) -> f64 {
+    if p <= 0.0 {
+        0.0
+    } else if q <= 0.0 {
+        f64::INFINITY
+    } else {
+        p * (p / q).ln()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hist::StreamHist;
+
+    #[test]
+    fn psi() {
+        let mut reference = StreamHist::with_capacity(20);
+        let mut same = StreamHist::with_capacity(20);
+        let mut wider = StreamHist::with_capacity(20);
+        for i in 0..1000 {
+            let x = (i % 100) as f64;
+            reference.insert(x);
+            same.insert(x);
+            wider.insert(x * 2.0);
+        }
+
+        assert_eq!(same.psi(&reference, 10), 0.0);
+        assert!(wider.psi(&reference, 10) > 0.2);
+
+        // the proportions of the empty buckets are floored, so it stays finite
+        let disjoint: StreamHist = (0..100).map(|x| x as f64 + 1000.0).collect();
+        let psi = disjoint.psi(&reference, 10);
+        assert!(psi.is_finite() && psi > wider.psi(&reference, 10));
+        assert_eq!(same.psi(&reference, 1), 0.0);
+
+        // the repeated quantiles of the discrete data
+        let discrete: StreamHist = (0..100).map(|x| (x % 3) as f64).collect();
+        assert!(discrete.psi(&discrete, 10).abs() < 1e-12);
+
+        assert!(StreamHist::default().psi(&reference, 10).is_nan());
+        assert!(reference.psi(&StreamHist::default(), 10).is_nan());
+    }
+
+    #[test]
+    fn divergences() {
+        let mut a = StreamHist::with_capacity(20);
+        let mut b = StreamHist::with_capacity(20);
+        for i in 0..=1000 {
+            let x = i as f64 / 1000.0;
+            a.insert(x);
+            b.insert(x * x);
+        }
+
+        assert_eq!(a.kl_divergence(&a), 0.0);
+        assert_eq!(a.js_divergence(&a), 0.0);
+        assert!(a.kl_divergence(&b) > 0.0);
+        assert!(b.kl_divergence(&a) > 0.0);
+        assert!(a.kl_divergence(&b).is_finite());
+
+        let js = a.js_divergence(&b);
+        assert!(js > 0.0 && js < 2.0f64.ln());
+        assert!((js - b.js_divergence(&a)).abs() < 1e-12);
+
+        assert!(StreamHist::default().kl_divergence(&a).is_nan());
+        assert!(a.js_divergence(&StreamHist::default()).is_nan());
+    }
+
+    #[test]
+    #[should_panic]
+    fn psi_zero_buckets() {
+        let hist = StreamHist::from(vec![1.0, 2.0, 3.0]);
+        hist.psi(&hist, 0);
+    }
+}
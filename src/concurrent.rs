@@ -0,0 +1,173 @@
+use crate::{hist::StreamHist, lock, sample::IntoSample};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Counter used for assigning the threads to the shards.
+static NEXT_THREAD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// The number of the current thread, assigned on its first insert.
+    static THREAD: usize = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Streaming histogram that can be shared between many threads inserting the values concurrently.
+///
+/// It keeps several [`StreamHist`] shards guarded by their own mutexes, and each of the threads inserts
+/// to its own shard, falling back to the other shards when it is busy, so the threads rarely wait for each
+/// other. The shards are merged using the [`StreamHist::merge`] procedure when taking a
+/// [`ConcurrentStreamHist::snapshot`]. Unlike the [`ThreadLocalHist`](crate::local::ThreadLocalHist),
+/// it does not need to be a `static` variable, so it can be created at runtime, e.g. per an endpoint.
+///
+/// # Examples
+///
+/// ```
+/// use histr::ConcurrentStreamHist;
+/// use std::sync::Arc;
+///
+/// let hist = Arc::new(ConcurrentStreamHist::new(10, 4));
+/// let threads: Vec<_> = (0..8)
+///     .map(|i| {
+///         let hist = hist.clone();
+///         std::thread::spawn(move || {
+///             for j in 0..100 {
+///                 hist.insert((i * 100 + j) as f64);
+///             }
+///         })
+///     })
+///     .collect();
+/// threads.into_iter().for_each(|t| t.join().unwrap());
+///
+/// let snapshot = hist.snapshot();
+/// assert_eq!(snapshot.count(), 800.0);
+/// assert_eq!((snapshot.min(), snapshot.max()), (0.0, 799.0));
+/// ```
+#[derive(Debug)]
+pub struct ConcurrentStreamHist {
+    shards: Box<[Mutex<StreamHist>]>,
+    size: usize,
+}
+
+impl ConcurrentStreamHist {
+    /// Initialize the histogram with the `shards` histograms of `size` bins.
+    ///
+    /// # Panics
+    ///
+    /// It panics when `shards` is zero.
+    pub fn new(size: usize, shards: usize) -> Self {
+        assert!(shards > 0, "number of shards needs to be positive");
+        ConcurrentStreamHist {
+            shards: (0..shards)
+                .map(|_| Mutex::new(StreamHist::with_capacity(size)))
+                .collect(),
+            size,
+        }
+    }
+
+    /// Initialize the histogram with the `size` bins, and as many shards as the available parallelism.
+    pub fn with_capacity(size: usize) -> Self {
+        let shards = std::thread::available_parallelism().map_or(1, |n| n.get());
+        ConcurrentStreamHist::new(size, shards)
+    }
+
+    /// Insert a new point to the histogram.
+    ///
+    /// # Panics
+    ///
+    /// The `value` needs to be a number. It will panic on `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    pub fn insert<T: IntoSample>(&self, value: T) {
+        let value = value.into_sample();
+        assert!(value.is_finite(), "{value} is not a number");
+
+        let n = self.shards.len();
+        let first = THREAD.with(|thread| *thread) % n;
+        // if the shard of the thread is busy, try any of the others before waiting for it
+        for i in 0..n {
+            if let Ok(mut hist) = self.shards[(first + i) % n].try_lock() {
+                hist.insert(value);
+                return;
+            }
+        }
+        lock(&self.shards[first]).insert(value);
+    }
+
+    /// Merge the shards into a single histogram with `size` bins.
+    pub fn snapshot(&self) -> StreamHist {
+        self.shards
+            .iter()
+            .fold(StreamHist::with_capacity(self.size), |mut acc, shard| {
+                acc.merge(lock(shard).clone());
+                acc
+            })
+    }
+
+    /// Take the histogram as in [`ConcurrentStreamHist::snapshot`], and reset all the shards.
+    ///
+    /// The values inserted while taking it are either included in the returned histogram or
+    /// kept in the shards, they are never lost.
+    pub fn take(&self) -> StreamHist {
+        self.shards
+            .iter()
+            .fold(StreamHist::with_capacity(self.size), |mut acc, shard| {
+                let hist =
+                    std::mem::replace(&mut *lock(shard), StreamHist::with_capacity(self.size));
+                acc.merge(hist);
+                acc
+            })
+    }
+
+    /// Upper bound for the number of bins of the shards and the snapshots.
+    pub fn capacity(&self) -> usize {
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConcurrentStreamHist;
+    use crate::hist::StreamHist;
+    use std::sync::Arc;
+
+    #[test]
+    fn concurrent() {
+        let hist = Arc::new(ConcurrentStreamHist::new(20, 3));
+        let threads: Vec<_> = (0..6)
+            .map(|i| {
+                let hist = hist.clone();
+                std::thread::spawn(move || {
+                    for j in 0..1000 {
+                        hist.insert((i * 1000 + j) as f64);
+                    }
+                })
+            })
+            .collect();
+        threads.into_iter().for_each(|t| t.join().unwrap());
+
+        let snapshot = hist.snapshot();
+        assert_eq!(snapshot.count(), 6000.0);
+        assert_eq!(snapshot.capacity(), 20);
+        assert_eq!((snapshot.min(), snapshot.max()), (0.0, 5999.0));
+        assert!((snapshot.mean() - 2999.5).abs() < 1e-6);
+        assert!((snapshot.median() - 2999.5).abs() < 100.0);
+
+        assert_eq!(hist.take(), snapshot);
+        assert!(hist.snapshot().is_empty());
+    }
+
+    #[test]
+    fn single_thread() {
+        let hist = ConcurrentStreamHist::new(5, 2);
+        let mut expected = StreamHist::with_capacity(5);
+        for i in 0..100 {
+            hist.insert(i as f64);
+            expected.insert(i as f64);
+        }
+        // all the values were inserted to the same shard
+        assert_eq!(hist.snapshot(), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_nan() {
+        ConcurrentStreamHist::new(5, 2).insert(f64::NAN);
+    }
+}
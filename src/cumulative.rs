@@ -0,0 +1,129 @@
+use crate::hist::StreamHist;
+use serde::{Deserialize, Serialize};
+use std::iter::zip;
+
+/// Cumulative view of the [`StreamHist`] histogram, as used by the Prometheus-style histograms.
+///
+/// For each of the bins, it holds the upper bound of the bin and the count of the values in this
+/// and all the preceding bins, so the counts are non-decreasing and the last one is equal to the total count.
+/// The bins span between the midpoints to the neighboring bins, and the last bin ends at `max`
+/// (see [`StreamHist::normalized_bins`]).
+///
+/// # Examples
+///
+/// ```
+/// use histr::{Bin, StreamHist};
+///
+/// let hist = StreamHist::from(vec![Bin::new(1.0, 2), Bin::new(2.0, 1), Bin::new(4.0, 3)]);
+/// let cumulative = hist.to_cumulative();
+///
+/// let mut iter = cumulative.iter();
+/// assert_eq!(iter.next(), Some((1.5, 2)));
+/// assert_eq!(iter.next(), Some((3.0, 3)));
+/// assert_eq!(iter.next(), Some((4.0, 6)));
+/// assert_eq!(iter.next(), None);
+///
+/// assert_eq!(
+///     serde_json::to_string(&cumulative).unwrap(),
+///     r#"{"bounds":[1.5,3.0,4.0],"counts":[2,3,6]}"#
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CumulativeHist {
+    bounds: Vec<f64>,
+    counts: Vec<u64>,
+}
+
+impl CumulativeHist {
+    /// Upper bounds of the bins.
+    pub fn bounds(&self) -> &[f64] {
+        &self.bounds
+    }
+
+    /// Cumulative counts of the values smaller or equal to the bounds.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// The total count of the values.
+    pub fn total(&self) -> u64 {
+        self.counts.last().copied().unwrap_or(0)
+    }
+
+    /// Returns `true` if the histogram contains no data.
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    /// Iterate over the `(bound, cumulative count)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (f64, u64)> + '_ {
+        zip(self.bounds.iter().copied(), self.counts.iter().copied())
+    }
+}
+
+impl From<&StreamHist> for CumulativeHist {
+    fn from(hist: &StreamHist) -> Self {
+        let bounds = hist
+            .bin_edges()
+            .into_iter()
+            .map(|(_, right)| right)
+            .collect();
+        let counts = hist
+            .iter()
+            .scan(0, |acc, bin| {
+                *acc += bin.count;
+                Some(*acc)
+            })
+            .collect();
+        CumulativeHist { bounds, counts }
+    }
+}
+
+impl StreamHist {
+    /// Transform the histogram to the [`CumulativeHist`] view.
+    pub fn to_cumulative(&self) -> CumulativeHist {
+        CumulativeHist::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CumulativeHist;
+    use crate::bins::Bin;
+    use crate::hist::StreamHist;
+
+    #[test]
+    fn empty() {
+        let cumulative = StreamHist::default().to_cumulative();
+        assert!(cumulative.is_empty());
+        assert_eq!(cumulative.total(), 0);
+        assert_eq!(cumulative.iter().next(), None);
+        assert_eq!(cumulative, CumulativeHist::default());
+    }
+
+    #[test]
+    fn to_cumulative() {
+        let hist = StreamHist::from_parts(
+            vec![Bin::new(2.0, 3), Bin::new(4.0, 1), Bin::new(8.0, 4)],
+            1.0,
+            9.0,
+            3,
+        );
+        let cumulative = hist.to_cumulative();
+        assert_eq!(cumulative.bounds(), &[3.0, 6.0, 9.0]);
+        assert_eq!(cumulative.counts(), &[3, 4, 8]);
+        assert_eq!(cumulative.total(), hist.count() as u64);
+    }
+
+    #[test]
+    fn serde() {
+        let hist = StreamHist::from(vec![1.0, 2.0, 5.0, 3.0]);
+        let cumulative = hist.to_cumulative();
+        let json = serde_json::to_string(&cumulative).unwrap();
+        assert_eq!(json, r#"{"bounds":[1.5,2.5,4.0,5.0],"counts":[1,2,3,4]}"#);
+        assert_eq!(
+            serde_json::from_str::<CumulativeHist>(&json).unwrap(),
+            cumulative
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use crate::hist::{trim_bins, weighted_mean, MergePolicy, StreamHist};
+use crate::{bins::Bin, sample::IntoSample};
+use serde::{Deserialize, Serialize};
+
+/// The weights are rescaled when the weight of the new values exceeds it, so they do not overflow.
+const MAX_SCALE: f64 = 1e100;
+
+/// Streaming histogram with the exponential forgetting, so it tracks a drifting distribution.
+///
+/// Before inserting each of the values, the weights of all the values already in the histogram are multiplied
+/// by the `decay` factor, so the weight of the value inserted $n$ inserts ago is $\mathrm{decay}^n$.
+/// The histogram can also be decayed explicitly using [`DecayingStreamHist::decay_by`], for example on a timer,
+/// so that the weights depend on the time rather than on the number of the inserts.
+///
+/// Instead of updating all the bins, the weights of the new values are increased, so the inserts are as cheap
+/// as for the [`StreamHist`]. The bins are merged using the same procedure as in [`StreamHist::insert`],
+/// with the weights in place of the counts.
+///
+/// # Examples
+///
+/// ```
+/// use histr::DecayingStreamHist;
+///
+/// let mut hist = DecayingStreamHist::new(10, 0.9);
+/// for _ in 0..100 {
+///     hist.insert(1.0);
+/// }
+/// for _ in 0..100 {
+///     hist.insert(10.0);
+/// }
+/// // the old values faded out
+/// assert!(hist.mean() > 9.99);
+/// assert!((hist.count() - 10.0).abs() < 1e-3);
+///
+/// let snapshot = hist.snapshot();
+/// assert_eq!(snapshot.median(), 10.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecayingStreamHist {
+    /// Sorted `(mean, weight)` pairs, the weights are relative to the `scale`.
+    bins: Vec<(f64, f64)>,
+    /// Smallest observed value.
+    min: f64,
+    /// Largest observed value.
+    max: f64,
+    /// Upper bound for the number of bins.
+    size: usize,
+    /// The multiplier applied to the weights before each insert.
+    decay: f64,
+    /// Weight of the newly inserted value.
+    scale: f64,
+}
+
+impl DecayingStreamHist {
+    /// Initialize an empty histogram with the number of bins equal to `size`, and the `decay` factor.
+    ///
+    /// # Panics
+    ///
+    /// The `decay` needs to be in the $(0, 1]$ range, otherwise it panics. For `decay` equal to `1.0`
+    /// the values do not decay unless calling the [`DecayingStreamHist::decay_by`] method.
+    pub fn new(size: usize, decay: f64) -> Self {
+        assert!(
+            decay > 0.0 && decay <= 1.0,
+            "decay {decay} needs to be between 0 and 1"
+        );
+        DecayingStreamHist {
+            bins: Vec::with_capacity(size + 1),
+            min: f64::NAN,
+            max: f64::NAN,
+            size,
+            decay,
+            scale: 1.0,
+        }
+    }
+
+    /// Decay the weights and insert a new point to the histogram.
+    ///
+    /// # Panics
+    ///
+    /// The `value` needs to be a number. It will panic on `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    pub fn insert<T: IntoSample>(&mut self, value: T) {
+        let value = value.into_sample();
+        assert!(value.is_finite(), "{value} is not a number");
+        let value = value + 0.0;
+        self.decay_by(self.decay);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+
+        let idx = self.bins.partition_point(|(mean, _)| *mean < value);
+        match self.bins.get_mut(idx) {
+            Some((mean, weight)) if *mean == value => *weight += self.scale,
+            _ => {
+                self.bins.insert(idx, (value, self.scale));
+                self.trim();
+            }
+        }
+    }
+
+    /// Multiply the weights of all the values in the histogram by the `factor`.
+    ///
+    /// # Panics
+    ///
+    /// The `factor` needs to be in the $(0, 1]$ range, otherwise it panics.
+    pub fn decay_by(&mut self, factor: f64) {
+        assert!(
+            factor > 0.0 && factor <= 1.0,
+            "factor {factor} needs to be between 0 and 1"
+        );
+        self.scale /= factor;
+        if self.scale > MAX_SCALE {
+            for (_, weight) in self.bins.iter_mut() {
+                *weight /= self.scale;
+            }
+            // the weights of the oldest values underflow, so they faded out completely
+            self.bins.retain(|(_, weight)| *weight > 0.0);
+            self.scale = 1.0;
+        }
+    }
+
+    /// Trim the histogram to have size not larger than `size`.
+    fn trim(&mut self) {
+        trim_bins(
+            &mut self.bins,
+            self.size,
+            MergePolicy::default(),
+            |bin| *bin,
+            |left, right| Some((weighted_mean(left, right), left.1 + right.1)),
+        );
+    }
+
+    /// Returns `true` if the histogram contains no data.
+    pub fn is_empty(&self) -> bool {
+        self.bins.is_empty()
+    }
+
+    /// The total weight of the values, the effective number of the values in the histogram.
+    pub fn count(&self) -> f64 {
+        self.bins.iter().map(|(_, weight)| weight).sum::<f64>() / self.scale
+    }
+
+    /// The weighted mean of the values. It returns `f64::NAN` for an empty histogram.
+    pub fn mean(&self) -> f64 {
+        let (sum, total) = self
+            .bins
+            .iter()
+            .fold((0.0, 0.0), |(sum, total), (mean, weight)| {
+                (sum + mean * weight, total + weight)
+            });
+        sum / total
+    }
+
+    /// Iterate over the `(mean, weight)` pairs of the bins.
+    pub fn iter(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.bins
+            .iter()
+            .map(|(mean, weight)| (*mean, weight / self.scale))
+    }
+
+    /// Convert to [`StreamHist`], with the weights of the bins rounded to the nearest integer counts.
+    ///
+    /// The bins with the weights smaller than one half are dropped. The `min` and `max` are the smallest
+    /// and largest values ever inserted, so they bound the values but may include the ones that faded out.
+    pub fn snapshot(&self) -> StreamHist {
+        let bins: Vec<Bin> = self
+            .iter()
+            .map(|(mean, weight)| Bin::new(mean, weight.round() as u64))
+            .filter(|bin| bin.count > 0)
+            .collect();
+        if bins.is_empty() {
+            return StreamHist::with_capacity(self.size);
+        }
+        StreamHist::from_parts(bins, self.min, self.max, self.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DecayingStreamHist;
+    use crate::hist::StreamHist;
+    use test_case::test_case;
+
+    #[test]
+    fn many_rescalings() {
+        let mut hist = DecayingStreamHist::new(4, 0.9);
+        for value in [1.0, 1.1, 1.2, 1.3] {
+            hist.insert(value);
+        }
+        for i in 0..20_000 {
+            hist.insert(if i % 2 == 0 { 1000.0 } else { 5000.0 });
+        }
+        hist.insert(1.6);
+        assert!(hist
+            .iter()
+            .all(|(mean, weight)| mean.is_finite() && weight > 0.0));
+        assert!(hist.mean().is_finite());
+        assert!(hist.snapshot().count() > 0.0);
+    }
+
+    #[test]
+    fn no_decay() {
+        let values = vec![5.0, 1.0, 2.0, 2.0, 8.0, 3.0];
+        let mut hist = DecayingStreamHist::new(3, 1.0);
+        let mut expected = StreamHist::with_capacity(3);
+        for value in values {
+            hist.insert(value);
+            expected.insert(value);
+        }
+        assert_eq!(hist.snapshot(), expected);
+        assert_eq!(hist.count(), 6.0);
+        assert_eq!(hist.mean(), expected.mean());
+    }
+
+    #[test]
+    fn decay() {
+        let mut hist = DecayingStreamHist::new(5, 0.5);
+        hist.insert(1.0);
+        hist.insert(2.0);
+        hist.insert(3.0);
+        assert_eq!(
+            hist.iter().collect::<Vec<_>>(),
+            vec![(1.0, 0.25), (2.0, 0.5), (3.0, 1.0)]
+        );
+        assert_eq!(hist.count(), 1.75);
+
+        hist.decay_by(0.5);
+        assert_eq!(hist.count(), 0.875);
+        let snapshot = hist.snapshot();
+        assert_eq!(snapshot.count(), 1.0);
+        assert_eq!(snapshot.mean(), 3.0);
+    }
+
+    #[test]
+    fn rescale() {
+        let mut hist = DecayingStreamHist::new(5, 0.5);
+        for _ in 0..1000 {
+            hist.insert(1.0);
+        }
+        assert!(hist.scale.is_finite());
+        assert!((hist.count() - 2.0).abs() < 1e-9);
+        assert_eq!(hist.mean(), 1.0);
+    }
+
+    #[test]
+    fn empty() {
+        let hist = DecayingStreamHist::new(5, 0.5);
+        assert!(hist.is_empty());
+        assert_eq!(hist.count(), 0.0);
+        assert!(hist.mean().is_nan());
+        assert!(hist.snapshot().is_empty());
+    }
+
+    #[test_case(0.0 ; "zero")]
+    #[test_case(1.5 ; "larger than one")]
+    #[test_case(f64::NAN ; "NaN")]
+    #[should_panic]
+    fn invalid_decay(decay: f64) {
+        DecayingStreamHist::new(5, decay);
+    }
+}
@@ -1,5 +1,11 @@
+use crate::fit::normal_cdf;
 use crate::hist::StreamHist;
 
+/// Maximal number of the bisection steps in [`KernelDensity::quantile`].
+const QUANTILE_ITERATIONS: usize = 100;
+/// The number of points at which the density is evaluated when searching for the [`KernelDensity::modes`].
+const MODES_GRID_POINTS: usize = 1000;
+
 /// Weighted [kernel density] estimator for the [`StreamHist`].
 ///
 /// [kernel density]: https://en.wikipedia.org/wiki/Kernel_density_estimation
@@ -8,9 +14,34 @@ pub struct KernelDensity {
     hist: StreamHist,
     /// Bandwidth of the kernels in the kernel density estimator. It is chosen automatically but may be adjusted.
     pub bandwidth: f64,
+    /// The kernel function, by default the [`Kernel::Triangular`] kernel.
+    pub kernel: Kernel,
 }
 
 impl KernelDensity {
+    /// Initialize kernel density estimator from the streaming histogram using the `kernel` function.
+    ///
+    /// The `bandwidth` is picked automatically using the [`bandwidth::auto`] rule of thumb.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Kernel, KernelDensity, StreamHist};
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 2.5, 3.0, 5.0]);
+    /// let kde = KernelDensity::with_kernel(hist, Kernel::Gaussian);
+    /// // the Gaussian kernel is positive everywhere
+    /// assert!(kde.density(8.0) > 0.0);
+    /// ```
+    pub fn with_kernel(hist: StreamHist, kernel: Kernel) -> Self {
+        let bandwidth = bandwidth::auto(&hist);
+        KernelDensity {
+            hist,
+            bandwidth,
+            kernel,
+        }
+    }
+
     /// Evaluate weighted kernel density estimator at the `value`.
     ///
     /// # NaN propagation
@@ -36,10 +67,244 @@ impl KernelDensity {
         }
         self.hist.iter().fold(0.0, |acc, bin| {
             let u = (value - bin.mean) / self.bandwidth;
-            let d = kernel::triangular(u) * bin.count as f64;
+            let d = self.kernel.evaluate(u) * bin.count as f64;
             acc + d
         }) / (self.hist.count() * self.bandwidth)
     }
+
+    /// Evaluate the kernel density estimator on the grid of `points` equally spaced values from `from` to `to`.
+    ///
+    /// Returns the `(value, density)` pairs, the same as calling [`KernelDensity::density`] for each
+    /// of the values. The bins are iterated once, adding their kernels only to the points within
+    /// the kernel's support, so it is faster than evaluating the points one by one, e.g. when plotting.
+    ///
+    /// # Panics
+    ///
+    /// It panics if `from` is larger than `to`, or any of them is `f64::NAN`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{KernelDensity, StreamHist};
+    ///
+    /// let kde = KernelDensity::from(StreamHist::from(vec![1.0, 2.0, 2.5, 3.0, 5.0]));
+    /// let grid = kde.evaluate_grid(0.0, 6.0, 13);
+    /// assert_eq!(grid.len(), 13);
+    /// for (x, density) in grid {
+    ///     assert!((density - kde.density(x)).abs() < 1e-12);
+    /// }
+    /// ```
+    pub fn evaluate_grid(&self, from: f64, to: f64, points: usize) -> Vec<(f64, f64)> {
+        let xs: Vec<f64> = crate::grid(from, to, points).collect();
+        let mut densities = vec![0.0; xs.len()];
+        let width = self.kernel.support() * self.bandwidth;
+        for bin in self.hist.iter() {
+            let start = xs.partition_point(|x| *x < bin.mean - width);
+            let end = xs.partition_point(|x| *x <= bin.mean + width);
+            for (x, density) in xs[start..end].iter().zip(&mut densities[start..end]) {
+                let u = (x - bin.mean) / self.bandwidth;
+                *density += self.kernel.evaluate(u) * bin.count as f64;
+            }
+        }
+        let norm = self.hist.count() * self.bandwidth;
+        xs.into_iter()
+            .zip(densities)
+            .map(|(x, density)| (x, density / norm))
+            .collect()
+    }
+
+    /// Evaluate the cumulative distribution function of the kernel density estimator at the `value`.
+    ///
+    /// It is the weighted sum of the cumulative distribution functions of the kernels, so it is a smooth
+    /// alternative to the piecewise linear [`StreamHist::cdf`].
+    ///
+    /// It returns `f64::NAN` for an empty histogram.
+    ///
+    /// # NaN propagation
+    ///
+    /// If `value` is `f64::NAN`, it will return `f64::NAN`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{KernelDensity, StreamHist};
+    ///
+    /// let kde = KernelDensity::from(StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]));
+    /// assert_eq!(kde.cdf(3.0), 0.5);
+    /// assert_eq!(kde.cdf(100.0), 1.0);
+    /// ```
+    pub fn cdf(&self, value: f64) -> f64 {
+        if value.is_nan() {
+            return f64::NAN;
+        }
+        self.hist.iter().fold(0.0, |acc, bin| {
+            let u = (value - bin.mean) / self.bandwidth;
+            acc + self.kernel.cdf(u) * bin.count as f64
+        }) / self.hist.count()
+    }
+
+    /// Quantile of the kernel density estimator for the probability `prob`, the inverse of the
+    /// [`KernelDensity::cdf`].
+    ///
+    /// It is found numerically using the bisection method. For `prob` equal to `0.0` or `1.0`, it returns
+    /// the edges of the support of the estimator, i.e. the smallest and largest bin means shifted by the
+    /// `bandwidth` (for the Gaussian kernel, by forty bandwidths, since it is not bounded).
+    ///
+    /// It returns `f64::NAN` for an empty histogram.
+    ///
+    /// # Panics
+    ///
+    /// `prob` needs to be a probability value between `0.0` and `1.0` (inclusive), otherwise it panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{KernelDensity, StreamHist};
+    ///
+    /// let kde = KernelDensity::from(StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]));
+    /// assert!((kde.quantile(0.5) - 3.0).abs() < 1e-9);
+    /// assert!(kde.quantile(0.9) > 4.0);
+    /// ```
+    pub fn quantile(&self, prob: f64) -> f64 {
+        assert!(
+            (0.0..=1.0).contains(&prob),
+            "{prob} is not a valid probability"
+        );
+        let (Some(first), Some(last)) = (self.hist.bins.first(), self.hist.bins.last()) else {
+            return f64::NAN;
+        };
+        let width = self.kernel.support() * self.bandwidth;
+        let (mut lo, mut hi) = (first.mean - width, last.mean + width);
+        if prob == 0.0 {
+            return lo;
+        }
+        if prob == 1.0 {
+            return hi;
+        }
+        for _ in 0..QUANTILE_ITERATIONS {
+            let mid = lo + (hi - lo) / 2.0;
+            if mid <= lo || mid >= hi {
+                break;
+            }
+            if self.cdf(mid) < prob {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        lo + (hi - lo) / 2.0
+    }
+
+    /// Locations of the local maxima (modes) of the kernel density estimator, sorted in ascending order.
+    ///
+    /// The density is evaluated on a grid spanning the bin means extended by the `bandwidth`. Only the peaks
+    /// with the [prominence] of at least `min_prominence` times the highest density are returned, so e.g.
+    /// `0.1` ignores the small bumps below 10% of the highest peak, while `0.0` returns all the local maxima.
+    /// For flat peaks, the middle of the flat region is returned.
+    ///
+    /// It returns an empty vector for an empty histogram.
+    ///
+    /// [prominence]: https://en.wikipedia.org/wiki/Topographic_prominence
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{KernelDensity, StreamHist};
+    ///
+    /// // bimodal latencies, e.g. the cache hits and misses
+    /// let mut hist = StreamHist::with_capacity(20);
+    /// for i in 0..100 {
+    ///     hist.insert(1.0 + (i % 10) as f64 / 10.0);
+    ///     hist.insert(10.0 + (i % 10) as f64 / 10.0);
+    /// }
+    /// let kde = KernelDensity::from(hist);
+    /// let modes = kde.modes(0.1);
+    /// assert_eq!(modes.len(), 2);
+    /// assert!(modes[0] > 1.0 && modes[0] < 2.0);
+    /// assert!(modes[1] > 10.0 && modes[1] < 11.0);
+    /// ```
+    pub fn modes(&self, min_prominence: f64) -> Vec<f64> {
+        let (Some(first), Some(last)) = (self.hist.bins.first(), self.hist.bins.last()) else {
+            return Vec::new();
+        };
+        let (from, to) = (first.mean - self.bandwidth, last.mean + self.bandwidth);
+        if self.bandwidth.is_nan() || from >= to {
+            // a single bin with a zero bandwidth
+            return vec![first.mean];
+        }
+        let (xs, ys): (Vec<f64>, Vec<f64>) = self
+            .evaluate_grid(from, to, MODES_GRID_POINTS)
+            .into_iter()
+            .unzip();
+        let max_density = ys.iter().copied().fold(0.0, f64::max);
+
+        let mut modes = Vec::new();
+        let mut i = 0;
+        while i < ys.len() {
+            let height = ys[i];
+            // the end of the flat region starting at `i`
+            let j = i + ys[i..].iter().take_while(|y| **y == height).count();
+            let rising = i == 0 || ys[i - 1] < height;
+            let falling = j == ys.len() || ys[j] < height;
+            if rising && falling {
+                // the lowest points between the peak and the higher peaks on both sides,
+                // the density outside of the grid decreases to zero
+                let lowest = |ys: &[f64]| ys.iter().fold(height, |acc, y| acc.min(*y));
+                let left = match ys[..i].iter().rposition(|y| *y > height) {
+                    Some(k) => lowest(&ys[k + 1..i]),
+                    None => 0.0,
+                };
+                let right = match ys[j..].iter().position(|y| *y > height) {
+                    Some(k) => lowest(&ys[j..j + k]),
+                    None => 0.0,
+                };
+                let prominence = height - left.max(right);
+                if prominence >= min_prominence * max_density {
+                    modes.push((xs[i] + xs[j - 1]) / 2.0);
+                }
+            }
+            i = j;
+        }
+        modes
+    }
+
+    /// Draw a random value from the kernel density estimator.
+    ///
+    /// It picks a bin with the probability proportional to its count and adds to its mean
+    /// the noise drawn from the `kernel` scaled by the `bandwidth`.
+    ///
+    /// It returns `f64::NAN` for an empty histogram.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{KernelDensity, StreamHist};
+    /// use rand::{rngs::StdRng, SeedableRng};
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    /// let kde = KernelDensity::from(hist);
+    ///
+    /// let mut rng = StdRng::seed_from_u64(42);
+    /// let value = kde.sample(&mut rng);
+    /// assert!(value >= 1.0 - kde.bandwidth && value <= 5.0 + kde.bandwidth);
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        if self.hist.is_empty() {
+            return f64::NAN;
+        }
+        let target = rng.random_range(0..crate::bins::sum_counts(&self.hist.bins));
+        let mut sum = 0;
+        let bin = self
+            .hist
+            .iter()
+            .find(|bin| {
+                sum += bin.count;
+                sum > target
+            })
+            .expect("the target is smaller than the total count");
+        bin.mean + self.kernel.sample(rng) * self.bandwidth
+    }
 }
 
 impl From<StreamHist> for KernelDensity {
@@ -47,41 +312,152 @@ impl From<StreamHist> for KernelDensity {
     ///
     /// The `bandwidth` is picked automatically using the [`bandwidth::auto`] rule of thumb.
     fn from(hist: StreamHist) -> Self {
-        let bandwidth = bandwidth::auto(&hist);
-        KernelDensity { hist, bandwidth }
+        KernelDensity::with_kernel(hist, Kernel::default())
     }
 }
 
-mod kernel {
-    #![allow(dead_code)]
+impl StreamHist {
+    /// Locations of the local maxima (modes) of the density estimated from the histogram, using the
+    /// [`KernelDensity`] with the default kernel and bandwidth, see [`KernelDensity::modes`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 2.0, 2.0, 3.0]);
+    /// assert_eq!(hist.modes(0.1).len(), 1);
+    /// assert!(StreamHist::default().modes(0.1).is_empty());
+    /// ```
+    pub fn modes(&self, min_prominence: f64) -> Vec<f64> {
+        KernelDensity::from(self.clone()).modes(min_prominence)
+    }
+}
+
+/// Kernel functions for the [`KernelDensity`] estimator.
+///
+/// All the kernels are symmetric probability densities with zero mean. The kernels other than
+/// [`Kernel::Gaussian`] are zero outside of the $[-1, 1]$ range.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Kernel {
+    /// Triangular kernel $1 - |u|$.
+    #[default]
+    Triangular,
+    /// Gaussian kernel $\frac{1}{\sqrt{2\pi}} \exp(-\frac{1}{2} u^2)$.
+    Gaussian,
+    /// Epanechnikov kernel $\frac{3}{4} (1 - u^2)$.
+    Epanechnikov,
+    /// Uniform kernel $\frac{1}{2}$.
+    Uniform,
+}
 
-    /// Triangular kernel `1 - |u|` for `value <= 1`.
+impl Kernel {
+    /// Evaluate the kernel function at the `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::Kernel;
+    ///
+    /// assert_eq!(Kernel::Triangular.evaluate(0.5), 0.5);
+    /// assert_eq!(Kernel::Uniform.evaluate(2.0), 0.0);
+    /// ```
     #[inline]
-    pub fn triangular(value: f64) -> f64 {
-        1.0 - value.abs().min(1.0)
+    pub fn evaluate(&self, value: f64) -> f64 {
+        match self {
+            Kernel::Triangular => 1.0 - value.abs().min(1.0),
+            Kernel::Gaussian => {
+                use std::f64::consts::PI;
+                (-0.5 * value.powi(2)).exp() / (2.0 * PI).sqrt()
+            }
+            Kernel::Epanechnikov => {
+                let u = value.abs().min(1.0);
+                0.75 * (1.0 - u.powi(2))
+            }
+            Kernel::Uniform => {
+                if value.abs() <= 1.0 {
+                    0.5
+                } else {
+                    0.0
+                }
+            }
+        }
     }
 
-    /// Gaussian kernel `1/sqrt(2pi) * exp(-1/2 * u^2)`.
+    /// Evaluate the cumulative distribution function of the kernel at the `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::Kernel;
+    ///
+    /// assert_eq!(Kernel::Epanechnikov.cdf(0.0), 0.5);
+    /// assert_eq!(Kernel::Triangular.cdf(-1.0), 0.0);
+    /// assert_eq!(Kernel::Uniform.cdf(0.5), 0.75);
+    /// ```
     #[inline]
-    pub fn gaussian(value: f64) -> f64 {
-        use std::f64::consts::PI;
-        (-0.5 * value.powi(2)).exp() / (2.0 * PI).sqrt()
+    pub fn cdf(&self, value: f64) -> f64 {
+        match self {
+            Kernel::Triangular => {
+                let u = value.clamp(-1.0, 1.0);
+                if u < 0.0 {
+                    (1.0 + u).powi(2) / 2.0
+                } else {
+                    1.0 - (1.0 - u).powi(2) / 2.0
+                }
+            }
+            Kernel::Gaussian => normal_cdf(value),
+            Kernel::Epanechnikov => {
+                let u = value.clamp(-1.0, 1.0);
+                0.5 + 0.75 * u - 0.25 * u.powi(3)
+            }
+            Kernel::Uniform => (value.clamp(-1.0, 1.0) + 1.0) / 2.0,
+        }
     }
 
-    /// Epanechnikov kernel `3/4 * (1 - u^2)` for `value <= 1`.
+    /// Standard deviation of the distribution with the density given by the kernel.
     #[inline]
-    pub fn epanechnikov(value: f64) -> f64 {
-        let u = value.abs().min(1.0);
-        0.75 * (1.0 - u.powi(2))
+    pub(crate) fn stdev(&self) -> f64 {
+        match self {
+            Kernel::Triangular => 6.0_f64.sqrt().recip(),
+            Kernel::Gaussian => 1.0,
+            Kernel::Epanechnikov => 5.0_f64.sqrt().recip(),
+            Kernel::Uniform => 3.0_f64.sqrt().recip(),
+        }
     }
 
-    /// Uniform kernel `1/2` for `value <= 1`.
+    /// Half-width of the range outside of which the kernel is (numerically) zero.
     #[inline]
-    pub fn uniform(value: f64) -> f64 {
-        if value.abs() <= 1.0 {
-            0.5
-        } else {
-            0.0
+    fn support(&self) -> f64 {
+        match self {
+            // the standard normal cdf is 0 or 1 in the floating point precision
+            Kernel::Gaussian => 40.0,
+            _ => 1.0,
+        }
+    }
+
+    /// Draw a random value from the distribution with the density given by the kernel.
+    #[cfg(feature = "rand")]
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        let mut uniform = || 2.0 * rng.random::<f64>() - 1.0;
+        match self {
+            // sum of two uniform variables is triangular on [0, 2]
+            Kernel::Triangular => (uniform() + uniform()) / 2.0,
+            // Box–Muller transform
+            Kernel::Gaussian => {
+                let (u1, u2) = (1.0 - rng.random::<f64>(), rng.random::<f64>());
+                (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+            }
+            // Devroye (1986), Non-Uniform Random Variate Generation, p. 237
+            Kernel::Epanechnikov => {
+                let (u1, u2, u3) = (uniform(), uniform(), uniform());
+                if u3.abs() >= u2.abs() && u3.abs() >= u1.abs() {
+                    u2
+                } else {
+                    u3
+                }
+            }
+            Kernel::Uniform => uniform(),
         }
     }
 }
@@ -120,6 +496,7 @@ pub mod bandwidth {
     //! [*Bandwidth Selection in Kernel Density Estimation: A Review*.](
     //! https://www.researchgate.net/publication/2316108_Bandwidth_Selection_in_Kernel_Density_Estimation_A_Review)
 
+    use super::Kernel;
     use crate::hist::StreamHist;
 
     /// Maximum of the [`sturges`] and [`fd`] bandwidth selection rules of thumb (as in Numpy).
@@ -160,6 +537,121 @@ pub mod bandwidth {
         0.9 * a * n.powf(-0.2)
     }
 
+    /// Least-squares cross-validation bandwidth selector for the default [`Kernel::Triangular`] kernel.
+    ///
+    /// See [`cv_for_kernel`] for details.
+    pub fn cv(hist: &StreamHist) -> f64 {
+        cv_for_kernel(hist, Kernel::default())
+    }
+
+    /// Least-squares cross-validation bandwidth selector for the `kernel`.
+    ///
+    /// It picks the bandwidth minimizing the estimated integrated squared error of the kernel density
+    /// estimator with the Gaussian kernel
+    ///
+    /// $$
+    /// \mathrm{LSCV}(h) = \int \hat f_h(x)^2 dx - \frac{2}{n} \sum_i \hat f_{h,-i}(x_i)
+    /// $$
+    ///
+    /// and rescales it to the `kernel` by matching the standard deviations of the kernels. Unlike the rules of
+    /// thumb, it adapts to the shape of the distribution, so it does not over-smooth the multimodal data.
+    /// Since all the values aggregated in a bin have the same mean, the leave-one-out estimates
+    /// $\hat f_{h,-i}$ leave out the whole bins, otherwise the ties would push the bandwidth towards zero.
+    /// The bandwidth is searched between the smallest distance between the bins and the range of the data.
+    /// It calculates the distances between all the pairs of bins, so it takes $O(n^2)$ time for $n$ bins.
+    ///
+    /// When the histogram has less than three bins, it falls back to the [`auto`] rule of thumb.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{bandwidth, KernelDensity, StreamHist};
+    ///
+    /// // two well separated modes
+    /// let mut hist = StreamHist::with_capacity(50);
+    /// for i in 0..1000 {
+    ///     let x = (i % 100) as f64 / 100.0;
+    ///     hist.insert(if i % 2 == 0 { x } else { x + 10.0 });
+    /// }
+    /// let mut kde = KernelDensity::from(hist.clone());
+    /// kde.bandwidth = bandwidth::cv(&hist);
+    /// assert!(kde.bandwidth < bandwidth::auto(&hist));
+    /// assert!(kde.density(5.0) < kde.density(0.5) / 100.0);
+    /// ```
+    ///
+    /// # References
+    ///
+    /// Rudemo, M. (1982). *Empirical Choice of Histograms and Kernel Density Estimators.*
+    /// Scandinavian Journal of Statistics, 9(2), 65-78.
+    pub fn cv_for_kernel(hist: &StreamHist, kernel: Kernel) -> f64 {
+        let bins = &hist.bins;
+        if bins.len() < 3 {
+            return auto(hist);
+        }
+        let lower = bins
+            .windows(2)
+            .map(|w| w[1].mean - w[0].mean)
+            .filter(|d| *d > 0.0)
+            .fold(f64::INFINITY, f64::min);
+        let upper = hist.max - hist.min;
+        if lower >= upper {
+            return auto(hist);
+        }
+
+        // coarse grid search on the log scale, then the golden section search around the minimum
+        let (lower, upper) = (lower.ln(), upper.ln());
+        let step = (upper - lower) / (CV_GRID_POINTS - 1) as f64;
+        let score = |log_h: f64| lscv(hist, log_h.exp());
+        let best = (0..CV_GRID_POINTS)
+            .map(|i| lower + step * i as f64)
+            .map(|log_h| (log_h, score(log_h)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(lower, |(log_h, _)| log_h);
+
+        let (mut a, mut b) = ((best - step).max(lower), (best + step).min(upper));
+        let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+        for _ in 0..CV_REFINE_ITERATIONS {
+            let (c, d) = (b - ratio * (b - a), a + ratio * (b - a));
+            if score(c) < score(d) {
+                b = d;
+            } else {
+                a = c;
+            }
+        }
+        ((a + b) / 2.0).exp() / kernel.stdev()
+    }
+
+    /// The number of the candidate bandwidths checked by [`cv_for_kernel`].
+    const CV_GRID_POINTS: usize = 50;
+    /// The number of the golden section search steps in [`cv_for_kernel`].
+    const CV_REFINE_ITERATIONS: usize = 30;
+
+    /// The least-squares cross-validation score for the Gaussian kernel with the bandwidth `h`,
+    /// leaving out whole bins.
+    fn lscv(hist: &StreamHist, h: f64) -> f64 {
+        use std::f64::consts::PI;
+        // the density of N(0, 2) is the convolution of two standard normal densities
+        let phi = |u: f64| (-0.5 * u * u).exp() / (2.0 * PI).sqrt();
+        let phi2 = |u: f64| (-0.25 * u * u).exp() / (4.0 * PI).sqrt();
+
+        let n = hist.count();
+        let (mut squared, mut leave_out) = (0.0, 0.0);
+        for (i, a) in hist.bins.iter().enumerate() {
+            let wa = a.count as f64;
+            let mut density = 0.0;
+            for (j, b) in hist.bins.iter().enumerate() {
+                let wb = b.count as f64;
+                let u = (a.mean - b.mean) / h;
+                squared += wa * wb * phi2(u);
+                if i != j {
+                    density += wb * phi(u);
+                }
+            }
+            leave_out += wa * density / ((n - wa) * h);
+        }
+        squared / (n * n * h) - 2.0 * leave_out / n
+    }
+
     impl StreamHist {
         /// Interquartile range calculated using the fast approximations for the quantiles.
         #[inline]
@@ -171,8 +663,9 @@ pub mod bandwidth {
 
 #[cfg(test)]
 mod tests {
-    use super::KernelDensity;
+    use super::{Kernel, KernelDensity};
     use crate::hist::StreamHist;
+    use test_case::test_case;
 
     #[test]
     fn empty_histogram() {
@@ -196,6 +689,182 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "rand")]
+    #[test]
+    fn sample() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut hist = StreamHist::from(vec![1.0, 2.0, 2.0, 3.0, 4.0, 5.0, 10.0]);
+        hist.resize(4);
+        let kde = KernelDensity::from(hist.clone());
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let n = 10_000;
+        let samples: Vec<f64> = (0..n).map(|_| kde.sample(&mut rng)).collect();
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        assert!((mean - hist.mean()).abs() < 0.1);
+        assert!(samples
+            .iter()
+            .all(|x| *x >= hist.min() - kde.bandwidth && *x <= hist.max() + kde.bandwidth));
+
+        let kde = KernelDensity::from(StreamHist::default());
+        assert!(kde.sample(&mut rng).is_nan());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test_case(Kernel::Triangular, 1.0 / 6.0 ; "triangular")]
+    #[test_case(Kernel::Gaussian, 1.0 ; "gaussian")]
+    #[test_case(Kernel::Epanechnikov, 0.2 ; "epanechnikov")]
+    #[test_case(Kernel::Uniform, 1.0 / 3.0 ; "uniform")]
+    fn kernel_sample(kernel: Kernel, variance: f64) {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let n = 100_000;
+        let samples: Vec<f64> = (0..n).map(|_| kernel.sample(&mut rng)).collect();
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let var = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64;
+        assert!(mean.abs() < 0.01);
+        assert!((var - variance).abs() < 0.01);
+    }
+
+    #[test_case(Kernel::Triangular ; "triangular")]
+    #[test_case(Kernel::Gaussian ; "gaussian")]
+    #[test_case(Kernel::Epanechnikov ; "epanechnikov")]
+    #[test_case(Kernel::Uniform ; "uniform")]
+    fn kernel_cdf(kernel: Kernel) {
+        let step = 0.001;
+        let mut total = kernel.cdf(-3.0);
+        for i in -3000..3000 {
+            let x = i as f64 * step;
+            assert!((kernel.cdf(x) - total).abs() < 1e-3, "{x}");
+            total += kernel.evaluate(x + step / 2.0) * step;
+        }
+        assert!((kernel.cdf(0.0) - 0.5).abs() < 1e-7);
+        assert_eq!(kernel.cdf(-50.0), 0.0);
+        assert_eq!(kernel.cdf(50.0), 1.0);
+    }
+
+    #[test_case(Kernel::Triangular ; "triangular")]
+    #[test_case(Kernel::Gaussian ; "gaussian")]
+    #[test_case(Kernel::Epanechnikov ; "epanechnikov")]
+    #[test_case(Kernel::Uniform ; "uniform")]
+    fn cdf_and_quantile(kernel: Kernel) {
+        let mut hist = StreamHist::from(vec![1.0, 2.0, 2.0, 3.0, 4.0, 5.0, 10.0]);
+        hist.resize(4);
+        let kde = KernelDensity::with_kernel(hist, kernel);
+
+        let mut prev = 0.0;
+        for i in 0..100 {
+            let x = i as f64 / 5.0 - 5.0;
+            let p = kde.cdf(x);
+            assert!(p >= prev);
+            prev = p;
+        }
+        for prob in [0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99] {
+            assert!((kde.cdf(kde.quantile(prob)) - prob).abs() < 1e-6, "{prob}");
+        }
+        assert!(kde.cdf(kde.quantile(0.0)) < 1e-9);
+        assert!(kde.cdf(kde.quantile(1.0)) > 1.0 - 1e-9);
+        assert!(kde.cdf(f64::NAN).is_nan());
+    }
+
+    #[test_case(Kernel::Triangular ; "triangular")]
+    #[test_case(Kernel::Gaussian ; "gaussian")]
+    #[test_case(Kernel::Epanechnikov ; "epanechnikov")]
+    #[test_case(Kernel::Uniform ; "uniform")]
+    fn evaluate_grid(kernel: Kernel) {
+        let mut hist = StreamHist::from(vec![1.0, 2.0, 2.0, 3.0, 4.0, 5.0, 10.0]);
+        hist.resize(4);
+        let kde = KernelDensity::with_kernel(hist, kernel);
+
+        let grid = kde.evaluate_grid(-5.0, 15.0, 201);
+        assert_eq!(grid.len(), 201);
+        for (x, density) in grid {
+            assert!((density - kde.density(x)).abs() < 1e-12, "{x}");
+        }
+        assert!(kde.evaluate_grid(0.0, 1.0, 0).is_empty());
+    }
+
+    #[test]
+    fn modes() {
+        let mut hist = StreamHist::with_capacity(30);
+        for i in 0..1000 {
+            // the larger peak at 0, the smaller one at 10, and a tiny one at 20
+            hist.insert((i % 7) as f64 / 7.0);
+            if i % 3 == 0 {
+                hist.insert(10.0 + (i % 5) as f64 / 5.0);
+            }
+            if i % 100 == 0 {
+                hist.insert(20.0);
+            }
+        }
+        let kde = KernelDensity::with_kernel(hist, Kernel::Gaussian);
+
+        let modes = kde.modes(0.0);
+        assert_eq!(modes.len(), 3);
+        assert!(modes[0].abs() < 1.0, "{modes:?}");
+        assert!((modes[1] - 10.5).abs() < 1.0, "{modes:?}");
+        assert!((modes[2] - 20.0).abs() < 1.0, "{modes:?}");
+
+        // the tiny peak is ignored
+        assert_eq!(kde.modes(0.1).len(), 2);
+        // only the highest peak is left
+        assert_eq!(kde.modes(0.99).len(), 1);
+
+        // all values are the same
+        let kde = KernelDensity::from(StreamHist::from(vec![5.0, 5.0, 5.0]));
+        assert_eq!(kde.modes(0.1), vec![5.0]);
+    }
+
+    #[test]
+    fn cv() {
+        use super::bandwidth;
+
+        let mut hist = StreamHist::with_capacity(3);
+        hist.insert(1.0);
+        hist.insert(2.0);
+        assert_eq!(bandwidth::cv(&hist), bandwidth::auto(&hist));
+
+        let mut hist = StreamHist::with_capacity(40);
+        for i in 0..2000 {
+            let x = ((i * 7919) % 1000) as f64 / 1000.0;
+            hist.insert(match i % 3 {
+                0 => x,
+                1 => x + 5.0,
+                _ => x + 10.0,
+            });
+        }
+        let h = bandwidth::cv_for_kernel(&hist, Kernel::Gaussian);
+        assert!(h > 0.05 && h < 1.0, "{h}");
+        assert!((bandwidth::cv(&hist) - h * 6.0_f64.sqrt()).abs() < 1e-9);
+
+        let mut kde = KernelDensity::with_kernel(hist, Kernel::Gaussian);
+        kde.bandwidth = h;
+        assert!(kde.density(2.75) < kde.density(0.5) / 10.0);
+        assert!(kde.density(7.75) < kde.density(5.5) / 10.0);
+    }
+
+    #[test]
+    fn empty_quantile() {
+        let kde = KernelDensity::from(StreamHist::default());
+        assert!(kde.quantile(0.5).is_nan());
+        assert!(kde.cdf(1.0).is_nan());
+    }
+
+    #[test_case(Kernel::Triangular ; "triangular")]
+    #[test_case(Kernel::Gaussian ; "gaussian")]
+    #[test_case(Kernel::Epanechnikov ; "epanechnikov")]
+    #[test_case(Kernel::Uniform ; "uniform")]
+    fn kernel_integrates_to_one(kernel: Kernel) {
+        let step = 0.001;
+        let total: f64 = (-5000..5000)
+            .map(|i| kernel.evaluate((i as f64 + 0.5) * step) * step)
+            .sum();
+        assert!((total - 1.0).abs() < 1e-3);
+        assert_eq!(kernel.evaluate(0.3), kernel.evaluate(-0.3));
+    }
+
     #[test]
     fn density_nan() {
         let hist = StreamHist::from(vec![1.0, 2.0, 2.0, 3.0, 4.0, 5.0]);
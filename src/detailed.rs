@@ -0,0 +1,385 @@
+use crate::hist::{trim_bins, MergePolicy, StreamHist};
+use crate::{bins::Bin, sample::IntoSample};
+use std::ops;
+
+/// Bin of a [`DetailedStreamHist`] histogram.
+///
+/// Besides the mean and the count, as in [`Bin`], it keeps the sum of squared differences of the merged
+/// values from the mean, so the spread of the values within the bin is not lost when merging the bins.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetailedBin {
+    mean: f64,
+    count: u64,
+    /// Sum of squared differences of the values from the `mean`.
+    m2: f64,
+}
+
+impl DetailedBin {
+    /// Mean of the values in the bin.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// The count of the values in the bin.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Variance of the values within the bin, zero for a bin of a single value.
+    pub fn variance(&self) -> f64 {
+        self.m2 / self.count as f64
+    }
+
+    /// The range of the uniform distribution with the same mean and variance as the values in the bin.
+    fn range(&self) -> (f64, f64) {
+        let half_width = (3.0 * self.variance()).sqrt();
+        (self.mean - half_width, self.mean + half_width)
+    }
+}
+
+impl From<f64> for DetailedBin {
+    /// Initialize a bin from a single value.
+    fn from(value: f64) -> Self {
+        DetailedBin {
+            mean: value,
+            count: 1,
+            m2: 0.0,
+        }
+    }
+}
+
+impl From<DetailedBin> for Bin {
+    /// Drop the within-bin spread.
+    fn from(bin: DetailedBin) -> Self {
+        Bin::new(bin.mean, bin.count)
+    }
+}
+
+impl ops::Add<DetailedBin> for DetailedBin {
+    type Output = DetailedBin;
+
+    /// Merge two bins, the same as for [`Bin`], and combine their sums of squares using the
+    /// [parallel algorithm] by Chan et al.
+    ///
+    /// [parallel algorithm]: https://en.wikipedia.org/wiki/Algorithms_for_calculating_variance#Parallel_algorithm
+    fn add(self, rhs: Self) -> Self::Output {
+        let total = self.count + rhs.count;
+        let delta = rhs.mean - self.mean;
+        let weight = self.count as f64 * rhs.count as f64 / total as f64;
+        DetailedBin {
+            mean: (self.mean * self.count as f64 + rhs.mean * rhs.count as f64) / total as f64,
+            count: total,
+            m2: self.m2 + rhs.m2 + delta.powi(2) * weight,
+        }
+    }
+}
+
+/// Streaming histogram that tracks the variance of the values within each of the bins.
+///
+/// It uses the same update procedure as [`StreamHist::insert`], so it has the same bin means and counts,
+/// but the bins are [`DetailedBin`]s that also keep the spread of the merged values. The [`StreamHist`]
+/// keeps only the centroids of the bins, so its variance misses the within-bin spread and is biased low.
+/// Here the variance is the sum of the between-bin and within-bin variances, that is exact for
+/// the inserted values.
+///
+/// For calculating the cumulative distribution function and the quantiles, the values in each bin are
+/// assumed to be uniformly distributed, with the same mean and variance as the values in the bin,
+/// instead of the trapezoids between the neighboring bin means used by [`StreamHist::quantile`].
+///
+/// # Examples
+///
+/// ```
+/// use histr::{DetailedStreamHist, StreamHist};
+///
+/// let mut hist = DetailedStreamHist::with_capacity(2);
+/// for value in [1.0, 2.0, 3.0, 10.0, 11.0, 12.0] {
+///     hist.insert(value);
+/// }
+/// assert_eq!(hist.bins()[0].mean(), 2.0);
+/// assert!((hist.bins()[0].variance() - 2.0 / 3.0).abs() < 1e-12);
+/// assert!((hist.variance() - 20.91666).abs() < 1e-4);
+///
+/// // the centroids only account for the variance between the bins
+/// let centroids = StreamHist::from(&hist);
+/// assert_eq!(centroids.variance(), 20.25);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetailedStreamHist {
+    /// Sorted bins.
+    bins: Vec<DetailedBin>,
+    /// Smallest observed value.
+    min: f64,
+    /// Largest observed value.
+    max: f64,
+    /// Upper bound for the number of bins.
+    size: usize,
+}
+
+impl DetailedStreamHist {
+    /// Initialize an empty histogram with the number of bins equal to `size`.
+    pub fn with_capacity(size: usize) -> Self {
+        DetailedStreamHist {
+            bins: Vec::with_capacity(size + 1),
+            min: f64::NAN,
+            max: f64::NAN,
+            size,
+        }
+    }
+
+    /// Insert a new point to the histogram, see [`StreamHist::insert`].
+    ///
+    /// # Panics
+    ///
+    /// The `value` needs to be a number. It will panic on `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    pub fn insert<T: IntoSample>(&mut self, value: T) {
+        let value = value.into_sample();
+        assert!(value.is_finite(), "{value} is not a number");
+        let value = value + 0.0;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+
+        let idx = self.bins.partition_point(|bin| bin.mean < value);
+        match self.bins.get_mut(idx) {
+            Some(bin) if bin.mean == value => bin.count += 1,
+            _ => {
+                self.bins.insert(idx, DetailedBin::from(value));
+                self.trim();
+            }
+        }
+    }
+
+    /// Trim the histogram to have size not larger than `size`.
+    fn trim(&mut self) {
+        trim_bins(
+            &mut self.bins,
+            self.size,
+            MergePolicy::default(),
+            |bin| (bin.mean, bin.count as f64),
+            |left, right| Some(left + right),
+        );
+    }
+
+    /// Sorted bins of the histogram.
+    pub fn bins(&self) -> &[DetailedBin] {
+        &self.bins
+    }
+
+    /// Returns `true` if the histogram contains no data.
+    pub fn is_empty(&self) -> bool {
+        self.bins.is_empty()
+    }
+
+    /// Smallest observed value, `f64::NAN` for an empty histogram.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Largest observed value, `f64::NAN` for an empty histogram.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// The total count of all the values used to create the histogram.
+    pub fn count(&self) -> f64 {
+        self.bins.iter().map(|bin| bin.count).sum::<u64>() as f64
+    }
+
+    /// Mean of the data, `f64::NAN` for an empty histogram.
+    pub fn mean(&self) -> f64 {
+        self.bins
+            .iter()
+            .fold(0.0, |acc, bin| acc + bin.mean * bin.count as f64)
+            / self.count()
+    }
+
+    /// Variance of the data, the sum of the variance of the bin means, as in [`StreamHist::variance`],
+    /// and the variances within the bins weighted by their counts. It is `f64::NAN` for an empty histogram.
+    pub fn variance(&self) -> f64 {
+        let mean = self.mean();
+        self.bins.iter().fold(0.0, |acc, bin| {
+            acc + bin.m2 + bin.count as f64 * (bin.mean - mean).powi(2)
+        }) / self.count()
+    }
+
+    /// Standard deviation of the data.
+    ///
+    /// Square root of the [`DetailedStreamHist::variance`].
+    pub fn stdev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Approximate count of the values smaller or equal to the `value`. If `left` is `true`,
+    /// the values equal to it are not counted.
+    fn count_by(&self, value: f64, left: bool) -> f64 {
+        self.bins
+            .iter()
+            .map(|bin| {
+                let (lower, upper) = bin.range();
+                let (lower, upper) = (lower.max(self.min), upper.min(self.max));
+                let share = if upper > lower {
+                    ((value - lower) / (upper - lower)).clamp(0.0, 1.0)
+                } else if value > bin.mean || (!left && value == bin.mean) {
+                    1.0
+                } else {
+                    0.0
+                };
+                share * bin.count as f64
+            })
+            .sum()
+    }
+
+    /// Approximate empirical cumulative distribution function, the fraction of the values smaller
+    /// or equal to the `value`.
+    pub fn cdf(&self, value: f64) -> f64 {
+        self.count_by(value, false) / self.count()
+    }
+
+    /// Approximate sample quantile.
+    ///
+    /// # Panics
+    ///
+    /// `prob` needs to be a probability value between `0.0` and `1.0` (inclusive),
+    /// otherwise it panics.
+    pub fn quantile(&self, prob: f64) -> f64 {
+        assert!(
+            (0.0..=1.0).contains(&prob),
+            "{prob} is not a valid probability"
+        );
+        if self.is_empty() {
+            return f64::NAN;
+        }
+        if prob == 0.0 {
+            return self.min;
+        }
+        if prob == 1.0 {
+            return self.max;
+        }
+
+        // the cumulative count is linear between the edges of the bins, and jumps
+        // at the means of the bins of the single values
+        let mut points: Vec<f64> = self
+            .bins
+            .iter()
+            .flat_map(|bin| {
+                let (lower, upper) = bin.range();
+                [lower.max(self.min), bin.mean, upper.min(self.max)]
+            })
+            .collect();
+        points.sort_by(f64::total_cmp);
+        points.dedup();
+
+        let target = prob * self.count();
+        let (mut prev_point, mut prev_count) = (self.min, 0.0);
+        for point in points {
+            let (before, after) = (self.count_by(point, true), self.count_by(point, false));
+            if target <= before && before > prev_count {
+                let share = (target - prev_count) / (before - prev_count);
+                return prev_point + share * (point - prev_point);
+            }
+            if target <= after {
+                return point;
+            }
+            (prev_point, prev_count) = (point, after);
+        }
+        self.max
+    }
+}
+
+impl From<&DetailedStreamHist> for StreamHist {
+    /// Convert to the [`StreamHist`] with the same size, dropping the within-bin spread.
+    fn from(hist: &DetailedStreamHist) -> Self {
+        if hist.is_empty() {
+            return StreamHist::with_capacity(hist.size);
+        }
+        let bins = hist.bins.iter().map(|bin| Bin::from(*bin)).collect();
+        StreamHist::from_parts(bins, hist.min, hist.max, hist.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DetailedBin, DetailedStreamHist};
+    use crate::hist::StreamHist;
+    use test_case::test_case;
+
+    #[test]
+    fn same_as_stream_hist() {
+        let values: Vec<f64> = (0..500).map(|i| ((i * 7919) % 613) as f64 / 7.0).collect();
+        let mut hist = DetailedStreamHist::with_capacity(10);
+        let mut expected = StreamHist::with_capacity(10);
+        for value in values.iter() {
+            hist.insert(*value);
+            expected.insert(*value);
+        }
+        let converted = StreamHist::from(&hist);
+        assert_eq!(converted.bins(), expected.bins());
+        assert_eq!(
+            (converted.min(), converted.max()),
+            (expected.min(), expected.max())
+        );
+        assert_eq!(hist.count(), expected.count());
+        assert!((hist.mean() - expected.mean()).abs() < 1e-9);
+
+        // the variance is exact, and larger than the variance of the centroids
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        assert!((hist.variance() - variance).abs() < 1e-6);
+        assert!(expected.variance() < hist.variance());
+    }
+
+    #[test]
+    fn add() {
+        let a = DetailedBin::from(1.0) + DetailedBin::from(3.0);
+        assert_eq!((a.mean(), a.count(), a.variance()), (2.0, 2, 1.0));
+        let b = a + DetailedBin::from(8.0);
+        assert_eq!((b.mean(), b.count()), (4.0, 3));
+        // the variance of [1, 3, 8]
+        assert!((b.variance() - 26.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test_case(0.0, 0.0 ; "min")]
+    #[test_case(0.1, 99.9 ; "p10")]
+    #[test_case(0.5, 499.5 ; "median")]
+    #[test_case(0.9, 899.1 ; "p90")]
+    #[test_case(1.0, 999.0 ; "max")]
+    fn quantile(prob: f64, expected: f64) {
+        let mut hist = DetailedStreamHist::with_capacity(5);
+        for i in 0..1000 {
+            hist.insert(i as f64);
+        }
+        let result = hist.quantile(prob);
+        assert!((result - expected).abs() < 15.0, "{result} != {expected}");
+        assert!((hist.cdf(result) - prob).abs() < 1e-9);
+    }
+
+    #[test]
+    fn single_values() {
+        let mut hist = DetailedStreamHist::with_capacity(10);
+        for value in [1.0, 2.0, 2.0, 3.0] {
+            hist.insert(value);
+        }
+        assert_eq!(hist.variance(), 0.5);
+        assert_eq!(hist.cdf(0.0), 0.0);
+        assert_eq!(hist.cdf(2.0), 0.75);
+        assert_eq!(hist.cdf(2.5), 0.75);
+        assert_eq!(hist.quantile(0.25), 1.0);
+        assert_eq!(hist.quantile(0.5), 2.0);
+        assert_eq!(hist.quantile(0.9), 3.0);
+    }
+
+    #[test]
+    fn empty() {
+        let hist = DetailedStreamHist::with_capacity(10);
+        assert!(hist.is_empty());
+        assert!(hist.mean().is_nan());
+        assert!(hist.variance().is_nan());
+        assert!(hist.quantile(0.5).is_nan());
+        assert_eq!(StreamHist::from(&hist), StreamHist::with_capacity(10));
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_nan() {
+        DetailedStreamHist::with_capacity(3).insert(f64::NAN);
+    }
+}
@@ -0,0 +1,171 @@
+//! Distances between the distributions summarized by two histograms, e.g. for detecting the drift
+//! between the baseline and the live data.
+//!
+//! The distances are calculated from the interpolated cumulative distribution functions
+//! (see [`StreamHist::cdf`]), evaluated at the bin means and the extremes of both histograms,
+//! and at the equally spaced points between them. All the functions return `f64::NAN` if any
+//! of the histograms is empty.
+//!
+//! # Examples
+//!
+//! ```
+//! use histr::distance;
+//! use histr::StreamHist;
+//!
+//! let baseline = StreamHist::from((0..100).map(|x| x as f64).collect::<Vec<_>>());
+//! let live = StreamHist::from((10..110).map(|x| x as f64).collect::<Vec<_>>());
+//!
+//! assert!((distance::kolmogorov_smirnov(&baseline, &live) - 0.1).abs() < 0.01);
+//! assert!((distance::wasserstein(&baseline, &live) - 10.0).abs() < 0.1);
+//! assert!((distance::total_variation(&baseline, &live) - 0.1).abs() < 0.01);
+//! ```
+
+use crate::hist::StreamHist;
+
+/// Number of the evaluation points per each interval between the consecutive bin means.
+const POINTS_PER_INTERVAL: usize = 10;
+
+/// The [Kolmogorov-Smirnov] statistic, the largest absolute difference between the cumulative
+/// distribution functions of the histograms.
+///
+/// [Kolmogorov-Smirnov]: https://en.wikipedia.org/wiki/Kolmogorov%E2%80%93Smirnov_test
+pub fn kolmogorov_smirnov(a: &StreamHist, b: &StreamHist) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return f64::NAN;
+    }
+    evaluation_points(a, b)
+        .into_iter()
+        .map(|x| (a.cdf(x) - b.cdf(x)).abs())
+        .fold(0.0, f64::max)
+}
+
+/// The first [Wasserstein distance] (earth mover's distance), the area between the cumulative
+/// distribution functions of the histograms, approximated using the trapezoidal rule.
+///
+/// It has the same unit as the values, e.g. for two histograms of the same data shifted
+/// by a constant it is approximately equal to the shift.
+///
+/// [Wasserstein distance]: https://en.wikipedia.org/wiki/Wasserstein_metric
+pub fn wasserstein(a: &StreamHist, b: &StreamHist) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return f64::NAN;
+    }
+    let points = evaluation_points(a, b);
+    let diffs: Vec<f64> = points
+        .iter()
+        .map(|x| (a.cdf(*x) - b.cdf(*x)).abs())
+        .collect();
+    points
+        .windows(2)
+        .zip(diffs.windows(2))
+        .map(|(x, d)| (x[1] - x[0]) * (d[0] + d[1]) / 2.0)
+        .sum()
+}
+
+/// The [total variation distance], the largest difference between the probabilities that the histograms
+/// assign to the same event, in the range from `0.0` for the same distributions to `1.0` for the distributions
+/// with disjoint supports.
+///
+/// It is calculated as a half of the sum of the absolute differences between the probabilities of the intervals
+/// between the evaluation points.
+///
+/// [total variation distance]: https://en.wikipedia.org/wiki/Total_variation_distance_of_probability_measures
+pub fn total_variation(a: &StreamHist, b: &StreamHist) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return f64::NAN;
+    }
+    let sum: f64 = probabilities(a, b).map(|(p, q)| (p - q).abs()).sum();
+    (sum / 2.0).min(1.0)
+}
+
+/// Pairs of the probabilities that the histograms assign to the consecutive intervals between
+/// the evaluation points, including the intervals below and above all of them.
+pub(crate) fn probabilities<'a>(
+    a: &'a StreamHist,
+    b: &'a StreamHist,
+) -> impl Iterator<Item = (f64, f64)> + 'a {
+    let mut points = evaluation_points(a, b);
+    // the values above the maximum, so the cumulative distribution functions reach one
+    points.push(f64::INFINITY);
+
+    let (mut prev_a, mut prev_b) = (0.0, 0.0);
+    points.into_iter().map(move |x| {
+        let (cdf_a, cdf_b) = (a.cdf(x), b.cdf(x));
+        let probs = ((cdf_a - prev_a).max(0.0), (cdf_b - prev_b).max(0.0));
+        (prev_a, prev_b) = (cdf_a, cdf_b);
+        probs
+    })
+}
+
+/// Sorted points where the cumulative distribution functions of both histograms are evaluated.
+fn evaluation_points(a: &StreamHist, b: &StreamHist) -> Vec<f64> {
+    let mut knots: Vec<f64> = [a.min, a.max, b.min, b.max]
+        .into_iter()
+        .chain(a.iter().map(|bin| bin.mean))
+        .chain(b.iter().map(|bin| bin.mean))
+        .collect();
+    knots.sort_by(f64::total_cmp);
+    knots.dedup();
+
+    let mut points = Vec::with_capacity(knots.len() * POINTS_PER_INTERVAL);
+    for w in knots.windows(2) {
+        let step = (w[1] - w[0]) / POINTS_PER_INTERVAL as f64;
+        points.extend((0..POINTS_PER_INTERVAL).map(|i| w[0] + step * i as f64));
+    }
+    points.extend(knots.last());
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{kolmogorov_smirnov, total_variation, wasserstein};
+    use crate::hist::StreamHist;
+
+    fn uniform(from: f64, to: f64, size: usize) -> StreamHist {
+        let mut hist = StreamHist::with_capacity(size);
+        for i in 0..=1000 {
+            hist.insert(from + (to - from) * i as f64 / 1000.0);
+        }
+        hist
+    }
+
+    #[test]
+    fn same() {
+        let hist = uniform(0.0, 1.0, 20);
+        assert_eq!(kolmogorov_smirnov(&hist, &hist), 0.0);
+        assert_eq!(wasserstein(&hist, &hist), 0.0);
+        assert_eq!(total_variation(&hist, &hist), 0.0);
+    }
+
+    #[test]
+    fn disjoint() {
+        let a = uniform(0.0, 1.0, 20);
+        let b = uniform(5.0, 6.0, 10);
+        assert_eq!(kolmogorov_smirnov(&a, &b), 1.0);
+        assert!((wasserstein(&a, &b) - 5.0).abs() < 0.01);
+        assert!((total_variation(&a, &b) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn symmetric() {
+        let a = uniform(0.0, 2.0, 20);
+        let b = uniform(1.0, 4.0, 15);
+        assert_eq!(kolmogorov_smirnov(&a, &b), kolmogorov_smirnov(&b, &a));
+        assert!((wasserstein(&a, &b) - wasserstein(&b, &a)).abs() < 1e-12);
+        assert!((total_variation(&a, &b) - total_variation(&b, &a)).abs() < 1e-12);
+
+        // the exact values for the uniform distributions
+        assert!((kolmogorov_smirnov(&a, &b) - 2.0 / 3.0).abs() < 0.02);
+        assert!((wasserstein(&a, &b) - 1.5).abs() < 0.01);
+        assert!((total_variation(&a, &b) - 2.0 / 3.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn empty() {
+        let hist = uniform(0.0, 1.0, 10);
+        let empty = StreamHist::default();
+        assert!(kolmogorov_smirnov(&hist, &empty).is_nan());
+        assert!(wasserstein(&empty, &hist).is_nan());
+        assert!(total_variation(&empty, &empty).is_nan());
+    }
+}
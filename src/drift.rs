@@ -0,0 +1,157 @@
+//! Detecting the drift of the distribution by comparing the histograms of the reference and the current data.
+//!
+//! The [`ks_test`] function is the two-sample [Kolmogorov-Smirnov test] between two histograms, and the
+//! [`DriftDetector`] keeps the reference histogram, e.g. of the training data, and flags the current histograms,
+//! e.g. of the live data, that differ from it.
+//!
+//! [Kolmogorov-Smirnov test]: https://en.wikipedia.org/wiki/Kolmogorov%E2%80%93Smirnov_test
+//!
+//! # Examples
+//!
+//! ```
+//! use histr::drift::DriftDetector;
+//! use histr::StreamHist;
+//!
+//! let reference = StreamHist::from((0..1000).map(|x| x as f64).collect::<Vec<_>>());
+//! let detector = DriftDetector::new(reference, 0.1);
+//!
+//! let same = StreamHist::from((0..1000).map(|x| x as f64 + 0.5).collect::<Vec<_>>());
+//! assert!(!detector.is_drift(&same));
+//!
+//! let shifted = StreamHist::from((200..1200).map(|x| x as f64).collect::<Vec<_>>());
+//! assert!(detector.is_drift(&shifted));
+//! assert!(detector.check(&shifted).p_value < 0.001);
+//! ```
+
+use crate::distance::kolmogorov_smirnov;
+use crate::fit::kolmogorov_survival;
+use crate::hist::StreamHist;
+
+/// Result of the two-sample test, see [`ks_test`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftReport {
+    /// The Kolmogorov-Smirnov statistic, the largest difference between the cumulative distribution functions,
+    /// see [`distance::kolmogorov_smirnov`](crate::distance::kolmogorov_smirnov).
+    pub statistic: f64,
+    /// The approximate p-value of the test, small values suggest that the histograms summarize
+    /// the data from different distributions.
+    pub p_value: f64,
+}
+
+/// Two-sample Kolmogorov-Smirnov test comparing the histograms.
+///
+/// The p-value uses the asymptotic Kolmogorov distribution, with the effective sample size $nm/(n+m)$
+/// calculated from the total counts of the histograms. Since the histograms approximate the data,
+/// the approximation errors add to the statistic, so for the large counts the p-values can be too small
+/// unless the histograms have enough bins.
+///
+/// It returns `f64::NAN`s if any of the histograms is empty.
+pub fn ks_test(reference: &StreamHist, current: &StreamHist) -> DriftReport {
+    let statistic = kolmogorov_smirnov(reference, current);
+    let (n, m) = (reference.count(), current.count());
+    let n = (n * m / (n + m)).sqrt();
+    DriftReport {
+        statistic,
+        p_value: kolmogorov_survival((n + 0.12 + 0.11 / n) * statistic),
+    }
+}
+
+/// Detector comparing the histograms to the reference histogram using the [`ks_test`].
+///
+/// The drift is flagged when the Kolmogorov-Smirnov statistic exceeds the `threshold`. Unlike the p-value,
+/// the statistic does not depend on the counts, so the same threshold can be used for the windows
+/// of different sizes, and it does not flag the negligible differences for the large counts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriftDetector {
+    reference: StreamHist,
+    threshold: f64,
+}
+
+impl DriftDetector {
+    /// Initialize the detector with the `reference` histogram and the `threshold` for the statistic.
+    ///
+    /// # Panics
+    ///
+    /// The `threshold` needs to be between `0.0` and `1.0` (inclusive), otherwise it panics.
+    pub fn new(reference: StreamHist, threshold: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&threshold),
+            "threshold {threshold} needs to be between 0 and 1"
+        );
+        DriftDetector {
+            reference,
+            threshold,
+        }
+    }
+
+    /// The reference histogram.
+    pub fn reference(&self) -> &StreamHist {
+        &self.reference
+    }
+
+    /// Replace the reference histogram, e.g. after retraining the model on the new data.
+    pub fn set_reference(&mut self, reference: StreamHist) {
+        self.reference = reference;
+    }
+
+    /// Compare the `current` histogram to the reference.
+    pub fn check(&self, current: &StreamHist) -> DriftReport {
+        ks_test(&self.reference, current)
+    }
+
+    /// Returns `true` if the statistic comparing the `current` histogram to the reference exceeds the threshold.
+    ///
+    /// It returns `false` if any of the histograms is empty.
+    pub fn is_drift(&self, current: &StreamHist) -> bool {
+        self.check(current).statistic > self.threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ks_test, DriftDetector};
+    use crate::hist::StreamHist;
+
+    fn hist(values: impl Iterator<Item = f64>) -> StreamHist {
+        let mut hist = StreamHist::with_capacity(20);
+        values.for_each(|x| hist.insert(x));
+        hist
+    }
+
+    #[test]
+    fn test() {
+        let reference = hist((0..1000).map(|i| (i % 100) as f64));
+        let same = hist((0..500).map(|i| (i % 100) as f64));
+        let shifted = hist((0..500).map(|i| (i % 100) as f64 + 30.0));
+
+        let result = ks_test(&reference, &same);
+        assert!(result.statistic < 0.01);
+        assert!(result.p_value > 0.9);
+
+        let result = ks_test(&reference, &shifted);
+        assert!((result.statistic - 0.3).abs() < 0.02);
+        assert!(result.p_value < 1e-10);
+
+        let result = ks_test(&reference, &StreamHist::default());
+        assert!(result.statistic.is_nan());
+        assert!(result.p_value.is_nan());
+    }
+
+    #[test]
+    fn detector() {
+        let mut detector = DriftDetector::new(hist((0..100).map(|i| i as f64)), 0.2);
+        let current = hist((10..110).map(|i| i as f64));
+        assert!(!detector.is_drift(&current));
+        assert!(!detector.is_drift(&StreamHist::default()));
+
+        detector.set_reference(hist((50..150).map(|i| i as f64)));
+        assert!(detector.is_drift(&current));
+        assert_eq!(detector.reference().min(), 50.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_threshold() {
+        DriftDetector::new(StreamHist::default(), 1.5);
+    }
+}
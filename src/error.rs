@@ -0,0 +1,89 @@
+use std::fmt;
+
+/// Errors returned by the fallible `histr` functions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Failed to insert a value to the histogram.
+    Insert(InsertError),
+    /// The parts of the histogram passed to [`StreamHist::try_from_parts`](crate::StreamHist::try_from_parts)
+    /// are not valid.
+    InvalidParts(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Insert(err) => err.fmt(f),
+            Error::InvalidParts(msg) => write!(f, "invalid histogram: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Insert(err) => Some(err),
+            Error::InvalidParts(_) => None,
+        }
+    }
+}
+
+impl From<InsertError> for Error {
+    fn from(err: InsertError) -> Self {
+        Error::Insert(err)
+    }
+}
+
+/// Error returned by [`StreamHist::try_insert`](crate::StreamHist::try_insert) for the values
+/// that are not numbers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InsertError {
+    /// The value was `f64::NAN`.
+    NotANumber,
+    /// The value was `f64::INFINITY` or `f64::NEG_INFINITY`.
+    Infinite(f64),
+}
+
+impl InsertError {
+    /// Check if the `value` can be inserted to the histogram.
+    pub(crate) fn check(value: f64) -> Result<(), InsertError> {
+        if value.is_nan() {
+            Err(InsertError::NotANumber)
+        } else if value.is_infinite() {
+            Err(InsertError::Infinite(value))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InsertError::NotANumber => write!(f, "NaN is not a number"),
+            InsertError::Infinite(value) => write!(f, "{value} is not a number"),
+        }
+    }
+}
+
+impl std::error::Error for InsertError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, InsertError};
+
+    #[test]
+    fn check() {
+        assert_eq!(InsertError::check(1.0), Ok(()));
+        assert_eq!(InsertError::check(f64::NAN), Err(InsertError::NotANumber));
+        assert_eq!(
+            InsertError::check(f64::NEG_INFINITY),
+            Err(InsertError::Infinite(f64::NEG_INFINITY))
+        );
+        assert_eq!(
+            Error::from(InsertError::Infinite(f64::INFINITY)).to_string(),
+            "inf is not a number"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use crate::hist::StreamHist;
+
+/// The number of the least significant bits of the mantissa that are rounded off before hashing,
+/// the remaining 40 bits give about 12 significant decimal digits.
+const ROUNDED_BITS: u32 = 12;
+
+// 64-bit FNV-1a parameters, see: http://www.isthe.com/chongo/tech/comp/fnv/
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+impl StreamHist {
+    /// Stable fingerprint of the histogram.
+    ///
+    /// It hashes the means and counts of the bins, and the `min` and `max` values, so the histograms
+    /// with the same data have the same fingerprints. The `size` of the histogram is not included.
+    /// The floating point values are rounded to about 12 significant digits before hashing, so the
+    /// differences due to the floating point arithmetic errors do not change the fingerprint.
+    ///
+    /// The hashing algorithm ([FNV-1a]) is fixed, so the fingerprints are the same across runs, platforms,
+    /// and versions of the compiler, and can be persisted.
+    ///
+    /// [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let mut hist = StreamHist::from(vec![1.0, 2.0, 3.0]);
+    /// let fingerprint = hist.fingerprint();
+    /// assert_eq!(fingerprint, StreamHist::from(vec![3.0, 2.0, 1.0]).fingerprint());
+    ///
+    /// hist.insert(4.0);
+    /// assert_ne!(fingerprint, hist.fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        hasher.write_u64(self.bins.len() as u64);
+        for bin in self.iter() {
+            hasher.write_u64(round(bin.mean));
+            hasher.write_u64(bin.count);
+        }
+        hasher.write_u64(round(self.min));
+        hasher.write_u64(round(self.max));
+        hasher.finish()
+    }
+}
+
+/// Round the mantissa of the `value` and return its bits, all the NaNs and zeros are treated as equal.
+fn round(value: f64) -> u64 {
+    if value.is_nan() {
+        return f64::NAN.to_bits();
+    }
+    if value == 0.0 {
+        return 0;
+    }
+    // the carry may overflow to the exponent, what is the correct rounding up
+    let half = 1 << (ROUNDED_BITS - 1);
+    let mask = !((1 << ROUNDED_BITS) - 1);
+    (value.to_bits() + half) & mask
+}
+
+/// The [FNV-1a] hash function, unlike the [`std::hash::DefaultHasher`], it is guaranteed to be stable.
+///
+/// [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/
+pub(crate) struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(FNV_OFFSET_BASIS)
+    }
+}
+
+impl Fnv1a {
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes())
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::round;
+    use crate::bins::Bin;
+    use crate::hist::StreamHist;
+
+    #[test]
+    fn fingerprint() {
+        let hist = StreamHist::from(vec![Bin::new(1.0, 2), Bin::new(2.5, 3)]);
+
+        // stable across releases
+        assert_eq!(StreamHist::default().fingerprint(), 0x814fc2490a3f5af5);
+        assert_eq!(hist.fingerprint(), hist.clone().fingerprint());
+
+        // size is ignored
+        let mut resized = hist.clone();
+        resized.resize(10);
+        assert_eq!(hist.fingerprint(), resized.fingerprint());
+
+        // tiny numerical differences are ignored
+        let noisy = StreamHist::from(vec![Bin::new(1.0 + 1e-15, 2), Bin::new(2.5, 3)]);
+        assert_eq!(hist.fingerprint(), noisy.fingerprint());
+
+        for other in [
+            StreamHist::from(vec![Bin::new(1.0, 2), Bin::new(2.5, 4)]),
+            StreamHist::from(vec![Bin::new(1.0, 2), Bin::new(2.6, 3)]),
+            StreamHist::from(vec![Bin::new(1.0, 5)]),
+            StreamHist {
+                min: 0.0,
+                ..hist.clone()
+            },
+        ] {
+            assert_ne!(hist.fingerprint(), other.fingerprint());
+        }
+    }
+
+    #[test]
+    fn rounding() {
+        assert_eq!(round(0.0), round(-0.0));
+        assert_eq!(round(f64::NAN), round(-f64::NAN));
+        assert_eq!(round(0.1 + 0.2), round(0.3));
+        assert_ne!(round(1.0), round(-1.0));
+        assert_ne!(round(1.0), round(1.0001));
+    }
+}
@@ -0,0 +1,351 @@
+use crate::hist::StreamHist;
+use std::f64::consts::SQRT_2;
+
+/// The number of points between the neighboring bins where the distributions are compared.
+const POINTS_PER_BIN: usize = 10;
+
+/// Parametric distribution that the histogram can be compared to using [`StreamHist::fit_test`].
+///
+/// The parameters can be given explicitly, or estimated from the histogram using [`StreamHist::fit_normal`],
+/// [`StreamHist::fit_exponential`], and [`StreamHist::fit_lognormal`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+    /// [Normal distribution](https://en.wikipedia.org/wiki/Normal_distribution).
+    Normal { mean: f64, stdev: f64 },
+    /// [Exponential distribution](https://en.wikipedia.org/wiki/Exponential_distribution).
+    Exponential { rate: f64 },
+    /// [Log-normal distribution](https://en.wikipedia.org/wiki/Log-normal_distribution),
+    /// with the `mu` and `sigma` parameters being the mean and standard deviation of the logarithms of the values.
+    LogNormal { mu: f64, sigma: f64 },
+}
+
+impl Distribution {
+    /// Cumulative distribution function of the distribution.
+    pub fn cdf(&self, value: f64) -> f64 {
+        match *self {
+            Distribution::Normal { mean, stdev } => normal_cdf((value - mean) / stdev),
+            Distribution::Exponential { rate } => {
+                if value < 0.0 {
+                    0.0
+                } else {
+                    1.0 - (-rate * value).exp()
+                }
+            }
+            Distribution::LogNormal { mu, sigma } => {
+                if value <= 0.0 {
+                    0.0
+                } else {
+                    normal_cdf((value.ln() - mu) / sigma)
+                }
+            }
+        }
+    }
+}
+
+/// Result of the [`StreamHist::fit_test`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FitReport {
+    /// The [Kolmogorov-Smirnov] statistic, the largest difference between the cumulative distribution functions.
+    ///
+    /// [Kolmogorov-Smirnov]: https://en.wikipedia.org/wiki/Kolmogorov%E2%80%93Smirnov_test
+    pub statistic: f64,
+    /// The approximate p-value of the test, small values suggest that the data does not follow the distribution.
+    pub p_value: f64,
+}
+
+impl StreamHist {
+    /// Estimate the parameters of the [normal distribution] by the method of moments, as the mean
+    /// and standard deviation of the histogram.
+    ///
+    /// [normal distribution]: https://en.wikipedia.org/wiki/Normal_distribution
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Distribution, StreamHist};
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 3.0]);
+    /// assert_eq!(hist.fit_normal(), Distribution::Normal { mean: 2.0, stdev: 1.0 });
+    /// ```
+    pub fn fit_normal(&self) -> Distribution {
+        Distribution::Normal {
+            mean: self.mean(),
+            stdev: self.stdev(),
+        }
+    }
+
+    /// Estimate the parameter of the [exponential distribution] by the method of moments,
+    /// as the inverse of the mean of the histogram.
+    ///
+    /// [exponential distribution]: https://en.wikipedia.org/wiki/Exponential_distribution
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Distribution, StreamHist};
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 3.0]);
+    /// assert_eq!(hist.fit_exponential(), Distribution::Exponential { rate: 0.5 });
+    /// ```
+    pub fn fit_exponential(&self) -> Distribution {
+        Distribution::Exponential {
+            rate: 1.0 / self.mean(),
+        }
+    }
+
+    /// Estimate the parameters of the [log-normal distribution] from the quantiles of the histogram.
+    ///
+    /// The `mu` is the logarithm of the median and `sigma` is estimated from the interquartile range
+    /// of the logarithms of the values. The quantiles are used rather than the moments, because merging
+    /// the bins of the skewed data biases the moments of the logarithms of the bin means.
+    ///
+    /// The parameters are `f64::NAN` if the first quartile is not positive.
+    ///
+    /// [log-normal distribution]: https://en.wikipedia.org/wiki/Log-normal_distribution
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Distribution, StreamHist};
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 4.0, 8.0, 16.0]);
+    /// match hist.fit_lognormal() {
+    ///     Distribution::LogNormal { mu, .. } => assert_eq!(mu, 4.0f64.ln()),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn fit_lognormal(&self) -> Distribution {
+        // the interquartile range of the standard normal distribution
+        const NORMAL_IQR: f64 = 1.3489795003921634;
+
+        let q25 = self.quantile(0.25);
+        if self.is_empty() || q25 <= 0.0 {
+            return Distribution::LogNormal {
+                mu: f64::NAN,
+                sigma: f64::NAN,
+            };
+        }
+        Distribution::LogNormal {
+            mu: self.median().ln(),
+            sigma: (self.quantile(0.75).ln() - q25.ln()) / NORMAL_IQR,
+        }
+    }
+
+    /// Compare the histogram to the parametric distribution using the [Kolmogorov-Smirnov] test.
+    ///
+    /// The statistic is the largest difference between the [`StreamHist::cdf`] and the cumulative distribution
+    /// function of the distribution, evaluated at the bin means and between them. The p-value uses the asymptotic
+    /// Kolmogorov distribution with the total count as the sample size. When the parameters of the distribution
+    /// were estimated from the same histogram, the p-value is conservative (too large). On the other hand, the
+    /// approximation errors of the histogram (see [`StreamHist::accuracy_report`]) add to the differences,
+    /// so for the large counts the histogram needs enough bins not to reject the matching distributions.
+    ///
+    /// It returns `f64::NAN` for the empty histogram, or the distribution with invalid parameters.
+    ///
+    /// [Kolmogorov-Smirnov]: https://en.wikipedia.org/wiki/Kolmogorov%E2%80%93Smirnov_test
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let mut hist = StreamHist::with_capacity(20);
+    /// for i in 0..1000 {
+    ///     // exponentially distributed values
+    ///     let u = (i as f64 + 0.5) / 1000.0;
+    ///     hist.insert(-(1.0 - u).ln());
+    /// }
+    ///
+    /// let exponential = hist.fit_test(hist.fit_exponential());
+    /// let normal = hist.fit_test(hist.fit_normal());
+    /// assert!(exponential.statistic < normal.statistic);
+    /// assert!(normal.p_value < 0.05);
+    /// ```
+    pub fn fit_test(&self, dist: Distribution) -> FitReport {
+        let mut points = vec![self.min, self.max];
+        for (left, right) in self.bin_edges() {
+            let step = (right - left) / POINTS_PER_BIN as f64;
+            points.extend((0..POINTS_PER_BIN).map(|i| left + step * i as f64));
+        }
+        points.extend(self.iter().map(|bin| bin.mean));
+
+        let statistic = if self.is_empty() {
+            f64::NAN
+        } else {
+            // unlike f64::max, it propagates NaNs from the invalid distributions
+            points
+                .into_iter()
+                .map(|x| (self.cdf(x) - dist.cdf(x)).abs())
+                .fold(0.0, |acc, x| if x > acc || x.is_nan() { x } else { acc })
+        };
+
+        let n = self.count().sqrt();
+        FitReport {
+            statistic,
+            p_value: kolmogorov_survival((n + 0.12 + 0.11 / n) * statistic),
+        }
+    }
+}
+
+/// Cumulative distribution function of the standard normal distribution.
+pub(crate) fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / SQRT_2))
+}
+
+/// The [error function] approximated using the formula 7.1.26 from Abramowitz and Stegun (1964),
+/// with the maximal absolute error of 1.5e-7.
+///
+/// [error function]: https://en.wikipedia.org/wiki/Error_function
+fn erf(x: f64) -> f64 {
+    const P: f64 = 0.3275911;
+    const A: [f64; 5] = [
+        0.254829592,
+        -0.284496736,
+        1.421413741,
+        -1.453152027,
+        1.061405429,
+    ];
+    let t = 1.0 / (1.0 + P * x.abs());
+    let poly = A.iter().rev().fold(0.0, |acc, a| acc * t + a) * t;
+    let y = 1.0 - poly * (-x * x).exp();
+    y.copysign(x)
+}
+
+/// Survival function of the Kolmogorov distribution, `P(K > lambda)`.
+pub(crate) fn kolmogorov_survival(lambda: f64) -> f64 {
+    if lambda.is_nan() {
+        return f64::NAN;
+    }
+    if lambda < 0.2 {
+        // the series converges slowly, but the probability is one up to the numerical precision
+        return 1.0;
+    }
+    let sum = (1..=100).fold(0.0, |acc, k| {
+        let k = k as f64;
+        let sign = if k as u64 % 2 == 1 { 1.0 } else { -1.0 };
+        acc + sign * (-2.0 * k * k * lambda * lambda).exp()
+    });
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{erf, kolmogorov_survival, Distribution};
+    use crate::bins::Bin;
+    use crate::hist::StreamHist;
+
+    /// Deterministic normally distributed values using the Box-Muller transform on a simple LCG.
+    fn normal_values(n: usize) -> Vec<f64> {
+        let mut state: u64 = 42;
+        let mut uniform = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+        };
+        (0..n)
+            .map(|_| {
+                let (u, v) = (uniform(), uniform());
+                (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn error_function() {
+        assert!(erf(0.0).abs() < 1e-7);
+        assert!((erf(1.0) - 0.8427007929).abs() < 1e-6);
+        assert!((erf(-1.0) + 0.8427007929).abs() < 1e-6);
+        assert!((erf(3.0) - 0.9999779095).abs() < 1e-6);
+    }
+
+    #[test]
+    fn kolmogorov() {
+        assert_eq!(kolmogorov_survival(0.0), 1.0);
+        assert!((kolmogorov_survival(1.36) - 0.049).abs() < 1e-3);
+        assert!((kolmogorov_survival(1.63) - 0.0098).abs() < 1e-3);
+        assert!(kolmogorov_survival(f64::NAN).is_nan());
+    }
+
+    #[test]
+    fn distributions() {
+        let normal = Distribution::Normal {
+            mean: 1.0,
+            stdev: 2.0,
+        };
+        assert!((normal.cdf(1.0) - 0.5).abs() < 1e-7);
+        assert!((normal.cdf(3.0) - 0.8413447).abs() < 1e-6);
+
+        let exponential = Distribution::Exponential { rate: 2.0 };
+        assert_eq!(exponential.cdf(-1.0), 0.0);
+        assert!((exponential.cdf(0.5) - (1.0 - (-1.0f64).exp())).abs() < 1e-12);
+
+        let log_normal = Distribution::LogNormal {
+            mu: 0.0,
+            sigma: 1.0,
+        };
+        assert_eq!(log_normal.cdf(0.0), 0.0);
+        assert!((log_normal.cdf(1.0) - 0.5).abs() < 1e-7);
+    }
+
+    #[test]
+    fn estimated_parameters() {
+        let hist = StreamHist::from(vec![Bin::new(1.0, 1), Bin::new(3.0, 1)]);
+        assert_eq!(
+            hist.fit_normal(),
+            Distribution::Normal {
+                mean: 2.0,
+                stdev: 1.0
+            }
+        );
+        assert_eq!(
+            hist.fit_exponential(),
+            Distribution::Exponential { rate: 0.5 }
+        );
+
+        for hist in [StreamHist::default(), StreamHist::from(vec![-1.0, 1.0])] {
+            match hist.fit_lognormal() {
+                Distribution::LogNormal { mu, sigma } => assert!(mu.is_nan() && sigma.is_nan()),
+                _ => unreachable!(),
+            }
+        }
+
+        let mut hist = StreamHist::with_capacity(50);
+        normal_values(5000)
+            .into_iter()
+            .for_each(|x| hist.insert((1.0 + 0.5 * x).exp()));
+        match hist.fit_lognormal() {
+            Distribution::LogNormal { mu, sigma } => {
+                assert!((mu - 1.0).abs() < 0.05, "{mu}");
+                assert!((sigma - 0.5).abs() < 0.05, "{sigma}");
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn fit_test() {
+        let empty = StreamHist::default();
+        let report = empty.fit_test(Distribution::Exponential { rate: 1.0 });
+        assert!(report.statistic.is_nan());
+        assert!(report.p_value.is_nan());
+
+        let mut normal = StreamHist::with_capacity(30);
+        // skewed data needs more bins to be approximated precisely
+        let mut log_normal = StreamHist::with_capacity(100);
+        for x in normal_values(2000) {
+            normal.insert(x);
+            log_normal.insert(x.exp());
+        }
+
+        let report = normal.fit_test(normal.fit_normal());
+        assert!(report.statistic < 0.03, "{report:?}");
+        assert!(report.p_value > 0.1, "{report:?}");
+        assert!(normal.fit_test(normal.fit_lognormal()).statistic.is_nan());
+
+        let report = log_normal.fit_test(log_normal.fit_lognormal());
+        assert!(report.p_value > 0.1, "{report:?}");
+        let report = log_normal.fit_test(log_normal.fit_normal());
+        assert!(report.p_value < 0.01, "{report:?}");
+    }
+}
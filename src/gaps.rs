@@ -0,0 +1,168 @@
+use crate::bins::Bin;
+use crate::hist::{MergeCost, MergePolicy};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Min-heap of the merge costs of the adjacent bins of a histogram, so that finding the bins to merge
+/// on insert takes $O(\log n)$ rather than $O(n)$ time.
+///
+/// The pairs of bins are identified by their means rather than their positions, since the positions
+/// shift with each insert. The heap is updated lazily: the entries of the pairs that are no longer adjacent
+/// are skipped, and the entries with outdated costs (e.g. after the count of a bin increased) are replaced
+/// when popped. It stays valid only as long as the costs of the pairs never decrease without pushing
+/// their new entries, otherwise it needs to be invalidated and is rebuilt from the bins on the next use.
+#[derive(Debug, Clone)]
+pub(crate) struct Gaps {
+    /// The costs of merging the pairs, with the means of the bins for identifying them, and breaking
+    /// the ties in favor of the leftmost pair.
+    heap: BinaryHeap<Reverse<(MergeCost, u64, u64)>>,
+    /// The heap is out of date and needs to be rebuilt.
+    stale: bool,
+}
+
+impl Gaps {
+    /// Initialize the heap that is rebuilt from the bins on the first use.
+    pub(crate) fn new() -> Self {
+        Gaps {
+            heap: BinaryHeap::new(),
+            stale: true,
+        }
+    }
+
+    /// Mark the heap as out of date after the bins were changed without updating it.
+    #[inline]
+    pub(crate) fn invalidate(&mut self) {
+        self.heap.clear();
+        self.stale = true;
+    }
+
+    /// Push the pairs of the bin at the `index` with its neighbors, after it was inserted or merged.
+    pub(crate) fn update(&mut self, bins: &[Bin], index: usize, policy: MergePolicy) {
+        if self.stale {
+            return;
+        }
+        // the outdated entries are dropped when they start to outnumber the bins
+        if self.heap.len() > 4 * bins.len() + 16 {
+            self.invalidate();
+            return;
+        }
+        for i in [index.wrapping_sub(1), index] {
+            if let (Some(left), Some(right)) = (bins.get(i), bins.get(i.wrapping_add(1))) {
+                self.heap.push(entry(left, right, policy));
+            }
+        }
+    }
+
+    /// Find the index of the pair of adjacent bins with the smallest merge cost, the same as
+    /// scanning all the pairs and picking the leftmost one of the smallest cost.
+    pub(crate) fn pop_min(&mut self, bins: &[Bin], policy: MergePolicy) -> Option<usize> {
+        let (idx, _) = self.peek_min(bins, policy)?;
+        self.heap.pop();
+        Some(idx)
+    }
+
+    /// Find the index and the merge cost of the pair of adjacent bins with the smallest merge cost,
+    /// see [`Gaps::pop_min`], without removing it from the heap.
+    pub(crate) fn peek_min(
+        &mut self,
+        bins: &[Bin],
+        policy: MergePolicy,
+    ) -> Option<(usize, MergeCost)> {
+        if self.stale {
+            self.heap = bins
+                .windows(2)
+                .map(|w| entry(&w[0], &w[1], policy))
+                .collect();
+            self.stale = false;
+        }
+        while let Some(Reverse((cost, left, right))) = self.heap.peek().copied() {
+            let idx = bins.partition_point(|bin| ordered_bits(bin.mean) < left);
+            match (bins.get(idx), bins.get(idx + 1)) {
+                (Some(first), Some(second))
+                    if ordered_bits(first.mean) == left && ordered_bits(second.mean) == right =>
+                {
+                    if policy.cost(first, second) == cost {
+                        return Some((idx, cost));
+                    }
+                    self.heap.pop();
+                    self.heap.push(entry(first, second, policy));
+                }
+                _ => {
+                    self.heap.pop();
+                }
+            }
+        }
+        None
+    }
+
+    /// Remove the pair found by [`Gaps::peek_min`] from the heap.
+    #[inline]
+    pub(crate) fn pop(&mut self) {
+        self.heap.pop();
+    }
+}
+
+#[inline]
+fn entry(left: &Bin, right: &Bin, policy: MergePolicy) -> Reverse<(MergeCost, u64, u64)> {
+    Reverse((
+        policy.cost(left, right),
+        ordered_bits(left.mean),
+        ordered_bits(right.mean),
+    ))
+}
+
+/// Bits of the `value` ordered the same as the numbers, including the negative ones.
+#[inline]
+fn ordered_bits(value: f64) -> u64 {
+    let bits = value.to_bits();
+    if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | (1 << 63)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ordered_bits, Gaps};
+    use crate::bins::Bin;
+    use crate::hist::MergePolicy;
+
+    #[test]
+    fn ordering() {
+        let values = [-1e10, -2.5, -1e-300, 0.0, 1e-300, 2.5, 1e10];
+        for w in values.windows(2) {
+            assert!(ordered_bits(w[0]) < ordered_bits(w[1]), "{w:?}");
+        }
+    }
+
+    #[test]
+    fn pop_min() {
+        let mut bins = vec![
+            Bin::new(-3.0, 1),
+            Bin::new(-1.0, 1),
+            Bin::new(0.0, 1),
+            Bin::new(1.0, 1),
+            Bin::new(5.0, 1),
+        ];
+        let mut gaps = Gaps::new();
+        // the leftmost of the smallest gaps
+        assert_eq!(gaps.pop_min(&bins, MergePolicy::SmallestGap), Some(1));
+
+        bins[1] = bins[2] + bins[1];
+        bins.remove(2);
+        gaps.update(&bins, 1, MergePolicy::SmallestGap);
+        assert_eq!(gaps.pop_min(&bins, MergePolicy::SmallestGap), Some(1));
+
+        // the outdated costs are replaced
+        let mut gaps = Gaps::new();
+        assert_eq!(gaps.pop_min(&bins, MergePolicy::SmallestCount), Some(2));
+        bins[2].count += 10;
+        assert_eq!(gaps.pop_min(&bins, MergePolicy::SmallestCount), Some(0));
+
+        assert_eq!(
+            Gaps::new().pop_min(&bins[..1], MergePolicy::default()),
+            None
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use crate::hist::StreamHist;
+use crate::sample::IntoSample;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{Read, Write};
+
+/// Version of the serialization format of the [`HistGroup`].
+const FORMAT_VERSION: u32 = 1;
+
+/// Group of the histograms identified by the labels, e.g. the latencies per endpoint or per tenant.
+///
+/// The histograms are created when the values with the new labels are inserted, as the empty copies
+/// of the template histogram, so all of them share its size and the other settings. The whole group
+/// can be merged with another one label by label, and saved to a single file.
+///
+/// # Examples
+///
+/// ```
+/// use histr::{HistGroup, StreamHist};
+///
+/// let mut group = HistGroup::with_capacity(10);
+/// for i in 0..100 {
+///     group.insert("/login", i as f64);
+///     group.insert("/search", (i * 10) as f64);
+/// }
+/// assert_eq!(group.len(), 2);
+/// assert_eq!(group["/login"].max(), 99.0);
+/// assert_eq!(group.get("/search").map(StreamHist::count), Some(100.0));
+/// assert_eq!(group.get("/logout"), None);
+/// assert_eq!(group.combined().count(), 200.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistGroup {
+    hists: BTreeMap<String, StreamHist>,
+    /// The empty histogram copied for the new labels.
+    template: StreamHist,
+}
+
+impl HistGroup {
+    /// Initialize an empty group, where the histograms of the labels are the empty copies of the `template`,
+    /// e.g. one created with [`StreamHist::with_merge_policy`]. The data of the template is dropped.
+    pub fn new(mut template: StreamHist) -> Self {
+        template.reset();
+        HistGroup {
+            hists: BTreeMap::new(),
+            template,
+        }
+    }
+
+    /// Initialize an empty group of the histograms with `size` bins.
+    pub fn with_capacity(size: usize) -> Self {
+        HistGroup::new(StreamHist::with_capacity(size))
+    }
+
+    /// Insert a new point to the histogram of the `label`, creating the histogram if it does not exist.
+    ///
+    /// # Panics
+    ///
+    /// The `value` needs to be a number. It will panic on `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    pub fn insert<T: IntoSample>(&mut self, label: &str, value: T) {
+        let value = value.into_sample();
+        assert!(value.is_finite(), "{value} is not a number");
+        self.hist_mut(label).insert(value);
+    }
+
+    /// The histogram of the `label`, created if it does not exist.
+    fn hist_mut(&mut self, label: &str) -> &mut StreamHist {
+        // avoid allocating the label for the existing histograms
+        if !self.hists.contains_key(label) {
+            self.hists.insert(label.to_owned(), self.template.clone());
+        }
+        self.hists.get_mut(label).expect("the histogram exists")
+    }
+
+    /// Merge the other group label by label, using the [`StreamHist::merge`] procedure. The histograms
+    /// of the labels missing in this group are merged into the empty copies of the template, so they
+    /// share its size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::HistGroup;
+    ///
+    /// let mut group = HistGroup::with_capacity(5);
+    /// group.insert("a", 1.0);
+    /// let mut other = HistGroup::with_capacity(20);
+    /// other.insert("a", 2.0);
+    /// other.insert("b", 3.0);
+    ///
+    /// group.merge(other);
+    /// assert_eq!(group["a"].count(), 2.0);
+    /// assert_eq!(group["b"].capacity(), 5);
+    /// ```
+    pub fn merge(&mut self, other: Self) {
+        for (label, hist) in other.hists {
+            match self.hists.get_mut(&label) {
+                Some(existing) => existing.merge(hist),
+                None => {
+                    let mut merged = self.template.clone();
+                    merged.merge(hist);
+                    self.hists.insert(label, merged);
+                }
+            }
+        }
+    }
+
+    /// The histogram of the `label`, or `None` if no values were inserted for it.
+    pub fn get(&self, label: &str) -> Option<&StreamHist> {
+        self.hists.get(label)
+    }
+
+    /// Remove the histogram of the `label` from the group and return it.
+    pub fn remove(&mut self, label: &str) -> Option<StreamHist> {
+        self.hists.remove(label)
+    }
+
+    /// Iterate over the labels and their histograms, sorted by the labels.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &StreamHist)> {
+        self.hists
+            .iter()
+            .map(|(label, hist)| (label.as_str(), hist))
+    }
+
+    /// The labels of the histograms, sorted.
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.hists.keys().map(String::as_str)
+    }
+
+    /// All the histograms merged into a single one, with the size of the template.
+    pub fn combined(&self) -> StreamHist {
+        self.hists
+            .values()
+            .fold(self.template.clone(), |mut acc, hist| {
+                acc.merge(hist.clone());
+                acc
+            })
+    }
+
+    /// The number of the labels.
+    pub fn len(&self) -> usize {
+        self.hists.len()
+    }
+
+    /// Returns `true` if the group has no histograms.
+    pub fn is_empty(&self) -> bool {
+        self.hists.is_empty()
+    }
+
+    /// The number of bins of the histograms created for the new labels.
+    pub fn capacity(&self) -> usize {
+        self.template.capacity()
+    }
+}
+
+impl std::ops::Index<&str> for HistGroup {
+    type Output = StreamHist;
+
+    /// The histogram of the `label`.
+    ///
+    /// # Panics
+    ///
+    /// It panics when there is no histogram for the `label`.
+    fn index(&self, label: &str) -> &StreamHist {
+        &self.hists[label]
+    }
+}
+
+/// The serialization format of the [`HistGroup`].
+///
+/// Like for the [`StreamHist`], only the `size` of the template is serialized, and not its other settings.
+#[derive(Serialize, Deserialize)]
+struct GroupRepr<M> {
+    version: u32,
+    size: usize,
+    hists: M,
+}
+
+impl Serialize for HistGroup {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GroupRepr {
+            version: FORMAT_VERSION,
+            size: self.template.capacity(),
+            hists: &self.hists,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for HistGroup {
+    /// Deserialize the group, validating each of the histograms as in [`StreamHist::try_from_parts`].
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = GroupRepr::<BTreeMap<String, StreamHist>>::deserialize(deserializer)?;
+        if repr.version != FORMAT_VERSION {
+            return Err(D::Error::custom("unsupported format version"));
+        }
+        // the size comes from an untrusted input, so no memory is reserved for it
+        let template = StreamHist::try_from_parts(Vec::new(), f64::NAN, f64::NAN, repr.size)
+            .map_err(D::Error::custom)?;
+        Ok(HistGroup {
+            hists: repr.hists,
+            template,
+        })
+    }
+}
+
+impl HistGroup {
+    /// Read the group from a JSON string.
+    ///
+    /// The JSON contains the format `"version"`, the `"size"` of the histograms created for the new labels,
+    /// and the `"hists"` object mapping the labels to the histograms in the format of [`StreamHist::from_json`].
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the JSON is malformed, does not match the format, or any of the histograms
+    /// is not valid.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Transform the group to a JSON string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::HistGroup;
+    ///
+    /// let mut group = HistGroup::with_capacity(5);
+    /// group.insert("a", 1.0);
+    /// let json = group.to_json();
+    /// assert_eq!(
+    ///     json,
+    ///     r#"{"version":1,"size":5,"hists":{"a":{"version":1,"means":[1.0],"counts":[1],"min":1.0,"max":1.0,"size":5}}}"#
+    /// );
+    /// assert_eq!(HistGroup::from_json(&json).unwrap(), group);
+    /// ```
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Read the group from JSON using a reader, see [`HistGroup::from_json`].
+    pub fn read_json<R>(reader: R) -> Result<Self, Box<dyn Error>>
+    where
+        R: Read,
+    {
+        let group = serde_json::from_reader(reader).map_err(Box::new)?;
+        Ok(group)
+    }
+
+    /// Write the group to JSON using a writer, see [`HistGroup::from_json`].
+    pub fn write_json<W>(&self, writer: &mut W) -> Result<(), Box<dyn Error>>
+    where
+        W: Write,
+    {
+        write!(writer, "{}", self.to_json()).map_err(Box::new)?;
+        Ok(())
+    }
+
+    /// Read the group from a [MessagePack] format using a reader.
+    ///
+    /// [MessagePack]: https://msgpack.org/
+    pub fn read_msgpack<R>(reader: R) -> Result<Self, Box<dyn Error>>
+    where
+        R: Read,
+    {
+        let group = rmp_serde::from_read(reader).map_err(Box::new)?;
+        Ok(group)
+    }
+
+    /// Write the group to [MessagePack] format using a writer.
+    ///
+    /// [MessagePack]: https://msgpack.org/
+    pub fn write_msgpack<W>(&self, writer: &mut W) -> Result<(), Box<dyn Error>>
+    where
+        W: Write,
+    {
+        rmp_serde::encode::write(writer, self).map_err(Box::new)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HistGroup;
+    use crate::hist::{MergePolicy, StreamHist};
+
+    #[test]
+    fn template() {
+        let mut template = StreamHist::with_merge_policy(3, MergePolicy::SmallestCount);
+        template.insert(42.0);
+        let mut group = HistGroup::new(template);
+        for i in 0..10 {
+            group.insert("a", i as f64);
+        }
+        assert_eq!(group.capacity(), 3);
+        assert_eq!(group["a"].merge_policy(), MergePolicy::SmallestCount);
+        assert_eq!(group["a"].bins().len(), 3);
+        // the data of the template is not copied
+        assert_eq!(group["a"].min(), 0.0);
+        assert_eq!(group["a"].count(), 10.0);
+    }
+
+    #[test]
+    fn merge() {
+        let mut a = HistGroup::with_capacity(5);
+        let mut b = HistGroup::with_capacity(5);
+        let mut expected = HistGroup::with_capacity(5);
+        for i in 0..100 {
+            let label = if i % 3 == 0 { "x" } else { "y" };
+            if i < 50 {
+                a.insert(label, i as f64);
+            } else {
+                b.insert(label, i as f64);
+            }
+            expected.insert(label, i as f64);
+        }
+        b.insert("z", 1.0);
+        expected.insert("z", 1.0);
+
+        a.merge(b);
+        assert_eq!(a.labels().collect::<Vec<_>>(), vec!["x", "y", "z"]);
+        for (label, hist) in a.iter() {
+            assert_eq!(hist.count(), expected[label].count());
+            assert_eq!(hist.min(), expected[label].min());
+            assert_eq!(hist.max(), expected[label].max());
+        }
+    }
+
+    #[test]
+    fn serialization() {
+        let mut group = HistGroup::with_capacity(5);
+        for i in 0..20 {
+            group.insert(&format!("label {}", i % 4), i as f64);
+        }
+
+        let mut buffer = Vec::new();
+        group.write_msgpack(&mut buffer).unwrap();
+        assert_eq!(HistGroup::read_msgpack(buffer.as_slice()).unwrap(), group);
+
+        let mut buffer = Vec::new();
+        group.write_json(&mut buffer).unwrap();
+        assert_eq!(HistGroup::read_json(buffer.as_slice()).unwrap(), group);
+
+        assert!(HistGroup::from_json(r#"{"version":2,"size":5,"hists":{}}"#).is_err());
+
+        let json = format!(r#"{{"version":1,"size":{},"hists":{{}}}}"#, usize::MAX);
+        let mut group = HistGroup::from_json(&json).unwrap();
+        assert_eq!(group.capacity(), usize::MAX);
+        group.insert("a", 1.0);
+        assert_eq!(group["a"].count(), 1.0);
+        assert!(HistGroup::from_json(
+            r#"{"version":1,"size":5,"hists":{"a":{"means":[1],"counts":[0]}}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_nan() {
+        HistGroup::with_capacity(5).insert("a", f64::NAN);
+    }
+}
@@ -1,24 +1,52 @@
-use serde::{Deserialize, Serialize};
-
 use crate::{
-    bins::{sum_counts, Bin},
+    accuracy::TargetError,
+    bins::Bin,
+    error::{Error, InsertError},
+    gaps::Gaps,
     is_sorted,
+    sample::IntoSample,
+    totals::Totals,
 };
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::iter::Sum;
+use std::ops::Add;
 use std::vec::Vec;
 
+/// The number of values sorted at once by [`StreamHist::extend`].
+const EXTEND_CHUNK_SIZE: usize = 1 << 16;
+
 /// Streaming histogram.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The fields are private, so that the bins stay sorted and the count, mean, and variance maintained while
+/// updating the histogram stay consistent with them. They can be read using the [`StreamHist::bins`],
+/// [`StreamHist::min`], [`StreamHist::max`], and [`StreamHist::capacity`] accessors, and the histogram
+/// can be initialized from them using [`StreamHist::try_from_parts`].
+#[derive(Debug, Clone)]
 pub struct StreamHist {
     /// Sorted [`Bin`]s of the histogram.
-    pub bins: Vec<Bin>,
+    pub(crate) bins: Vec<Bin>,
     /// Smallest observed value.
-    pub min: f64,
+    pub(crate) min: f64,
     /// Largest observed value.
-    pub max: f64,
+    pub(crate) max: f64,
     /// Upper bound for the number of bins.
-    pub size: usize,
+    pub(crate) size: usize,
+    pub(crate) totals: Totals,
+    /// Which bins are merged when trimming the histogram.
+    pub(crate) policy: MergePolicy,
+    /// Number of the lowest and the highest bins that are not merged when trimming the histogram.
+    pub(crate) protected: usize,
+    /// Merge costs of the adjacent bins, for finding the bins to merge on insert.
+    pub(crate) gaps: Gaps,
+    /// The accuracy kept by adjusting the size, see [`StreamHist::with_target_error`].
+    pub(crate) target: Option<TargetError>,
 }
 
+/// Upper bound for the number of the bins preallocated for a new histogram, the larger histograms grow
+/// as needed, so that a huge `size` (e.g. read from an untrusted file) does not reserve the memory upfront.
+const PREALLOCATED_BINS: usize = 1024;
+
 impl StreamHist {
     /// Initialize an empty histogram with the number of bins equal to `size`.
     ///
@@ -29,22 +57,200 @@ impl StreamHist {
     ///
     /// let mut hist = StreamHist::with_capacity(5);
     ///
-    /// assert_eq!(hist.count(), 0.0); // has no data
-    /// assert_eq!(hist.size, 5);      // but has the capacity
+    /// assert_eq!(hist.count(), 0.0);   // has no data
+    /// assert_eq!(hist.capacity(), 5);  // but has the capacity
     ///
     /// for i in 1..=10 {
     ///     hist.insert(i as f64);
     /// }
-    /// assert_eq!(hist.count(), 10.0); // it ingested ten values
-    /// assert_eq!(hist.bins.len(), 5); // the number of bins is equal to size
+    /// assert_eq!(hist.count(), 10.0);   // it ingested ten values
+    /// assert_eq!(hist.bins().len(), 5); // the number of bins is equal to size
     /// ```
     pub fn with_capacity(size: usize) -> Self {
         StreamHist {
-            bins: Vec::with_capacity(size + 1),
+            bins: Vec::with_capacity(size.min(PREALLOCATED_BINS) + 1),
             min: f64::NAN,
             max: f64::NAN,
             size,
+            totals: Totals::default(),
+            policy: MergePolicy::default(),
+            protected: 0,
+            gaps: Gaps::new(),
+            target: None,
+        }
+    }
+
+    /// Initialize an empty histogram with the number of bins equal to `size`, that chooses the bins to merge
+    /// according to the `policy`.
+    ///
+    /// The policy is a setting of the histogram rather than a part of its data, so it is not serialized,
+    /// and it is not compared by the `==` operator. The histograms created in other ways use the default policy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Bin, MergePolicy, StreamHist};
+    ///
+    /// let mut hist = StreamHist::with_merge_policy(3, MergePolicy::SmallestCount);
+    /// let mut default = StreamHist::with_capacity(3);
+    /// for value in [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 100.0] {
+    ///     hist.insert(value);
+    ///     default.insert(value);
+    /// }
+    /// // the bins are kept at similar counts, so the outlier is merged with its neighbor
+    /// assert_eq!(hist.bins(), &[Bin::new(1.5, 2), Bin::new(3.5, 2), Bin::new(37.0, 3)]);
+    /// assert_eq!(default.bins().last(), Some(&Bin::new(100.0, 1)));
+    /// ```
+    pub fn with_merge_policy(size: usize, policy: MergePolicy) -> Self {
+        StreamHist {
+            policy,
+            ..StreamHist::with_capacity(size)
+        }
+    }
+
+    /// Initialize an empty histogram with the number of bins equal to `size`, where the `k` lowest and
+    /// the `k` highest bins are never merged with their neighbors.
+    ///
+    /// The extreme values tend to be absorbed by the bins in the body of the distribution, degrading
+    /// the accuracy of the tail quantiles (e.g. p99 or p999). With the protected tails, only the bins
+    /// between them are merged, so the tails keep the extreme values as separate bins, at the cost of
+    /// fewer bins left for the rest of the distribution. The number of the protected bins is limited,
+    /// so that at least one bin is left between the tails. Like the [`MergePolicy`], it is not serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Bin, StreamHist};
+    ///
+    /// let mut hist = StreamHist::with_protected_tails(10, 2);
+    /// let mut default = StreamHist::with_capacity(10);
+    /// for i in 1..=1000 {
+    ///     hist.insert(i as f64);
+    ///     default.insert(i as f64);
+    /// }
+    /// assert_eq!(hist.bins()[..2], [Bin::new(1.0, 1), Bin::new(2.0, 1)]);
+    /// assert_eq!(hist.bins()[8..], [Bin::new(999.0, 1), Bin::new(1000.0, 1)]);
+    ///
+    /// let p999 = 999.001;
+    /// assert!((hist.quantile(0.999) - p999).abs() < (default.quantile(0.999) - p999).abs());
+    /// ```
+    pub fn with_protected_tails(size: usize, k: usize) -> Self {
+        StreamHist {
+            protected: k,
+            ..StreamHist::with_capacity(size)
+        }
+    }
+
+    /// Initialize the histogram from the already sorted `bins` and calculate its aggregates.
+    pub(crate) fn from_parts(bins: Vec<Bin>, min: f64, max: f64, size: usize) -> Self {
+        let totals = Totals::from_bins(&bins);
+        StreamHist {
+            bins,
+            min,
+            max,
+            size,
+            totals,
+            policy: MergePolicy::default(),
+            protected: 0,
+            gaps: Gaps::new(),
+            target: None,
+        }
+    }
+
+    /// Initialize the histogram from its parts, validating them.
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::InvalidParts`] if:
+    /// * the `bins` are not sorted by their means, any of the means is not a number, or any of the counts is zero,
+    /// * there are more `bins` than `size`,
+    /// * the `bins` are not empty and `min` or `max` is not a number, or the means are not between them.
+    ///
+    /// For empty `bins`, the `min` and `max` are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Bin, StreamHist};
+    ///
+    /// let bins = vec![Bin::new(1.0, 2), Bin::new(3.0, 1)];
+    /// let hist = StreamHist::try_from_parts(bins.clone(), 0.5, 3.0, 5).unwrap();
+    /// assert_eq!(hist.bins(), &bins[..]);
+    /// assert_eq!(hist.count(), 3.0);
+    ///
+    /// // the bins are outside of the min and max range
+    /// assert!(StreamHist::try_from_parts(bins, 2.0, 3.0, 5).is_err());
+    /// ```
+    pub fn try_from_parts(bins: Vec<Bin>, min: f64, max: f64, size: usize) -> Result<Self, Error> {
+        if !is_sorted(&bins) {
+            return Err(Error::InvalidParts("the bins are not sorted"));
+        }
+        if bins.iter().any(|bin| !bin.mean.is_finite()) {
+            return Err(Error::InvalidParts("the means of the bins are not numbers"));
+        }
+        if bins.iter().any(|bin| bin.count == 0) {
+            return Err(Error::InvalidParts("the counts of the bins are zero"));
         }
+        if bins.len() > size {
+            return Err(Error::InvalidParts("there are more bins than the size"));
+        }
+        let (Some(first), Some(last)) = (bins.first(), bins.last()) else {
+            // the size can come from an untrusted input, so no memory is reserved for it
+            return Ok(StreamHist::from_parts(bins, f64::NAN, f64::NAN, size));
+        };
+        if !min.is_finite() || !max.is_finite() {
+            return Err(Error::InvalidParts("min or max is not a number"));
+        }
+        if first.mean < min || last.mean > max {
+            return Err(Error::InvalidParts(
+                "the bins are outside of the min and max range",
+            ));
+        }
+        Ok(StreamHist::from_parts(bins, min, max, size))
+    }
+
+    /// Sorted bins of the histogram.
+    #[inline]
+    pub fn bins(&self) -> &[Bin] {
+        &self.bins
+    }
+
+    /// Smallest observed value, `f64::NAN` for an empty histogram.
+    #[inline]
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Largest observed value, `f64::NAN` for an empty histogram.
+    #[inline]
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Upper bound for the number of bins, see [`StreamHist::resize`] for changing it.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.size
+    }
+
+    /// The policy choosing which bins are merged, see [`StreamHist::with_merge_policy`].
+    #[inline]
+    pub fn merge_policy(&self) -> MergePolicy {
+        self.policy
+    }
+
+    /// Change the policy choosing which bins are merged. The bins that were already merged are not changed.
+    pub fn set_merge_policy(&mut self, policy: MergePolicy) {
+        self.policy = policy;
+        self.gaps.invalidate();
+    }
+
+    /// Number of the lowest and the highest bins that are not merged, see [`StreamHist::with_protected_tails`].
+    ///
+    /// It is limited so that at least one bin is left between the tails.
+    #[inline]
+    pub fn protected_tails(&self) -> usize {
+        self.protected.min(self.size.saturating_sub(1) / 2)
     }
 
     /// Adjust the number of bins in histogram.
@@ -60,15 +266,19 @@ impl StreamHist {
     /// use histr::StreamHist;
     ///
     /// let mut hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
-    /// assert_eq!(hist.size, 5);
-    /// assert_eq!(hist.bins.len(), 5);
+    /// assert_eq!(hist.capacity(), 5);
+    /// assert_eq!(hist.bins().len(), 5);
     /// assert_eq!(hist.count(), 5.0);
     ///
     /// hist.resize(3);
-    /// assert_eq!(hist.size, 3);       // changed
-    /// assert_eq!(hist.bins.len(), 3); // changed
+    /// assert_eq!(hist.capacity(), 3);  // changed
+    /// assert_eq!(hist.bins().len(), 3); // changed
     /// assert_eq!(hist.count(), 5.0);
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), fields(bins = self.bins.len()))
+    )]
     pub fn resize(&mut self, size: usize) {
         self.size = size;
         self.trim()
@@ -76,7 +286,10 @@ impl StreamHist {
 
     /// Insert a new point to the histogram.
     ///
-    /// The inserted `value` needs to be a number (not NaN or infinite), otherwise it panics.
+    /// The inserted `value` can be any type implementing [`IntoSample`] (e.g. `f64`, `u64`, or [`Duration`]),
+    /// it needs to be a number (not NaN or infinite), otherwise it panics.
+    ///
+    /// [`Duration`]: std::time::Duration
     ///
     /// The "update" procedure that it uses is described by Ben-Haim and Tom-Tov (2010).
     ///
@@ -97,13 +310,110 @@ impl StreamHist {
     /// expected.resize(5);
     /// assert_eq!(hist, expected);
     /// ```
-    pub fn insert(&mut self, value: f64) {
+    pub fn insert<T: IntoSample>(&mut self, value: T) {
+        self.insert_with_count(value.into_sample(), 1)
+    }
+
+    /// Insert a new point to the histogram, returning an error instead of panicking if it is not a number.
+    ///
+    /// # Errors
+    ///
+    /// It returns [`InsertError`] for `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`, the histogram
+    /// is not modified in such a case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{InsertError, StreamHist};
+    ///
+    /// let mut hist = StreamHist::with_capacity(5);
+    /// assert!(hist.try_insert(1.0).is_ok());
+    /// assert_eq!(hist.try_insert(f64::NAN), Err(InsertError::NotANumber));
+    /// assert_eq!(hist.count(), 1.0);
+    /// ```
+    pub fn try_insert<T: IntoSample>(&mut self, value: T) -> Result<(), InsertError> {
+        let value = value.into_sample();
+        InsertError::check(value)?;
+        self.insert(value);
+        Ok(())
+    }
+
+    /// Insert a `value` that was observed `count` times to the histogram.
+    ///
+    /// It is equivalent to calling [`StreamHist::insert`] `count` times, but the value is inserted at once
+    /// as a single bin with the `count`, so it is useful for the pre-aggregated data, like the values with
+    /// their frequencies. Inserting a value with the count equal to zero does nothing.
+    ///
+    /// # Panics
+    ///
+    /// The `value` needs to be a number. It will panic on `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Bin, StreamHist};
+    ///
+    /// let mut hist = StreamHist::with_capacity(2);
+    /// hist.insert_with_count(1.0, 3);
+    /// hist.insert_with_count(5.0, 2);
+    ///
+    /// let mut expected = StreamHist::from(vec![Bin::new(1.0, 3), Bin::new(5.0, 2)]);
+    /// assert_eq!(hist, expected);
+    /// assert_eq!(hist.count(), 5.0);
+    /// ```
+    pub fn insert_with_count(&mut self, value: f64, count: u64) {
+        if count == 0 {
+            return;
+        }
+        assert!(value.is_finite(), "{value} is not a number");
+        self.insert_bin(Bin {
+            mean: value + 0.0,
+            count,
+        });
+    }
+
+    /// Insert a new point to the histogram, without checking if it is a number.
+    ///
+    /// It is the same as [`StreamHist::insert`], but for the hot paths where the values were already
+    /// validated (e.g. they were parsed from integers), so the check can be skipped. It is checked only
+    /// in the debug builds.
+    ///
+    /// Inserting `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY` does not cause undefined behavior,
+    /// but it leaves the histogram in an invalid state, so the results of all the further operations on it
+    /// are meaningless.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let mut hist = StreamHist::with_capacity(5);
+    /// for i in 0..100_u32 {
+    ///     hist.insert_unchecked(f64::from(i));
+    /// }
+    /// assert_eq!(hist.count(), 100.0);
+    /// ```
+    #[inline]
+    pub fn insert_unchecked(&mut self, value: f64) {
+        debug_assert!(value.is_finite(), "{value} is not a number");
+        self.insert_bin(Bin {
+            mean: value + 0.0,
+            count: 1,
+        });
+    }
+
+    /// Insert the `bin` with non-zero count to the histogram.
+    fn insert_bin(&mut self, bin: Bin) {
+        let value = bin.mean;
         if self.is_empty() {
             self.min = value;
             self.max = value;
-            self.insert_at(0, value);
+            self.insert_at(0, bin);
+            self.totals = Totals::default();
+            self.totals.insert(value, bin.count);
             return;
         }
+        self.totals.insert(value, bin.count);
 
         if value < self.min {
             self.min = value;
@@ -113,27 +423,152 @@ impl StreamHist {
         }
 
         // Algorithm 1: Update Procedure from Ben-Haim & Tom-Tov (2010), p. 851
-        let idx = self.partition_point(value);
+        let idx = if self.bins.last().is_some_and(|last| last.mean < value) {
+            // fast path for the non-decreasing values (e.g. timestamps), they are appended without searching
+            self.bins.len()
+        } else {
+            self.bins.partition_point(|x| x.mean < value)
+        };
         if idx < self.bins.len() && self.bins[idx].mean == value {
-            self.increment_bin_count(idx);
+            self.bins[idx].count += bin.count;
+        } else if self.bins.len() == self.size && self.protected_tails() == 0 {
+            self.insert_merging(idx, bin);
         } else {
-            self.insert_at(idx, value);
+            self.insert_at(idx, bin);
+            self.gaps.update(&self.bins, idx, self.policy);
             self.trim();
         }
+        if self.target.is_some() {
+            self.adjust_size();
+        }
 
         debug_assert!(is_sorted(&self.bins));
     }
 
-    /// Create a new bin with mean equal to `value` and insert it at the `index`.
+    /// Insert the `bin` at the `index`.
     #[inline]
-    fn insert_at(&mut self, index: usize, value: f64) {
-        self.bins.insert(index, Bin::from(value));
+    fn insert_at(&mut self, index: usize, bin: Bin) {
+        self.bins.insert(index, bin);
     }
 
-    /// Increment count of the bin at the `index`
-    #[inline]
-    fn increment_bin_count(&mut self, index: usize) {
-        self.bins[index].count += 1;
+    /// Insert the `bin` at the `index` of the full histogram and merge the pair of bins with the smallest
+    /// merge cost, the same as [`StreamHist::insert_at`] followed by [`StreamHist::trim`], but only the bins
+    /// between the inserted and the merged ones are shifted, rather than all the bins after them twice.
+    fn insert_merging(&mut self, index: usize, bin: Bin) {
+        let policy = self.policy;
+        // the pair of the bins around the index is split by the new bin, the indexes of the pairs
+        // after it are shifted by it
+        let existing = loop {
+            match self.gaps.peek_min(&self.bins, policy) {
+                Some((i, _)) if i + 1 == index => self.gaps.pop(),
+                Some((i, cost)) => break Some((cost, if i < index { i } else { i + 1 })),
+                None => break None,
+            }
+        };
+        let (_, j) = [
+            index
+                .checked_sub(1)
+                .map(|i| (policy.cost(&self.bins[i], &bin), i)),
+            self.bins
+                .get(index)
+                .map(|right| (policy.cost(&bin, right), index)),
+            existing,
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+        .expect("there are at least two bins");
+        if existing.is_some_and(|(_, i)| i == j) {
+            self.gaps.pop();
+        }
+
+        // `j` is the index of the pair to merge after inserting the new bin
+        let (left, right) = if j + 1 == index {
+            (self.bins[j], bin)
+        } else if j == index {
+            (bin, self.bins[index])
+        } else if j < index {
+            let (left, right) = (self.bins[j], self.bins[j + 1]);
+            self.bins.copy_within(j + 2..index, j + 1);
+            self.bins[index - 1] = bin;
+            self.gaps.update(&self.bins, index - 1, policy);
+            (left, right)
+        } else {
+            let (left, right) = (self.bins[j - 1], self.bins[j]);
+            self.bins.copy_within(index..j - 1, index + 1);
+            self.bins[index] = bin;
+            self.gaps.update(&self.bins, index, policy);
+            (left, right)
+        };
+        self.totals.merge_bins(&left, &right);
+        self.bins[j] = right + left;
+        self.gaps.update(&self.bins, j, policy);
+    }
+
+    /// Copy of the histogram for the readers, e.g. a metrics scraper reading the statistics while
+    /// the histogram is updated.
+    ///
+    /// Unlike [`Clone::clone`], it copies only the data and the settings of the histogram, without
+    /// the auxiliary structures used for speeding up the inserts, so it is cheaper to make and to keep.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let mut hist = StreamHist::with_capacity(5);
+    /// hist.insert(1.0);
+    /// let snapshot = hist.snapshot();
+    /// hist.insert(2.0);
+    /// assert_eq!(snapshot.count(), 1.0);
+    /// assert_eq!(hist.count(), 2.0);
+    /// ```
+    pub fn snapshot(&self) -> StreamHist {
+        StreamHist {
+            bins: self.bins.clone(),
+            gaps: Gaps::new(),
+            ..*self
+        }
+    }
+
+    /// Take the data collected so far as a separate histogram and [`reset`](StreamHist::reset) this one,
+    /// e.g. for reporting the statistics per interval. It does not copy the bins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let mut hist = StreamHist::with_capacity(5);
+    /// hist.insert(1.0);
+    /// hist.insert(2.0);
+    ///
+    /// let frozen = hist.freeze();
+    /// assert_eq!(frozen.count(), 2.0);
+    /// assert!(hist.is_empty());
+    /// assert_eq!(hist.capacity(), 5);
+    /// ```
+    pub fn freeze(&mut self) -> StreamHist {
+        let bins = std::mem::replace(
+            &mut self.bins,
+            Vec::with_capacity(self.size.min(PREALLOCATED_BINS) + 1),
+        );
+        let frozen = StreamHist {
+            bins,
+            gaps: Gaps::new(),
+            ..*self
+        };
+        self.reset();
+        frozen
+    }
+
+    /// Remove all the data from the histogram, keeping its size and the other settings.
+    pub fn reset(&mut self) {
+        self.bins.clear();
+        self.min = f64::NAN;
+        self.max = f64::NAN;
+        self.totals = Totals::default();
+        self.gaps.invalidate();
     }
 
     /// Returns `true` if the histogram contains no data.
@@ -169,28 +604,122 @@ impl StreamHist {
     fn trim(&mut self) {
         if self.size == 0 {
             self.bins = Vec::default();
+            self.totals = Totals::default();
+            self.gaps.invalidate();
+        }
+        // a single merge happens on almost every insert, only the cascades are worth reporting
+        #[cfg(feature = "tracing")]
+        let (excess, start) = {
+            let excess = self.bins.len().saturating_sub(self.size);
+            (excess, (excess > 1).then(std::time::Instant::now))
+        };
+        if self.bins.len() > self.size + 1 {
+            self.merge_cascade();
+            self.gaps.invalidate();
         }
         while self.bins.len() > self.size {
-            let idx = self.min_diff_index();
+            // the heap does not know which of the bins are protected, so they are scanned
+            let idx = if self.protected_tails() == 0 {
+                self.gaps.pop_min(&self.bins, self.policy)
+            } else {
+                None
+            }
+            .unwrap_or_else(|| self.min_cost_index());
             self.merge_at(idx);
+            self.gaps.update(&self.bins, idx, self.policy);
+        }
+        #[cfg(feature = "tracing")]
+        if let Some(start) = start {
+            tracing::debug!(
+                merged = excess,
+                bins = self.bins.len(),
+                elapsed = ?start.elapsed(),
+                "trimmed the histogram"
+            );
         }
         debug_assert!(is_sorted(&self.bins));
     }
 
     #[inline]
     fn merge_at(&mut self, idx: usize) {
-        let updated = self.bins.remove(idx + 1) + self.bins[idx];
-        self.bins[idx] = updated;
+        let right = self.bins.remove(idx + 1);
+        self.totals.merge_bins(&self.bins[idx], &right);
+        self.bins[idx] = right + self.bins[idx];
     }
 
-    /// Find the index of the smallest difference of means between subsequent bins.
-    fn min_diff_index(&self) -> usize {
-        self.bins
+    /// Merge the closest bins until the histogram has `size` bins, the same as repeating [`StreamHist::merge_at`]
+    /// with [`StreamHist::min_cost_index`], but using a heap of the merge costs of the bins, so that
+    /// trimming many bins at once (e.g. after merging histograms) takes $O(n \log n)$ rather than $O(n^2)$ time.
+    fn merge_cascade(&mut self) {
+        let n = self.bins.len();
+        let k = self.protected_tails();
+        let policy = self.policy;
+        let diff = |left: &Bin, right: &Bin| policy.cost(left, right);
+
+        let mut next: Vec<usize> = (1..=n).collect();
+        let mut prev: Vec<usize> = (0..n).map(|i| i.wrapping_sub(1)).collect();
+        let mut version = vec![0_usize; n];
+        let mut alive = vec![true; n];
+        let mut heap: BinaryHeap<Reverse<(MergeCost, usize, usize, usize)>> = (0..n - 1)
+            .map(|i| Reverse((diff(&self.bins[i], &self.bins[i + 1]), i, 0, 0)))
+            .collect();
+
+        let mut excess = n - self.size;
+        while excess > 0 {
+            let Some(Reverse((_, i, left_version, right_version))) = heap.pop() else {
+                break;
+            };
+            let j = next[i];
+            // skip the outdated differences of the bins that were already merged
+            if !alive[i] || j >= n || version[i] != left_version || version[j] != right_version {
+                continue;
+            }
+            // the bins in the tails are never merged, so they keep their original positions
+            if i < k || j >= n - k {
+                continue;
+            }
+            self.totals.merge_bins(&self.bins[i], &self.bins[j]);
+            self.bins[i] = self.bins[j] + self.bins[i];
+            alive[j] = false;
+            version[i] += 1;
+            next[i] = next[j];
+            if next[i] < n {
+                prev[next[i]] = i;
+                let k = next[i];
+                heap.push(Reverse((
+                    diff(&self.bins[i], &self.bins[k]),
+                    i,
+                    version[i],
+                    version[k],
+                )));
+            }
+            if prev[i] < n {
+                let h = prev[i];
+                heap.push(Reverse((
+                    diff(&self.bins[h], &self.bins[i]),
+                    h,
+                    version[h],
+                    version[i],
+                )));
+            }
+            excess -= 1;
+        }
+
+        let mut alive = alive.into_iter();
+        self.bins.retain(|_| alive.next().unwrap_or(false));
+    }
+
+    /// Find the index of the pair of subsequent bins with the smallest merge cost according to the policy,
+    /// skipping the protected bins in the tails.
+    fn min_cost_index(&self) -> usize {
+        // it is called only when there are more bins than the size, so at least two are left between the tails
+        let k = self.protected_tails();
+        self.bins[k..self.bins.len() - k]
             .windows(2)
-            .map(|bins| bins[1].mean - bins[0].mean)
+            .map(|bins| self.policy.cost(&bins[0], &bins[1]))
             .enumerate()
-            .min_by(|(_, a), (_, b)| a.total_cmp(b))
-            .map_or(0, |(index, _)| index)
+            .min_by_key(|(_, cost)| *cost)
+            .map_or(0, |(index, _)| k + index)
     }
 
     /// The total count of all the values used to create the histogram.
@@ -209,7 +738,7 @@ impl StreamHist {
     /// ```
     #[inline]
     pub fn count(&self) -> f64 {
-        sum_counts(&self.bins) as f64
+        self.totals.count as f64
     }
 
     /// Merge two histograms.
@@ -233,15 +762,212 @@ impl StreamHist {
     /// assert_eq!(hist1, expected);
     /// ```
     pub fn merge(&mut self, other: Self) {
+        self.merge_with(other, SizePolicy::KeepSelf)
+    }
+
+    /// Merge two histograms, with the `size` of the merged histogram chosen according to the `policy`.
+    ///
+    /// See [`StreamHist::merge`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{SizePolicy, StreamHist};
+    ///
+    /// let mut hist1 = StreamHist::with_capacity(2);
+    /// hist1.insert(1.0);
+    /// let hist2 = StreamHist::from(vec![2.0, 3.0, 4.0, 5.0]);
+    ///
+    /// // the resolution of the more detailed histogram is preserved
+    /// hist1.merge_with(hist2, SizePolicy::KeepLarger);
+    /// assert_eq!(hist1.capacity(), 4);
+    /// assert_eq!(hist1.bins().len(), 4);
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(bins = self.bins.len(), other_bins = other.bins.len())
+        )
+    )]
+    pub fn merge_with(&mut self, other: Self, policy: SizePolicy) {
+        self.size = match policy {
+            SizePolicy::KeepSelf => self.size,
+            SizePolicy::KeepLarger => self.size.max(other.size),
+            SizePolicy::Fixed(size) => size,
+        };
         // Algorithm 2: Merge Procedure from Ben-Haim & Tom-Tov (2010), p. 852
         self.bins.extend(other.bins);
         self.bins.sort();
+        self.gaps.invalidate();
+        self.totals.merge(&other.totals);
         self.min = self.min.min(other.min);
         self.max = self.max.max(other.max);
         self.trim();
+        self.adjust_size();
         debug_assert!(is_sorted(&self.bins));
     }
 
+    /// Approximately remove the values of the `other` histogram from this histogram, reverting
+    /// the [`StreamHist::merge`].
+    ///
+    /// The count of each bin of the `other` histogram is subtracted from the count of the bin with the nearest mean,
+    /// if it is smaller than the count to subtract, the bin is removed and the rest is subtracted from the next nearest
+    /// bins. The means of the bins are not changed. If the `other` histogram has larger count, all the bins are removed.
+    ///
+    /// Since the information on what bins were merged is lost, the result only approximates the histogram of the
+    /// remaining values, and the errors accumulate with repeated use. The `min` and `max` are not updated unless
+    /// the histogram becomes empty, since the remaining extremes are not known, so they still bound the values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let expired = StreamHist::from(vec![1.0, 2.0, 3.0]);
+    /// let current = StreamHist::from(vec![10.0, 11.0, 12.0]);
+    ///
+    /// let mut total = StreamHist::with_capacity(5);
+    /// total.merge(expired.clone());
+    /// total.merge(current);
+    ///
+    /// total.unmerge(&expired);
+    /// assert_eq!(total.count(), 3.0);
+    /// assert_eq!(total.mean(), 11.0);
+    /// ```
+    pub fn unmerge(&mut self, other: &StreamHist) {
+        for bin in other.bins.iter() {
+            let mut remaining = bin.count;
+            while remaining > 0 && !self.bins.is_empty() {
+                let idx = self.nearest_bin(bin.mean);
+                let taken = remaining.min(self.bins[idx].count);
+                self.bins[idx].count -= taken;
+                remaining -= taken;
+                if self.bins[idx].count == 0 {
+                    self.bins.remove(idx);
+                }
+            }
+        }
+        if self.bins.is_empty() {
+            self.min = f64::NAN;
+            self.max = f64::NAN;
+        }
+        self.totals = Totals::from_bins(&self.bins);
+        self.gaps.invalidate();
+    }
+
+    /// Approximately remove the `value` from the histogram, e.g. for undoing an insert.
+    ///
+    /// The count of the bin with the nearest mean is decremented, and its mean is moved away from the `value`,
+    /// so the total of the values summarized by the bin decreases by the `value`. The mean is kept between
+    /// the means of the neighboring bins, so the bins stay sorted, and the bin is removed when its count drops to
+    /// zero. Removing a value that was never inserted still removes one of the values of the nearest bin.
+    ///
+    /// Since the values merged into the bins are not known, the result only approximates the histogram of the
+    /// remaining values, and the errors accumulate with repeated use. As in [`StreamHist::unmerge`], the `min`
+    /// and `max` are not updated unless the histogram becomes empty. Removing from an empty histogram does nothing.
+    ///
+    /// # Panics
+    ///
+    /// The `value` needs to be a number. It will panic on `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Bin, StreamHist};
+    ///
+    /// let mut hist = StreamHist::with_capacity(2);
+    /// for value in [1.0, 2.0, 3.0, 10.0] {
+    ///     hist.insert(value);
+    /// }
+    /// assert_eq!(hist.bins(), &[Bin::new(2.0, 3), Bin::new(10.0, 1)]);
+    ///
+    /// hist.remove(3.0);
+    /// assert_eq!(hist.bins(), &[Bin::new(1.5, 2), Bin::new(10.0, 1)]);
+    /// hist.remove(10.0);
+    /// assert_eq!(hist.bins(), &[Bin::new(1.5, 2)]);
+    /// assert_eq!(hist.count(), 2.0);
+    /// ```
+    pub fn remove(&mut self, value: f64) {
+        assert!(value.is_finite(), "{value} is not a number");
+        if self.is_empty() {
+            return;
+        }
+        let idx = self.nearest_bin(value);
+        let bin = self.bins[idx];
+        self.totals.remove(bin.mean, bin.count);
+        if bin.count == 1 {
+            self.bins.remove(idx);
+        } else {
+            let count = bin.count - 1;
+            let lower = idx.checked_sub(1).map_or(self.min, |i| self.bins[i].mean);
+            let upper = self.bins.get(idx + 1).map_or(self.max, |next| next.mean);
+            let mean = (bin.mean * bin.count as f64 - value) / count as f64;
+            let mean = mean.clamp(lower, upper);
+            self.bins[idx] = Bin { mean, count };
+            self.totals.insert(mean, count);
+        }
+        // the means of the neighboring bins could be equal after clamping
+        if idx > 0
+            && self
+                .bins
+                .get(idx)
+                .is_some_and(|bin| bin.mean == self.bins[idx - 1].mean)
+        {
+            let bin = self.bins.remove(idx);
+            self.bins[idx - 1].count += bin.count;
+        } else if self
+            .bins
+            .get(idx + 1)
+            .is_some_and(|next| next.mean == self.bins[idx].mean)
+        {
+            let bin = self.bins.remove(idx + 1);
+            self.bins[idx].count += bin.count;
+        }
+        if self.bins.is_empty() {
+            self.min = f64::NAN;
+            self.max = f64::NAN;
+        }
+        self.gaps.invalidate();
+        debug_assert!(is_sorted(&self.bins));
+    }
+
+    /// Index of the bin with the mean nearest to the `value`, the histogram cannot be empty.
+    fn nearest_bin(&self, value: f64) -> usize {
+        let idx = self.bins.partition_point(|bin| bin.mean < value);
+        if idx == self.bins.len()
+            || (idx > 0 && value - self.bins[idx - 1].mean <= self.bins[idx].mean - value)
+        {
+            idx - 1
+        } else {
+            idx
+        }
+    }
+
+    /// Merge the histograms without modifying them, returning the merged histogram.
+    ///
+    /// The `size` of the first histogram is preserved, see [`StreamHist::merge`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist1 = StreamHist::from(vec![1.0, 3.0, 5.0]);
+    /// let hist2 = StreamHist::from(vec![2.0, 4.0, 6.0]);
+    /// let merged = hist1.merged(&hist2).merged(&hist1);
+    ///
+    /// assert_eq!(merged.count(), 9.0);
+    /// assert_eq!(merged.capacity(), 3);
+    /// assert_eq!(hist1.count(), 3.0);
+    /// ```
+    pub fn merged(&self, other: &Self) -> Self {
+        let mut merged = self.clone();
+        merged.merge(other.clone());
+        merged
+    }
+
     /// Create an iterator over the bins.
     ///
     /// # Examples
@@ -260,26 +986,52 @@ impl StreamHist {
     pub fn iter(&self) -> impl Iterator<Item = &Bin> {
         self.bins.iter()
     }
+
+    /// Consume the histogram, returning its sorted bins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Bin, StreamHist};
+    ///
+    /// let hist = StreamHist::from(vec![2.0, 1.0]);
+    /// assert_eq!(hist.into_bins(), vec![Bin::from(1.0), Bin::from(2.0)]);
+    /// ```
+    pub fn into_bins(self) -> Vec<Bin> {
+        self.bins
+    }
 }
 
-impl From<Vec<f64>> for StreamHist {
-    /// Initialize histogram from a vector of values.
+/// The `size` of the histogram after merging, see [`StreamHist::merge_with`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SizePolicy {
+    /// Keep the size of the histogram that the other histogram is merged into.
+    #[default]
+    KeepSelf,
+    /// Use the larger of the sizes of the two histograms.
+    KeepLarger,
+    /// Use the given size.
+    Fixed(usize),
+}
+
+impl<T: IntoSample> From<Vec<T>> for StreamHist {
+    /// Initialize histogram from a vector of values, see [`IntoSample`] for the supported types.
     ///
     /// # Panics
     ///
     /// All the `values` need to be a numbers. It will panic on any `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
-    fn from(values: Vec<f64>) -> Self {
+    fn from(values: Vec<T>) -> Self {
         if values.is_empty() {
             return StreamHist::default();
         }
-        let mut bins: Vec<Bin> = values.iter().map(|x| Bin::from(*x)).collect();
+        let mut bins: Vec<Bin> = values
+            .into_iter()
+            .map(|x| Bin::from(x.into_sample()))
+            .collect();
         bins.sort();
-        StreamHist {
-            bins: bins.clone(),
-            min: bins.first().unwrap().mean,
-            max: bins.last().unwrap().mean,
-            size: bins.len(),
-        }
+        let (min, max) = (bins.first().unwrap().mean, bins.last().unwrap().mean);
+        let size = bins.len();
+        StreamHist::from_parts(bins, min, max, size)
     }
 }
 
@@ -290,15 +1042,145 @@ impl From<Vec<Bin>> for StreamHist {
         }
         let mut bins = bins;
         bins.sort();
-        StreamHist {
-            bins: bins.clone(),
-            min: bins.first().unwrap().mean,
-            max: bins.last().unwrap().mean,
-            size: bins.len(),
+        let (min, max) = (bins.first().unwrap().mean, bins.last().unwrap().mean);
+        let size = bins.len();
+        StreamHist::from_parts(bins, min, max, size)
+    }
+}
+
+impl IntoIterator for StreamHist {
+    type Item = Bin;
+    type IntoIter = std::vec::IntoIter<Bin>;
+
+    /// Consume the histogram, iterating over its bins.
+    fn into_iter(self) -> Self::IntoIter {
+        self.bins.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a StreamHist {
+    type Item = &'a Bin;
+    type IntoIter = std::slice::Iter<'a, Bin>;
+
+    /// Iterate over the bins, the same as [`StreamHist::iter`].
+    fn into_iter(self) -> Self::IntoIter {
+        self.bins.iter()
+    }
+}
+
+impl<T: IntoSample> FromIterator<T> for StreamHist {
+    /// Initialize histogram from the values, the same as [`StreamHist::from<Vec<T>>`].
+    ///
+    /// The histogram has as many bins as the values. To create a histogram with a limited number of bins,
+    /// use [`Extend::extend`] on a histogram initialized with [`StreamHist::with_capacity`].
+    ///
+    /// # Panics
+    ///
+    /// All the `values` need to be a numbers. It will panic on any `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let data = [5.0, 1.0, 3.0];
+    /// let hist: StreamHist = data.iter().copied().collect();
+    /// assert_eq!(hist, StreamHist::from(vec![1.0, 3.0, 5.0]));
+    /// ```
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        values
+            .into_iter()
+            .map(|value| Bin::from(value.into_sample()))
+            .collect()
+    }
+}
+
+impl FromIterator<Bin> for StreamHist {
+    /// Initialize histogram from the bins, the same as [`StreamHist::from<Vec<Bin>>`].
+    fn from_iter<I: IntoIterator<Item = Bin>>(bins: I) -> Self {
+        StreamHist::from(bins.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T: IntoSample> Extend<T> for StreamHist {
+    /// Insert all the values to the histogram.
+    ///
+    /// Instead of inserting the values one by one, the values are sorted in chunks, converted to bins, and merged
+    /// with the histogram using the same procedure as in [`StreamHist::merge`], so it is much faster than calling
+    /// [`StreamHist::insert`] for each of the values. The result can slightly differ from inserting the values one
+    /// by one, since the bins are merged in a different order.
+    ///
+    /// # Panics
+    ///
+    /// All the `values` need to be a numbers. It will panic on any `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let mut hist = StreamHist::with_capacity(10);
+    /// hist.extend((0..100_000).map(|x| (x % 1000) as f64));
+    ///
+    /// assert_eq!(hist.count(), 100_000.0);
+    /// assert_eq!(hist.bins().len(), 10);
+    /// assert_eq!(hist.min(), 0.0);
+    /// assert_eq!(hist.max(), 999.0);
+    /// ```
+    fn extend<I: IntoIterator<Item = T>>(&mut self, values: I) {
+        let mut values = values.into_iter().map(IntoSample::into_sample).peekable();
+        let mut chunk = Vec::new();
+        while values.peek().is_some() {
+            chunk.clear();
+            chunk.extend(values.by_ref().take(EXTEND_CHUNK_SIZE));
+            chunk.sort_by(f64::total_cmp);
+
+            let mut bins: Vec<Bin> = Vec::new();
+            for value in chunk.iter() {
+                match bins.last_mut() {
+                    Some(last) if last.mean == *value => last.count += 1,
+                    _ => bins.push(Bin::from(*value)),
+                }
+            }
+            let (min, max) = (bins[0].mean, bins[bins.len() - 1].mean);
+            self.merge(StreamHist::from_parts(bins, min, max, self.size));
         }
     }
 }
 
+impl Add for StreamHist {
+    type Output = StreamHist;
+
+    /// Merge the histograms, the same as [`StreamHist::merge`].
+    fn add(mut self, other: Self) -> Self::Output {
+        self.merge(other);
+        self
+    }
+}
+
+impl Sum for StreamHist {
+    /// Merge all the histograms, the same as [`StreamHist::merge`]. The `size` of the first
+    /// histogram is preserved, and the empty iterator gives the [`StreamHist::default`] histogram.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let shards: Vec<StreamHist> = (0..4)
+    ///     .map(|i| StreamHist::from(vec![i as f64, i as f64 + 0.5]))
+    ///     .collect();
+    /// let total: StreamHist = shards.into_iter().sum();
+    ///
+    /// assert_eq!(total.count(), 8.0);
+    /// assert_eq!(total.bins().len(), 2);
+    /// assert_eq!(total.max(), 3.5);
+    /// ```
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(Add::add).unwrap_or_default()
+    }
+}
+
 impl Default for StreamHist {
     /// Initialize empty histogram.
     ///
@@ -309,7 +1191,7 @@ impl Default for StreamHist {
     ///
     /// let hist = StreamHist::default();
     /// assert!(hist.is_empty());
-    /// assert_eq!(hist.size, 0);
+    /// assert_eq!(hist.capacity(), 0);
     /// assert_eq!(hist.count(), 0.0);
     ///
     /// assert_eq!(StreamHist::default(), StreamHist::default());
@@ -320,6 +1202,11 @@ impl Default for StreamHist {
             min: f64::NAN,
             max: f64::NAN,
             size: 0,
+            totals: Totals::default(),
+            policy: MergePolicy::default(),
+            protected: 0,
+            gaps: Gaps::new(),
+            target: None,
         }
     }
 }
@@ -333,6 +1220,95 @@ impl PartialEq for StreamHist {
     }
 }
 
+/// The cost of merging a pair of bins, see [`MergePolicy::cost`].
+pub(crate) type MergeCost = (u64, u64);
+
+/// Which pair of the adjacent bins is merged when trimming the histogram, see [`StreamHist::with_merge_policy`].
+///
+/// The policies trade the accuracy in the tails of the distribution for the accuracy in its body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Merge the bins with the closest means, as in the paper by Ben-Haim and Tom-Tov (2010).
+    /// The isolated extreme values are kept in separate bins, so it favors the tails.
+    #[default]
+    SmallestGap,
+    /// Merge the bins with the smallest combined count, the ties are broken by the smallest gap.
+    /// The bins have similar counts, so it favors the body of the distribution.
+    SmallestCount,
+    /// Merge the bins with the smallest gap multiplied by the combined count, similarly to some of
+    /// the t-digest variants, as a compromise between the other policies.
+    WeightedGap,
+}
+
+impl MergePolicy {
+    /// The cost of merging the `left` and `right` bins, compared lexicographically.
+    ///
+    /// The costs are non-negative, so their bits are ordered the same as the values.
+    #[inline]
+    pub(crate) fn cost(self, left: &Bin, right: &Bin) -> MergeCost {
+        self.cost_of(left.mean, right.mean, (left.count + right.count) as f64)
+    }
+
+    /// The cost of merging the bins with the `left` and `right` means and the combined non-negative `weight`,
+    /// see [`MergePolicy::cost`].
+    #[inline]
+    pub(crate) fn cost_of(self, left: f64, right: f64, weight: f64) -> MergeCost {
+        let gap = right - left;
+        match self {
+            MergePolicy::SmallestGap => (gap.to_bits(), 0),
+            MergePolicy::SmallestCount => (weight.to_bits(), gap.to_bits()),
+            MergePolicy::WeightedGap => ((gap * weight).to_bits(), gap.to_bits()),
+        }
+    }
+}
+
+/// Merge the adjacent bins chosen by the `policy` until there are at most `size` of them, for the histogram
+/// variants that keep their bins in other forms than the [`StreamHist`].
+///
+/// The `parts` gives the mean and the non-negative weight of a bin, and `merge` combines the two adjacent bins,
+/// or returns `None` when the merged bin is empty and should be dropped.
+pub(crate) fn trim_bins<T>(
+    bins: &mut Vec<T>,
+    size: usize,
+    policy: MergePolicy,
+    parts: impl Fn(&T) -> (f64, f64),
+    merge: impl Fn(T, T) -> Option<T>,
+) {
+    if size == 0 {
+        bins.clear();
+    }
+    while bins.len() > size {
+        let idx = bins
+            .windows(2)
+            .map(|w| {
+                let ((left, w1), (right, w2)) = (parts(&w[0]), parts(&w[1]));
+                policy.cost_of(left, right, w1 + w2)
+            })
+            .enumerate()
+            // the leftmost of the pairs with the smallest cost
+            .min_by_key(|(_, cost)| *cost)
+            .map_or(0, |(idx, _)| idx);
+
+        let right = bins.remove(idx + 1);
+        let left = bins.remove(idx);
+        if let Some(merged) = merge(left, right) {
+            bins.insert(idx, merged);
+        }
+    }
+}
+
+/// The mean of the two bins weighted by their non-negative weights, or their midpoint when both
+/// of the weights are zero.
+#[inline]
+pub(crate) fn weighted_mean((m1, w1): (f64, f64), (m2, w2): (f64, f64)) -> f64 {
+    let weight = w1 + w2;
+    if weight > 0.0 {
+        (m1 * w1 + m2 * w2) / weight
+    } else {
+        (m1 + m2) / 2.0
+    }
+}
+
 /// Both values are either NaNs or are equal
 #[inline]
 fn nan_or_eq(a: f64, b: f64) -> bool {
@@ -341,10 +1317,35 @@ fn nan_or_eq(a: f64, b: f64) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::StreamHist;
+    use super::{trim_bins, weighted_mean, MergePolicy, StreamHist};
     use crate::bins::Bin;
     use test_case::test_case;
 
+    #[test]
+    fn trim_bins_shared() {
+        let merge = |left, right| Some((weighted_mean(left, right), left.1 + right.1));
+        let mut bins = vec![(0.0, 1.0), (1.0, 5.0), (1.5, 5.0), (10.0, 1.0)];
+        trim_bins(&mut bins, 3, MergePolicy::SmallestGap, |bin| *bin, merge);
+        assert_eq!(bins, vec![(0.0, 1.0), (1.25, 10.0), (10.0, 1.0)]);
+
+        let mut bins = vec![(0.0, 1.0), (1.0, 5.0), (1.5, 5.0), (10.0, 1.0)];
+        trim_bins(&mut bins, 3, MergePolicy::SmallestCount, |bin| *bin, merge);
+        assert_eq!(bins, vec![(5.0 / 6.0, 6.0), (1.5, 5.0), (10.0, 1.0)]);
+
+        // the empty bins are dropped, and the weightless ones merged at the midpoint
+        let mut bins = vec![(0.0, 0.0), (1.0, 0.0), (5.0, 1.0)];
+        trim_bins(&mut bins, 2, MergePolicy::default(), |bin| *bin, merge);
+        assert_eq!(bins, vec![(0.5, 0.0), (5.0, 1.0)]);
+        trim_bins(
+            &mut bins,
+            1,
+            MergePolicy::default(),
+            |bin| *bin,
+            |_, _| None,
+        );
+        assert!(bins.is_empty());
+    }
+
     #[test]
     #[should_panic]
     fn partition_point_nan() {
@@ -379,6 +1380,285 @@ mod tests {
         StreamHist::from(vec![1.0, 2.0, 3.0]).insert(value);
     }
 
+    #[test]
+    fn insert_with_count() {
+        let mut hist = StreamHist::with_capacity(3);
+        let mut expected = StreamHist::with_capacity(3);
+        for (value, count) in [(2.0, 3), (1.0, 1), (2.0, 2), (7.0, 0), (5.0, 4), (4.0, 2)] {
+            hist.insert_with_count(value, count);
+            (0..count).for_each(|_| expected.insert(value));
+        }
+        assert_eq!(hist, expected);
+        assert_eq!(hist.count(), 12.0);
+        assert!((hist.mean() - expected.mean()).abs() < 1e-12);
+        assert!((hist.variance() - expected.variance()).abs() < 1e-12);
+        assert_eq!(hist.max, 5.0);
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut hist = StreamHist::from(vec![1.0, 2.0]);
+        let expected = hist.clone();
+        assert!(hist.try_insert(f64::NAN).is_err());
+        assert!(hist.try_insert(f64::INFINITY).is_err());
+        assert!(StreamHist::default().try_insert(f64::NEG_INFINITY).is_err());
+        assert_eq!(hist, expected);
+
+        assert_eq!(hist.try_insert(3.0), Ok(()));
+        assert_eq!(hist.count(), 3.0);
+    }
+
+    #[test]
+    fn try_from_parts() {
+        let bins = vec![Bin::new(1.0, 2), Bin::new(3.0, 1)];
+        assert_eq!(
+            StreamHist::try_from_parts(bins.clone(), 1.0, 3.0, 2),
+            Ok(StreamHist::from(bins.clone()))
+        );
+        assert_eq!(
+            StreamHist::try_from_parts(vec![], 1.0, 3.0, 2),
+            Ok(StreamHist::with_capacity(2))
+        );
+
+        let unsorted = vec![Bin::new(3.0, 1), Bin::new(1.0, 2)];
+        assert!(StreamHist::try_from_parts(unsorted, 1.0, 3.0, 2).is_err());
+        let zero = vec![Bin::new(1.0, 0)];
+        assert!(StreamHist::try_from_parts(zero, 1.0, 1.0, 2).is_err());
+        assert!(StreamHist::try_from_parts(bins.clone(), 1.0, 3.0, 1).is_err());
+        assert!(StreamHist::try_from_parts(bins.clone(), f64::NAN, 3.0, 2).is_err());
+        assert!(StreamHist::try_from_parts(bins.clone(), 1.0, 2.0, 2).is_err());
+        let nan = vec![Bin {
+            mean: f64::NAN,
+            count: 1,
+        }];
+        assert!(StreamHist::try_from_parts(nan, 1.0, 2.0, 2).is_err());
+    }
+
+    #[test_case(MergePolicy::SmallestGap ; "smallest gap")]
+    #[test_case(MergePolicy::SmallestCount ; "smallest count")]
+    #[test_case(MergePolicy::WeightedGap ; "weighted gap")]
+    fn merge_cascade(policy: MergePolicy) {
+        let values: Vec<f64> = (0..500).map(|i| ((i * 7919) % 613) as f64 / 7.0).collect();
+        let mut hist = StreamHist::from(values.clone());
+        hist.set_merge_policy(policy);
+        let mut expected = hist.clone();
+
+        hist.resize(10);
+        expected.size = 10;
+        while expected.bins.len() > expected.size {
+            let idx = expected.min_cost_index();
+            expected.merge_at(idx);
+        }
+        assert_eq!(hist, expected);
+        assert!((hist.totals.m2 - expected.totals.m2).abs() < 1e-6);
+        assert_eq!(hist.count(), 500.0);
+    }
+
+    #[test_case(MergePolicy::SmallestGap ; "smallest gap")]
+    #[test_case(MergePolicy::SmallestCount ; "smallest count")]
+    #[test_case(MergePolicy::WeightedGap ; "weighted gap")]
+    fn incremental_gaps(policy: MergePolicy) {
+        let mut hist = StreamHist::with_merge_policy(20, policy);
+        let mut expected = hist.clone();
+        for i in 0..2000 {
+            let value = ((i * 7919) % 613) as f64 / 7.0 - 40.0;
+            hist.insert(value);
+
+            // insert the bin and merge the bins found by scanning them
+            let idx = expected.partition_point(value);
+            if expected.bins.get(idx).is_some_and(|bin| bin.mean == value) {
+                expected.bins[idx].count += 1;
+            } else {
+                expected.insert_at(idx, Bin::from(value));
+                while expected.bins.len() > expected.size {
+                    let idx = expected.min_cost_index();
+                    expected.merge_at(idx);
+                }
+            }
+            let parts = |hist: &StreamHist| -> Vec<(f64, u64)> {
+                hist.bins.iter().map(|bin| bin.into()).collect()
+            };
+            assert_eq!(parts(&hist), parts(&expected));
+        }
+    }
+
+    #[test]
+    fn freeze() {
+        let mut hist = StreamHist::with_protected_tails(5, 1);
+        hist.set_merge_policy(MergePolicy::WeightedGap);
+        (0..100).for_each(|i| hist.insert(i as f64));
+        let snapshot = hist.snapshot();
+        assert_eq!(snapshot, hist);
+        assert_eq!(snapshot.totals, hist.totals);
+
+        let frozen = hist.freeze();
+        assert_eq!(frozen, snapshot);
+        assert_eq!(frozen.merge_policy(), MergePolicy::WeightedGap);
+        assert_eq!(frozen.protected_tails(), 1);
+        assert_eq!(hist, StreamHist::with_capacity(5));
+        assert_eq!(hist.merge_policy(), MergePolicy::WeightedGap);
+        assert_eq!(hist.protected_tails(), 1);
+
+        // the histogram is updated as usual after resetting it
+        (0..100).for_each(|i| hist.insert(i as f64));
+        assert_eq!(hist, frozen);
+    }
+
+    #[test]
+    fn protected_tails() {
+        let values: Vec<f64> = (0..500).map(|i| ((i * 7919) % 613) as f64 / 7.0).collect();
+        let mut sorted = values.clone();
+        sorted.sort_by(f64::total_cmp);
+        sorted.dedup();
+
+        let mut hist = StreamHist::with_protected_tails(10, 3);
+        values.iter().for_each(|x| hist.insert(*x));
+        assert_eq!(hist.protected_tails(), 3);
+        assert_eq!(hist.bins.len(), 10);
+        assert_eq!(hist.count(), 500.0);
+        for i in 0..3 {
+            assert_eq!(hist.bins[i].mean, sorted[i]);
+            assert_eq!(hist.bins[9 - i].mean, sorted[sorted.len() - 1 - i]);
+        }
+
+        // the same bins after merging many at once
+        let mut merged = StreamHist::from(values);
+        merged.protected = 3;
+        merged.resize(10);
+        assert_eq!(merged.bins[..3], hist.bins[..3]);
+        assert_eq!(merged.bins[7..], hist.bins[7..]);
+
+        // at least one bin is left between the tails
+        merged.resize(4);
+        assert_eq!(merged.protected_tails(), 1);
+        assert_eq!(merged.bins[0].mean, sorted[0]);
+        assert_eq!(merged.bins[3].mean, sorted[sorted.len() - 1]);
+        merged.resize(1);
+        assert_eq!(merged.protected_tails(), 0);
+        assert_eq!(merged.count(), 500.0);
+    }
+
+    #[test]
+    fn merge_policy() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 100.0];
+        let hist = |policy| {
+            let mut hist = StreamHist::with_merge_policy(3, policy);
+            values.iter().for_each(|x| hist.insert(*x));
+            hist
+        };
+
+        let gap = hist(MergePolicy::SmallestGap);
+        assert_eq!(gap.bins.last(), Some(&Bin::new(100.0, 1)));
+        assert_eq!(gap.merge_policy(), MergePolicy::default());
+
+        let count = hist(MergePolicy::SmallestCount);
+        assert_eq!(
+            count.bins.iter().map(|bin| bin.count).collect::<Vec<_>>(),
+            vec![2, 2, 3]
+        );
+        assert_eq!(count.bins.last(), Some(&Bin::new(37.0, 3)));
+
+        let weighted = hist(MergePolicy::WeightedGap);
+        assert_eq!(weighted.bins.last(), Some(&Bin::new(100.0, 1)));
+        for hist in [gap, count, weighted] {
+            assert_eq!(hist.count(), 7.0);
+            assert_eq!((hist.min(), hist.max()), (1.0, 100.0));
+        }
+    }
+
+    #[test]
+    fn extend() {
+        let values: Vec<f64> = (0..1000).map(|i| ((i * 31) % 101) as f64).collect();
+        let mut hist = StreamHist::with_capacity(10);
+        hist.extend(values.iter().copied());
+        hist.extend(Vec::<f64>::new());
+
+        let mut expected = StreamHist::from(values);
+        expected.resize(10);
+        assert_eq!(hist.count(), 1000.0);
+        assert_eq!(hist.bins.len(), 10);
+        assert_eq!((hist.min, hist.max), (0.0, 100.0));
+        assert!((hist.mean() - expected.mean()).abs() < 1e-9);
+        assert!((hist.median() - expected.median()).abs() < 5.0);
+
+        let mut hist = StreamHist::with_capacity(3);
+        hist.extend([1_u64, 2, 2]);
+        let mut expected = StreamHist::from(vec![Bin::from(1.0), Bin::new(2.0, 2)]);
+        expected.resize(3);
+        assert_eq!(hist, expected);
+    }
+
+    #[test]
+    fn from_iter() {
+        let hist: StreamHist = [3.0, 1.0, 2.0].into_iter().collect();
+        assert_eq!(hist, StreamHist::from(vec![1.0, 2.0, 3.0]));
+
+        let hist: StreamHist = (1..=3_u64).collect();
+        assert_eq!(hist, StreamHist::from(vec![1.0, 2.0, 3.0]));
+
+        let hist: StreamHist = [Bin::new(2.0, 2), Bin::from(1.0)].into_iter().collect();
+        assert_eq!(
+            hist,
+            StreamHist::from(vec![Bin::from(1.0), Bin::new(2.0, 2)])
+        );
+
+        let hist: StreamHist = std::iter::empty::<f64>().collect();
+        assert_eq!(hist, StreamHist::default());
+    }
+
+    #[test]
+    fn into_iter() {
+        let bins = vec![Bin::from(1.0), Bin::new(2.0, 3)];
+        let hist = StreamHist::from(bins.clone());
+
+        let mut count = 0;
+        for bin in &hist {
+            count += bin.count;
+        }
+        assert_eq!(count, 4);
+        assert_eq!(hist.clone().into_iter().collect::<Vec<_>>(), bins);
+        assert_eq!(hist.into_bins(), bins);
+    }
+
+    #[test]
+    #[should_panic]
+    fn extend_invalid() {
+        StreamHist::with_capacity(3).extend([1.0, f64::NAN]);
+    }
+
+    #[test]
+    fn insert_sorted() {
+        let mut hist = StreamHist::with_capacity(4);
+        for value in [1.0, 1.0, 2.0, 3.0, 3.0, 3.0, 5.0] {
+            hist.insert(value);
+        }
+        assert_eq!(
+            hist,
+            StreamHist::from_parts(
+                vec![
+                    Bin::new(1.0, 2),
+                    Bin::new(2.0, 1),
+                    Bin::new(3.0, 3),
+                    Bin::new(5.0, 1)
+                ],
+                1.0,
+                5.0,
+                4
+            )
+        );
+        assert_eq!(hist.bins[0].count, 2);
+        assert_eq!(hist.bins[2].count, 3);
+
+        // appending after the merges
+        hist.insert(5.5);
+        hist.insert(5.5);
+        assert_eq!(hist.bins.len(), 4);
+        assert_eq!(hist.count(), 9.0);
+        assert_eq!(hist.max, 5.5);
+        assert_eq!(hist.bins.last().unwrap().mean, 16.0 / 3.0);
+        assert_eq!(hist.bins.last().unwrap().count, 3);
+    }
+
     #[test]
     fn insert() {
         let mut hist = StreamHist::with_capacity(3);
@@ -387,83 +1667,78 @@ mod tests {
         hist.insert(10.0);
         assert_eq!(
             hist,
-            StreamHist {
-                bins: vec![Bin::new(10.0, 2)],
-                min: 10.0,
-                max: 10.0,
-                size: 3,
-            }
+            StreamHist::from_parts(vec![Bin::new(10.0, 2)], 10.0, 10.0, 3)
         );
         // second and third elements
         hist.insert(30.0);
         hist.insert(20.0);
         assert_eq!(
             hist,
-            StreamHist {
-                bins: vec![Bin::from(10.0), Bin::from(20.0), Bin::from(30.0)],
-                min: 10.0,
-                max: 30.0,
-                size: 3,
-            }
+            StreamHist::from_parts(
+                vec![Bin::from(10.0), Bin::from(20.0), Bin::from(30.0)],
+                10.0,
+                30.0,
+                3
+            )
         );
         // update count for the first element
         hist.insert(10.0);
         assert_eq!(
             hist,
-            StreamHist {
-                bins: vec![Bin::new(10.0, 2), Bin::from(20.0), Bin::from(30.0)],
-                min: 10.0,
-                max: 30.0,
-                size: 3,
-            }
+            StreamHist::from_parts(
+                vec![Bin::new(10.0, 2), Bin::from(20.0), Bin::from(30.0)],
+                10.0,
+                30.0,
+                3
+            )
         );
 
         // update count for the last element
         hist.insert(35.0);
         assert_eq!(
             hist,
-            StreamHist {
-                bins: vec![Bin::new(10.0, 2), Bin::from(20.0), Bin::new(32.5, 2)],
-                min: 10.0,
-                max: 35.0,
-                size: 3,
-            }
+            StreamHist::from_parts(
+                vec![Bin::new(10.0, 2), Bin::from(20.0), Bin::new(32.5, 2)],
+                10.0,
+                35.0,
+                3
+            )
         );
 
         // update count for the first element
         hist.insert(1.0);
         assert_eq!(
             hist,
-            StreamHist {
-                bins: vec![Bin::new(7.0, 3), Bin::from(20.0), Bin::new(32.5, 2)],
-                min: 1.0,
-                max: 35.0,
-                size: 3,
-            }
+            StreamHist::from_parts(
+                vec![Bin::new(7.0, 3), Bin::from(20.0), Bin::new(32.5, 2)],
+                1.0,
+                35.0,
+                3
+            )
         );
 
         // update count for the last element
         hist.insert(37.0);
         assert_eq!(
             hist,
-            StreamHist {
-                bins: vec![Bin::new(7.0, 3), Bin::from(20.0), Bin::new(34.0, 3)],
-                min: 1.0,
-                max: 37.0,
-                size: 3,
-            }
+            StreamHist::from_parts(
+                vec![Bin::new(7.0, 3), Bin::from(20.0), Bin::new(34.0, 3)],
+                1.0,
+                37.0,
+                3
+            )
         );
 
         // update count for the second element
         hist.insert(22.0);
         assert_eq!(
             hist,
-            StreamHist {
-                bins: vec![Bin::new(7.0, 3), Bin::new(21.0, 2), Bin::new(34.0, 3)],
-                min: 1.0,
-                max: 37.0,
-                size: 3,
-            }
+            StreamHist::from_parts(
+                vec![Bin::new(7.0, 3), Bin::new(21.0, 2), Bin::new(34.0, 3)],
+                1.0,
+                37.0,
+                3
+            )
         );
     }
 
@@ -486,15 +1761,130 @@ mod tests {
         h1.merge(h2);
         assert_eq!(
             h1,
-            StreamHist {
-                bins: vec![Bin::new(0.75, 4), Bin::new(2.5, 3), Bin::new(6.0, 2)],
-                min: 0.0,
-                max: 6.0,
-                size: 3,
-            }
+            StreamHist::from_parts(
+                vec![Bin::new(0.75, 4), Bin::new(2.5, 3), Bin::new(6.0, 2)],
+                0.0,
+                6.0,
+                3
+            )
         );
     }
 
+    #[test]
+    fn add_and_sum() {
+        let hists = vec![
+            StreamHist::from(vec![1.0, 2.0, 3.0]),
+            StreamHist::from(vec![0.0, 1.0, 2.5]),
+            StreamHist::from(vec![6.0, 6.0]),
+        ];
+        let mut expected = hists[0].clone();
+        hists[1..]
+            .iter()
+            .for_each(|hist| expected.merge(hist.clone()));
+
+        assert_eq!(hists[0].merged(&hists[1]).merged(&hists[2]), expected);
+        assert_eq!(
+            hists[0].clone() + hists[1].clone() + hists[2].clone(),
+            expected
+        );
+        assert_eq!(hists.into_iter().sum::<StreamHist>(), expected);
+        assert_eq!(
+            Vec::<StreamHist>::new().into_iter().sum::<StreamHist>(),
+            StreamHist::default()
+        );
+    }
+
+    #[test]
+    fn unmerge() {
+        let mut hist = StreamHist::from(vec![Bin::new(1.0, 3), Bin::new(5.0, 2), Bin::new(9.0, 4)]);
+        hist.unmerge(&StreamHist::from(vec![Bin::new(4.0, 3), Bin::new(10.0, 1)]));
+        assert_eq!(
+            hist,
+            StreamHist::from_parts(vec![Bin::new(1.0, 2), Bin::new(9.0, 3)], 1.0, 9.0, 3)
+        );
+        assert_eq!(hist.count(), 5.0);
+        assert_eq!(hist.mean(), (2.0 + 27.0) / 5.0);
+
+        hist.unmerge(&StreamHist::from(vec![Bin::new(0.0, 10)]));
+        assert_eq!(hist, StreamHist::with_capacity(3));
+        assert_eq!(hist.count(), 0.0);
+    }
+
+    #[test]
+    fn remove() {
+        let values: Vec<f64> = (0..200).map(|i| ((i * 7919) % 100) as f64).collect();
+        let mut hist = StreamHist::with_capacity(10);
+        values.iter().for_each(|x| hist.insert(*x));
+        let mut expected = StreamHist::with_capacity(10);
+        values[..100].iter().for_each(|x| expected.insert(*x));
+
+        for x in values[100..].iter().rev() {
+            hist.remove(*x);
+            assert!(crate::is_sorted(&hist.bins));
+            let totals = crate::totals::Totals::from_bins(&hist.bins);
+            assert_eq!(hist.totals.count, totals.count);
+            assert!((hist.totals.mean - totals.mean).abs() < 1e-9);
+            assert!((hist.totals.m2 - totals.m2).abs() < 1e-6 * totals.m2.max(1.0));
+        }
+        assert_eq!(hist.count(), 100.0);
+        assert!((hist.mean() - expected.mean()).abs() < 1e-9);
+        assert!((hist.median() - expected.median()).abs() < 5.0);
+
+        // the means are kept between the neighbors
+        let mut hist =
+            StreamHist::from(vec![Bin::new(0.0, 1), Bin::new(2.0, 2), Bin::new(10.0, 1)]);
+        hist.remove(5.9);
+        assert_eq!(hist.bins(), &[Bin::new(0.0, 2), Bin::new(10.0, 1)]);
+        assert_eq!(hist.count(), 3.0);
+
+        hist.remove(0.0);
+        hist.remove(0.0);
+        hist.remove(10.0);
+        assert_eq!(hist, StreamHist::with_capacity(3));
+        hist.remove(1.0);
+        assert!(hist.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_nan() {
+        StreamHist::from(vec![1.0]).remove(f64::NAN);
+    }
+
+    #[test]
+    fn merge_with() {
+        use super::SizePolicy;
+
+        let small = StreamHist::from(vec![1.0, 2.0]);
+        let large = StreamHist::from(vec![3.0, 4.0, 5.0, 6.0]);
+
+        let mut hist = small.clone();
+        hist.merge_with(large.clone(), SizePolicy::KeepSelf);
+        assert_eq!(hist.size, 2);
+        assert_eq!(hist.bins.len(), 2);
+
+        let mut hist = small.clone();
+        hist.merge_with(large.clone(), SizePolicy::KeepLarger);
+        assert_eq!(hist.size, 4);
+        assert_eq!(hist.bins.len(), 4);
+        assert_eq!(hist.count(), 6.0);
+
+        let mut hist = large.clone();
+        hist.merge_with(small.clone(), SizePolicy::KeepLarger);
+        assert_eq!(hist.size, 4);
+
+        let mut hist = small.clone();
+        hist.merge_with(large.clone(), SizePolicy::Fixed(10));
+        assert_eq!(hist.size, 10);
+        assert_eq!(hist.bins.len(), 6);
+        assert_eq!(hist.min, 1.0);
+        assert_eq!(hist.max, 6.0);
+
+        let mut hist = small;
+        hist.merge(large);
+        assert_eq!(hist.size, 2);
+    }
+
     #[test]
     fn resize() {
         let mut hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0]);
@@ -505,36 +1895,36 @@ mod tests {
         assert!(hist.bins.len() == 5);
         assert_eq!(
             hist,
-            StreamHist {
-                bins: vec![
+            StreamHist::from_parts(
+                vec![
                     Bin::new(1.5, 2),
                     Bin::new(3.5, 2),
                     Bin::new(5.5, 2),
                     Bin::new(7.5, 2),
                     Bin::new(9.5, 2),
                 ],
-                min: 1.0,
-                max: 10.0,
-                size: 5,
-            }
+                1.0,
+                10.0,
+                5
+            )
         );
 
         hist.resize(20);
         assert!(hist.size == 20);
         assert_eq!(
             hist,
-            StreamHist {
-                bins: vec![
+            StreamHist::from_parts(
+                vec![
                     Bin::new(1.5, 2),
                     Bin::new(3.5, 2),
                     Bin::new(5.5, 2),
                     Bin::new(7.5, 2),
                     Bin::new(9.5, 2),
                 ],
-                min: 1.0,
-                max: 10.0,
-                size: 20,
-            }
+                1.0,
+                10.0,
+                20
+            )
         );
     }
 
@@ -556,18 +1946,18 @@ mod tests {
     fn from_vec_is_sorted() {
         assert_eq!(
             StreamHist::from(vec![5.0, 1.0, 3.0, 4.0, 2.0]),
-            StreamHist {
-                bins: vec![
+            StreamHist::from_parts(
+                vec![
                     Bin::from(1.0),
                     Bin::from(2.0),
                     Bin::from(3.0),
                     Bin::from(4.0),
                     Bin::from(5.0)
                 ],
-                min: 1.0,
-                max: 5.0,
-                size: 5,
-            }
+                1.0,
+                5.0,
+                5
+            )
         );
     }
 
@@ -589,18 +1979,18 @@ mod tests {
                 Bin::from(4.0),
                 Bin::from(2.0)
             ]),
-            StreamHist {
-                bins: vec![
+            StreamHist::from_parts(
+                vec![
                     Bin::from(1.0),
                     Bin::from(2.0),
                     Bin::from(3.0),
                     Bin::from(4.0),
                     Bin::from(5.0)
                 ],
-                min: 1.0,
-                max: 5.0,
-                size: 5,
-            }
+                1.0,
+                5.0,
+                5
+            )
         );
     }
 }
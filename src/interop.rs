@@ -0,0 +1,170 @@
+use crate::bins::Bin;
+use crate::error::Error;
+use crate::hist::StreamHist;
+
+impl StreamHist {
+    /// Initialize the histogram from the `(mean, count)` centroids of the [t-digest].
+    ///
+    /// The centroids of t-digest are the same as the bins of the histogram, so they are converted as-is,
+    /// the centroids with the equal means are combined, and the ones with zero counts are skipped. The size
+    /// of the histogram is equal to the number of the centroids, and the `min` and `max` are the extreme means,
+    /// since the t-digest stores them separately.
+    ///
+    /// [t-digest]: https://github.com/tdunning/t-digest
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::InvalidParts`] if any of the means is not a number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let centroids = [(2.5, 3), (1.0, 1), (7.0, 2)];
+    /// let hist = StreamHist::from_tdigest_centroids(&centroids).unwrap();
+    ///
+    /// assert_eq!(hist.count(), 6.0);
+    /// assert_eq!(hist.to_tdigest_centroids(), vec![(1.0, 1), (2.5, 3), (7.0, 2)]);
+    /// ```
+    pub fn from_tdigest_centroids(centroids: &[(f64, u64)]) -> Result<Self, Error> {
+        let bins = to_bins(centroids.iter().copied())?;
+        let size = bins.len();
+        Ok(from_sorted_bins(bins, size))
+    }
+
+    /// The `(mean, count)` pairs of the bins, that can be used as the centroids of the [t-digest].
+    ///
+    /// [t-digest]: https://github.com/tdunning/t-digest
+    pub fn to_tdigest_centroids(&self) -> Vec<(f64, u64)> {
+        self.iter().map(|bin| bin.into()).collect()
+    }
+
+    /// Initialize the histogram with `size` bins from the `(low, high, count)` buckets of the [HdrHistogram],
+    /// e.g. the recorded values with their ranges of equivalent values.
+    ///
+    /// The values of each bucket are placed in a bin at the midpoint of its range, and the closest bins
+    /// are merged using the same procedure as in [`StreamHist::resize`]. The `min` and `max` are the lowest
+    /// and the highest bounds of the non-empty buckets.
+    ///
+    /// [HdrHistogram]: https://hdrhistogram.github.io/HdrHistogram/
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::InvalidParts`] if any of the bounds is not a number, or `low` is larger than `high`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let buckets = [(0.0, 10.0, 5), (10.0, 20.0, 3), (20.0, 40.0, 0), (40.0, 80.0, 2)];
+    /// let hist = StreamHist::from_hdr_buckets(&buckets, 10).unwrap();
+    ///
+    /// assert_eq!(hist.count(), 10.0);
+    /// assert_eq!((hist.min(), hist.max()), (0.0, 80.0));
+    /// assert_eq!(hist.bins().len(), 3);
+    /// ```
+    pub fn from_hdr_buckets(buckets: &[(f64, f64, u64)], size: usize) -> Result<Self, Error> {
+        let (mut min, mut max) = (f64::NAN, f64::NAN);
+        for (low, high, count) in buckets.iter().copied() {
+            if !low.is_finite() || !high.is_finite() {
+                return Err(Error::InvalidParts(
+                    "the bounds of the buckets are not numbers",
+                ));
+            }
+            if low > high {
+                return Err(Error::InvalidParts(
+                    "the bounds of the buckets are not ordered",
+                ));
+            }
+            if count > 0 {
+                min = min.min(low);
+                max = max.max(high);
+            }
+        }
+        let bins = to_bins(
+            buckets
+                .iter()
+                .map(|(low, high, count)| ((low + high) / 2.0, *count)),
+        )?;
+        let mut hist = from_sorted_bins(bins, size);
+        if !hist.is_empty() {
+            (hist.min, hist.max) = (min, max);
+        }
+        Ok(hist)
+    }
+}
+
+/// Sort the `(mean, count)` pairs by the means and combine the ones with equal means, skipping the empty ones.
+pub(crate) fn to_bins(pairs: impl Iterator<Item = (f64, u64)>) -> Result<Vec<Bin>, Error> {
+    let mut bins = Vec::new();
+    for (mean, count) in pairs {
+        if !mean.is_finite() {
+            return Err(Error::InvalidParts("the means of the bins are not numbers"));
+        }
+        if count > 0 {
+            bins.push(Bin::new(mean, count));
+        }
+    }
+    bins.sort();
+    bins.dedup_by(|next, prev| {
+        let same = next.mean == prev.mean;
+        if same {
+            prev.count += next.count;
+        }
+        same
+    });
+    Ok(bins)
+}
+
+/// Histogram with the sorted `bins` merged to the `size`.
+fn from_sorted_bins(bins: Vec<Bin>, size: usize) -> StreamHist {
+    let (Some(first), Some(last)) = (bins.first(), bins.last()) else {
+        return StreamHist::with_capacity(size);
+    };
+    let (min, max, len) = (first.mean, last.mean, bins.len());
+    let mut hist = StreamHist::from_parts(bins, min, max, len);
+    hist.resize(size);
+    hist
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bins::Bin;
+    use crate::hist::StreamHist;
+
+    #[test]
+    fn tdigest() {
+        let hist =
+            StreamHist::from_tdigest_centroids(&[(3.0, 1), (1.0, 2), (3.0, 4), (5.0, 0)]).unwrap();
+        assert_eq!(
+            hist,
+            StreamHist::from_parts(vec![Bin::new(1.0, 2), Bin::new(3.0, 5)], 1.0, 3.0, 2)
+        );
+        assert_eq!(hist.to_tdigest_centroids(), vec![(1.0, 2), (3.0, 5)]);
+
+        assert!(StreamHist::from_tdigest_centroids(&[]).unwrap().is_empty());
+        assert!(StreamHist::from_tdigest_centroids(&[(f64::NAN, 1)]).is_err());
+    }
+
+    #[test]
+    fn hdr() {
+        let buckets: Vec<(f64, f64, u64)> = (0..100)
+            .map(|i| (i as f64, i as f64 + 1.0, if i < 90 { 1 } else { 0 }))
+            .collect();
+        let hist = StreamHist::from_hdr_buckets(&buckets, 10).unwrap();
+        assert_eq!(hist.bins().len(), 10);
+        assert_eq!(hist.capacity(), 10);
+        assert_eq!(hist.count(), 90.0);
+        assert_eq!((hist.min(), hist.max()), (0.0, 90.0));
+        assert!((hist.mean() - 45.0).abs() < 1e-9);
+
+        assert_eq!(
+            StreamHist::from_hdr_buckets(&[(1.0, 2.0, 0)], 5).unwrap(),
+            StreamHist::with_capacity(5)
+        );
+        assert!(StreamHist::from_hdr_buckets(&[(2.0, 1.0, 1)], 5).is_err());
+        assert!(StreamHist::from_hdr_buckets(&[(1.0, f64::INFINITY, 1)], 5).is_err());
+    }
+}
@@ -29,16 +29,94 @@
 //! println!("{}", hist.to_json());
 //! ```
 
+mod accuracy;
+#[cfg(feature = "approx")]
+mod approx;
+mod array;
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "tokio")]
+mod async_io;
 mod bins;
+mod category;
+#[cfg(feature = "cbor")]
+mod cbor;
+pub mod collector;
+mod compare;
+mod concurrent;
+mod cumulative;
+mod decay;
 mod density;
+mod detailed;
+pub mod distance;
+pub mod drift;
+mod error;
 mod fast;
+mod fingerprint;
+mod fit;
+mod gaps;
+mod group;
 mod hist;
+mod interop;
+pub mod local;
+#[cfg(feature = "ndarray")]
+mod ndarray;
+pub mod observer;
+mod otel;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "plot")]
+pub mod plot;
+#[cfg(feature = "polars")]
+pub mod polars;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+#[cfg(feature = "proptest")]
+mod proptest;
+#[cfg(feature = "protobuf")]
+mod protobuf;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "quickcheck")]
+mod quickcheck;
+#[cfg(feature = "metrics")]
+pub mod recorder;
+mod sample;
 mod serde;
+mod signed;
+#[cfg(feature = "arc-swap")]
+pub mod snapshot;
 mod stats;
+#[cfg(feature = "tokio")]
+pub mod stream;
+mod totals;
+mod transform;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod window;
 
+pub use self::accuracy::{AccuracyReport, QuantileAccuracy, MAX_TARGET_SIZE};
+pub use self::array::ArrayStreamHist;
+#[cfg(feature = "tokio")]
+pub use self::async_io::parse_field;
 pub use self::bins::Bin;
-pub use self::density::{bandwidth, KernelDensity};
-pub use self::hist::StreamHist;
+pub use self::category::CategoryHist;
+pub use self::concurrent::ConcurrentStreamHist;
+pub use self::cumulative::CumulativeHist;
+pub use self::decay::DecayingStreamHist;
+pub use self::density::{bandwidth, Kernel, KernelDensity};
+pub use self::detailed::{DetailedBin, DetailedStreamHist};
+pub use self::error::{Error, InsertError};
+pub use self::fit::{Distribution, FitReport};
+pub use self::group::HistGroup;
+pub use self::hist::{MergePolicy, SizePolicy, StreamHist};
+pub use self::otel::{ExponentialBuckets, ExponentialHistogram};
+pub use self::sample::IntoSample;
+pub use self::signed::SignedHist;
+pub use self::stats::QuantileInterpolation;
+pub use self::window::WindowedStreamHist;
 
 /// Check if slice is sorted
 fn is_sorted<T>(slice: &[T]) -> bool
@@ -47,3 +125,22 @@ where
 {
     slice.windows(2).all(|w| w[0] <= w[1])
 }
+
+/// The `points` equally spaced values from `from` to `to` (inclusive).
+fn grid(from: f64, to: f64, points: usize) -> impl Iterator<Item = f64> {
+    assert!(from <= to, "{from} needs to be smaller or equal to {to}");
+    let step = if points > 1 {
+        (to - from) / (points - 1) as f64
+    } else {
+        0.0
+    };
+    (0..points).map(move |i| from + step * i as f64)
+}
+
+/// Lock the mutex ignoring the poisoning, the histograms stay valid if any of the threads panicked.
+#[inline]
+fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
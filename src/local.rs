@@ -0,0 +1,144 @@
+//! Thread-local histograms for recording the values from many threads at high rates.
+//!
+//! Each of the threads records the values to its own histogram, so the threads do not contend with each other.
+//! The histograms of all the threads are merged on demand when taking a [`snapshot`](ThreadLocalHist::snapshot).
+//! The histograms are defined using the [`thread_local_hist!`](crate::thread_local_hist) macro.
+//!
+//! # Examples
+//!
+//! ```
+//! use histr::thread_local_hist;
+//!
+//! thread_local_hist! {
+//!     static LATENCY: 10;
+//! }
+//!
+//! let threads: Vec<_> = (0..4)
+//!     .map(|i| std::thread::spawn(move || LATENCY.record(i as f64)))
+//!     .collect();
+//! threads.into_iter().for_each(|t| t.join().unwrap());
+//!
+//! let hist = LATENCY.snapshot();
+//! assert_eq!(hist.count(), 4.0);
+//! ```
+
+use crate::hist::StreamHist;
+use crate::lock;
+use std::cell::OnceCell;
+use std::sync::{Arc, Mutex};
+use std::thread::LocalKey;
+
+/// Histogram of the thread, shared with the registry for merging.
+#[doc(hidden)]
+pub type Local = OnceCell<Arc<Mutex<StreamHist>>>;
+
+/// Registry of the thread-local histograms, see the [module level documentation](crate::local) for details.
+#[derive(Debug)]
+pub struct ThreadLocalHist {
+    size: usize,
+    hists: Mutex<Vec<Arc<Mutex<StreamHist>>>>,
+    local: &'static LocalKey<Local>,
+}
+
+impl ThreadLocalHist {
+    /// Use the [`thread_local_hist!`](crate::thread_local_hist) macro instead.
+    #[doc(hidden)]
+    pub const fn new(size: usize, local: &'static LocalKey<Local>) -> Self {
+        ThreadLocalHist {
+            size,
+            hists: Mutex::new(Vec::new()),
+            local,
+        }
+    }
+
+    /// Record the `value` to the histogram of the current thread.
+    ///
+    /// The values that are not numbers (`f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`) are ignored.
+    pub fn record(&self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.local.with(|local| {
+            // the lock is contended only while the snapshot is taken
+            let hist = local.get_or_init(|| self.register());
+            lock(hist).insert(value)
+        })
+    }
+
+    /// Create the histogram for the current thread.
+    fn register(&self) -> Arc<Mutex<StreamHist>> {
+        let hist = Arc::new(Mutex::new(StreamHist::with_capacity(self.size)));
+        lock(&self.hists).push(hist.clone());
+        hist
+    }
+
+    /// Merge the histograms of all the threads, including the threads that already finished.
+    pub fn snapshot(&self) -> StreamHist {
+        let hists = lock(&self.hists).clone();
+        hists
+            .iter()
+            .fold(StreamHist::with_capacity(self.size), |mut acc, hist| {
+                acc.merge(lock(hist).clone());
+                acc
+            })
+    }
+}
+
+/// Declare a static [`ThreadLocalHist`] with histograms having the number of bins equal to the given size.
+///
+/// # Examples
+///
+/// ```
+/// use histr::thread_local_hist;
+///
+/// thread_local_hist! {
+///     /// Request latency.
+///     pub static LATENCY: 10;
+/// }
+///
+/// LATENCY.record(1.5);
+/// assert_eq!(LATENCY.snapshot().count(), 1.0);
+/// ```
+#[macro_export]
+macro_rules! thread_local_hist {
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $size:expr;) => {
+        $(#[$attr])*
+        $vis static $name: $crate::local::ThreadLocalHist = {
+            ::std::thread_local! {
+                static LOCAL: $crate::local::Local = const { ::std::cell::OnceCell::new() };
+            }
+            $crate::local::ThreadLocalHist::new($size, &LOCAL)
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    thread_local_hist! {
+        static HIST: 5;
+    }
+
+    #[test]
+    fn merge_threads() {
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                thread::spawn(move || {
+                    for j in 0..100 {
+                        HIST.record((i * 100 + j) as f64);
+                    }
+                    HIST.record(f64::NAN);
+                })
+            })
+            .collect();
+        threads.into_iter().for_each(|t| t.join().unwrap());
+
+        let hist = HIST.snapshot();
+        assert_eq!(hist.count(), 400.0);
+        assert_eq!(hist.bins.len(), 5);
+        assert_eq!(hist.min, 0.0);
+        assert_eq!(hist.max, 399.0);
+        assert_eq!(HIST.hists.lock().unwrap().len(), 4);
+    }
+}
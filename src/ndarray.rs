@@ -0,0 +1,108 @@
+use crate::density::KernelDensity;
+use crate::hist::StreamHist;
+use ndarray::{Array1, ArrayView1};
+
+impl StreamHist {
+    /// Insert all the values from the [`ndarray`] array to the histogram.
+    ///
+    /// # Panics
+    ///
+    /// All the `values` need to be a numbers. It will panic on any `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    /// use ndarray::array;
+    ///
+    /// let mut hist = StreamHist::with_capacity(5);
+    /// hist.extend_from_array(&array![1.0, 2.0, 3.0].view());
+    /// assert_eq!(hist.count(), 3.0);
+    /// ```
+    pub fn extend_from_array(&mut self, values: &ArrayView1<f64>) {
+        values.iter().for_each(|value| self.insert(*value));
+    }
+
+    /// Evaluate [`StreamHist::cdf`] at each of the `values`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    /// use ndarray::{array, Array1};
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    /// let grid = Array1::linspace(0.0, 6.0, 3);
+    /// assert_eq!(hist.cdf_array(&grid.view()), array![0.0, 0.5, 1.0]);
+    /// ```
+    pub fn cdf_array(&self, values: &ArrayView1<f64>) -> Array1<f64> {
+        values.mapv(|value| self.cdf(value))
+    }
+
+    /// Evaluate [`StreamHist::quantile`] at each of the probabilities `probs`.
+    ///
+    /// # Panics
+    ///
+    /// All the `probs` need to be probability values between `0.0` and `1.0` (inclusive), otherwise it panics.
+    pub fn quantile_array(&self, probs: &ArrayView1<f64>) -> Array1<f64> {
+        probs.mapv(|prob| self.quantile(prob))
+    }
+}
+
+impl KernelDensity {
+    /// Evaluate [`KernelDensity::density`] at each of the `values`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{KernelDensity, StreamHist};
+    /// use ndarray::Array1;
+    ///
+    /// let kde = KernelDensity::from(StreamHist::from(vec![1.0, 2.0, 2.0, 3.0]));
+    /// let grid = Array1::linspace(0.0, 4.0, 100);
+    /// let density = kde.density_array(&grid.view());
+    /// assert_eq!(density.len(), 100);
+    /// ```
+    pub fn density_array(&self, values: &ArrayView1<f64>) -> Array1<f64> {
+        values.mapv(|value| self.density(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::density::KernelDensity;
+    use crate::hist::StreamHist;
+    use ndarray::{array, Array1};
+
+    #[test]
+    fn extend_from_array() {
+        let mut hist = StreamHist::with_capacity(10);
+        hist.extend_from_array(&array![3.0, 1.0, 2.0].view());
+        hist.extend_from_array(&Array1::<f64>::zeros(0).view());
+
+        let mut expected = StreamHist::from(vec![1.0, 2.0, 3.0]);
+        expected.resize(10);
+        assert_eq!(hist, expected);
+    }
+
+    #[test]
+    fn grids() {
+        let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        let values = array![0.0, 3.0, f64::NAN];
+        let cdf = hist.cdf_array(&values.view());
+        assert_eq!(cdf.slice(ndarray::s![..2]), array![0.0, 0.5]);
+        assert!(cdf[2].is_nan());
+
+        assert_eq!(
+            hist.quantile_array(&array![0.0, 0.5, 1.0].view()),
+            array![1.0, 3.0, 5.0]
+        );
+
+        let kde = KernelDensity::from(hist);
+        let values = array![0.0, 3.0];
+        assert_eq!(
+            kde.density_array(&values.view()),
+            array![kde.density(0.0), kde.density(3.0)]
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Histograms calling the registered callbacks on inserts, e.g. for raising alarms.
+//!
+//! The [`ObservedHist`] wraps the [`StreamHist`] and calls the [`on_insert`](ObservedHist::on_insert) callbacks
+//! after each of the inserts. The [`on_quantile_exceeds`](ObservedHist::on_quantile_exceeds) callbacks are called
+//! when the quantile rises above the threshold. They are edge-triggered, so the callback is called again only after
+//! the quantile drops back to or below the threshold and then exceeds it again.
+//!
+//! # Examples
+//!
+//! ```
+//! use histr::observer::ObservedHist;
+//! use histr::StreamHist;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//!
+//! let alarms = Arc::new(AtomicUsize::new(0));
+//! let counter = alarms.clone();
+//!
+//! let mut hist = ObservedHist::new(StreamHist::with_capacity(10));
+//! hist.on_quantile_exceeds(0.5, 100.0, move |median| {
+//!     println!("the median latency is {median}");
+//!     counter.fetch_add(1, Ordering::Relaxed);
+//! });
+//!
+//! for value in [10.0, 20.0, 500.0, 600.0, 700.0] {
+//!     hist.insert(value);
+//! }
+//! assert_eq!(alarms.load(Ordering::Relaxed), 1);
+//! ```
+
+use crate::hist::StreamHist;
+use crate::sample::IntoSample;
+use std::fmt;
+
+type Callback = Box<dyn FnMut(f64) + Send>;
+
+/// [`StreamHist`] histogram with the observer callbacks, see the [module level documentation](crate::observer)
+/// for details.
+pub struct ObservedHist {
+    hist: StreamHist,
+    on_insert: Vec<Callback>,
+    alarms: Vec<QuantileAlarm>,
+}
+
+struct QuantileAlarm {
+    prob: f64,
+    threshold: f64,
+    /// The quantile was above the threshold when it was last evaluated.
+    exceeded: bool,
+    callback: Callback,
+}
+
+impl ObservedHist {
+    /// Wrap the histogram, there are no callbacks registered initially.
+    pub fn new(hist: StreamHist) -> Self {
+        ObservedHist {
+            hist,
+            on_insert: Vec::new(),
+            alarms: Vec::new(),
+        }
+    }
+
+    /// Register the `callback` called with each of the inserted values.
+    pub fn on_insert<F>(&mut self, callback: F) -> &mut Self
+    where
+        F: FnMut(f64) + Send + 'static,
+    {
+        self.on_insert.push(Box::new(callback));
+        self
+    }
+
+    /// Register the `callback` called with the value of the `prob` quantile when it rises above the `threshold`.
+    ///
+    /// The quantile is re-calculated only when inserting the values that could move it across the threshold:
+    /// the values larger than the `threshold` while it is not exceeded, and the values smaller or equal to it
+    /// otherwise, so the callbacks are cheap for the values on the expected side of the threshold. If the quantile
+    /// already exceeds the `threshold` when registering the callback, it is not called until it drops below it.
+    ///
+    /// # Panics
+    ///
+    /// `prob` needs to be between `0.0` and `1.0` (inclusive), otherwise it panics.
+    pub fn on_quantile_exceeds<F>(&mut self, prob: f64, threshold: f64, callback: F) -> &mut Self
+    where
+        F: FnMut(f64) + Send + 'static,
+    {
+        assert!(
+            (0.0..=1.0).contains(&prob),
+            "{prob} needs to be between 0 and 1"
+        );
+        let exceeded = !self.hist.is_empty() && self.hist.quantile(prob) > threshold;
+        self.alarms.push(QuantileAlarm {
+            prob,
+            threshold,
+            exceeded,
+            callback: Box::new(callback),
+        });
+        self
+    }
+
+    /// Insert a new point to the histogram and call the callbacks, see [`StreamHist::insert`].
+    ///
+    /// # Panics
+    ///
+    /// The `value` needs to be a number. It will panic on `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    pub fn insert<T: IntoSample>(&mut self, value: T) {
+        let value = value.into_sample();
+        self.hist.insert(value);
+
+        for callback in self.on_insert.iter_mut() {
+            callback(value);
+        }
+        for alarm in self.alarms.iter_mut() {
+            if alarm.exceeded == (value > alarm.threshold) {
+                continue;
+            }
+            let quantile = self.hist.quantile(alarm.prob);
+            let exceeded = quantile > alarm.threshold;
+            if exceeded && !alarm.exceeded {
+                (alarm.callback)(quantile);
+            }
+            alarm.exceeded = exceeded;
+        }
+    }
+
+    /// The observed histogram.
+    pub fn hist(&self) -> &StreamHist {
+        &self.hist
+    }
+
+    /// Unwrap the histogram, dropping the callbacks.
+    pub fn into_inner(self) -> StreamHist {
+        self.hist
+    }
+}
+
+impl fmt::Debug for ObservedHist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservedHist")
+            .field("hist", &self.hist)
+            .field("on_insert", &self.on_insert.len())
+            .field("alarms", &self.alarms.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ObservedHist;
+    use crate::hist::StreamHist;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn on_insert() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut hist = ObservedHist::new(StreamHist::with_capacity(2));
+        let copy = seen.clone();
+        hist.on_insert(move |value| copy.lock().unwrap().push(value));
+
+        hist.insert(1.0);
+        hist.insert(2_u64);
+        hist.insert(3.0);
+        assert_eq!(*seen.lock().unwrap(), vec![1.0, 2.0, 3.0]);
+        assert_eq!(hist.hist().count(), 3.0);
+        assert_eq!(hist.into_inner().bins.len(), 2);
+    }
+
+    #[test]
+    fn on_quantile_exceeds() {
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let mut hist = ObservedHist::new(StreamHist::with_capacity(10));
+        let copy = fired.clone();
+        hist.on_quantile_exceeds(1.0, 5.0, move |q| copy.lock().unwrap().push(q));
+
+        hist.insert(1.0);
+        hist.insert(6.0);
+        hist.insert(7.0);
+        assert_eq!(*fired.lock().unwrap(), vec![6.0]);
+
+        // the maximum never drops, so it does not re-arm
+        hist.insert(1.0);
+        hist.insert(8.0);
+        assert_eq!(*fired.lock().unwrap(), vec![6.0]);
+    }
+
+    #[test]
+    fn rearm() {
+        let fired = Arc::new(Mutex::new(0));
+        let mut hist = ObservedHist::new(StreamHist::with_capacity(100));
+        let copy = fired.clone();
+        hist.on_quantile_exceeds(0.5, 10.0, move |_| *copy.lock().unwrap() += 1);
+
+        for value in [20.0, 1.0, 1.0, 1.0, 20.0, 20.0, 20.0] {
+            hist.insert(value);
+        }
+        assert_eq!(*fired.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn already_exceeded() {
+        let fired = Arc::new(Mutex::new(0));
+        let mut hist = ObservedHist::new(StreamHist::from(vec![100.0]));
+        let copy = fired.clone();
+        hist.on_quantile_exceeds(0.5, 10.0, move |_| *copy.lock().unwrap() += 1);
+
+        hist.insert(200.0);
+        assert_eq!(*fired.lock().unwrap(), 0);
+    }
+}
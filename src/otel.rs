@@ -0,0 +1,373 @@
+use crate::error::Error;
+use crate::hist::StreamHist;
+use crate::interop::to_bins;
+
+/// The range of the scales supported by the OpenTelemetry exponential histograms.
+const SCALES: std::ops::RangeInclusive<i32> = -10..=20;
+
+/// Histogram in the [OpenTelemetry exponential histogram] data model, see [`StreamHist::to_otel_exponential`].
+///
+/// The bucket with the index $i$ covers the values in the range $(\mathrm{base}^i, \mathrm{base}^{i + 1}]$,
+/// where $\mathrm{base} = 2^{2^{-\mathrm{scale}}}$. The negative values are counted in the `negative` buckets
+/// by their absolute values, and the zeros in the `zero_count`. The fields map directly to the ones
+/// of the OTLP `ExponentialHistogramDataPoint` message.
+///
+/// [OpenTelemetry exponential histogram]: https://opentelemetry.io/docs/specs/otel/metrics/data-model/#exponentialhistogram
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExponentialHistogram {
+    /// The resolution of the buckets, larger scales mean narrower buckets.
+    pub scale: i32,
+    /// The count of the values equal to zero.
+    pub zero_count: u64,
+    /// The buckets of the positive values.
+    pub positive: ExponentialBuckets,
+    /// The buckets of the absolute values of the negative values.
+    pub negative: ExponentialBuckets,
+    /// The total count of the values.
+    pub count: u64,
+    /// The sum of the values.
+    pub sum: f64,
+    /// Smallest value, if known.
+    pub min: Option<f64>,
+    /// Largest value, if known.
+    pub max: Option<f64>,
+}
+
+/// Consecutive buckets of the [`ExponentialHistogram`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExponentialBuckets {
+    /// Index of the first bucket.
+    pub offset: i32,
+    /// The counts of the values in the buckets, starting from the `offset`.
+    pub bucket_counts: Vec<u64>,
+}
+
+impl ExponentialBuckets {
+    /// Count the `values` in the buckets of the `scale`, the values need to be positive.
+    fn from_values(values: impl Iterator<Item = (f64, u64)>, scale: i32) -> Self {
+        let indexes: Vec<(i32, u64)> = values
+            .map(|(value, count)| (bucket_index(value, scale), count))
+            .collect();
+        let (Some(first), Some(last)) = (
+            indexes.iter().map(|(i, _)| *i).min(),
+            indexes.iter().map(|(i, _)| *i).max(),
+        ) else {
+            return ExponentialBuckets::default();
+        };
+        let mut bucket_counts = vec![0; (last - first) as usize + 1];
+        for (i, count) in indexes {
+            bucket_counts[(i - first) as usize] += count;
+        }
+        ExponentialBuckets {
+            offset: first,
+            bucket_counts,
+        }
+    }
+
+    /// The indexes of the buckets and their upper bounds fit in `i32`.
+    fn in_range(&self) -> bool {
+        i64::from(self.offset).saturating_add(self.bucket_counts.len() as i64)
+            <= i64::from(i32::MAX)
+    }
+
+    /// Iterate over the `(lower, upper, count)` bounds and counts of the non-empty buckets of the `scale`,
+    /// the buckets need to be [in range](ExponentialBuckets::in_range).
+    fn iter(&self, scale: i32) -> impl Iterator<Item = (f64, f64, u64)> + '_ {
+        (self.offset..)
+            .zip(self.bucket_counts.iter())
+            .filter(|(_, count)| **count > 0)
+            .map(move |(i, count)| (lower_bound(i, scale), lower_bound(i + 1, scale), *count))
+    }
+}
+
+/// The lower bound of the bucket with the `index`.
+#[inline]
+fn lower_bound(index: i32, scale: i32) -> f64 {
+    (index as f64 * (-scale as f64).exp2()).exp2()
+}
+
+/// Index of the bucket of the positive `value`, corrected for the rounding errors of the logarithm.
+fn bucket_index(value: f64, scale: i32) -> i32 {
+    let mut index = (value.log2() * (scale as f64).exp2()).ceil() as i32 - 1;
+    while lower_bound(index, scale) >= value {
+        index -= 1;
+    }
+    while lower_bound(index + 1, scale) < value {
+        index += 1;
+    }
+    index
+}
+
+impl StreamHist {
+    /// Convert to the [OpenTelemetry exponential histogram] data model with the `scale`, so it can be
+    /// exported using the OTLP exporters.
+    ///
+    /// The counts of the bins are assigned to the buckets containing their means, so the count and the sum
+    /// of the values are preserved exactly, while the bins are approximated with the resolution of the buckets.
+    /// The buckets span from the smallest to the largest of the bin means, so the large scales for
+    /// the histograms of the values spanning many orders of magnitude give many buckets.
+    ///
+    /// [OpenTelemetry exponential histogram]: https://opentelemetry.io/docs/specs/otel/metrics/data-model/#exponentialhistogram
+    ///
+    /// # Panics
+    ///
+    /// The `scale` needs to be between `-10` and `20` (inclusive), as in OpenTelemetry, otherwise it panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Bin, StreamHist};
+    ///
+    /// let hist = StreamHist::from(vec![Bin::new(-3.0, 1), Bin::new(0.0, 2), Bin::new(1.5, 3), Bin::new(5.0, 4)]);
+    /// let otel = hist.to_otel_exponential(0);
+    ///
+    /// // buckets (1, 2], (2, 4], (4, 8]
+    /// assert_eq!(otel.positive.offset, 0);
+    /// assert_eq!(otel.positive.bucket_counts, vec![3, 0, 4]);
+    /// // bucket (2, 4] for the absolute values
+    /// assert_eq!((otel.negative.offset, otel.negative.bucket_counts), (1, vec![1]));
+    /// assert_eq!(otel.zero_count, 2);
+    /// assert_eq!((otel.count, otel.sum), (10, 21.5));
+    /// ```
+    pub fn to_otel_exponential(&self, scale: i32) -> ExponentialHistogram {
+        assert!(
+            SCALES.contains(&scale),
+            "scale {scale} needs to be between -10 and 20"
+        );
+        let pairs = || self.iter().map(|bin| (bin.mean, bin.count));
+        ExponentialHistogram {
+            scale,
+            zero_count: self
+                .iter()
+                .filter(|bin| bin.mean == 0.0)
+                .map(|bin| bin.count)
+                .sum(),
+            positive: ExponentialBuckets::from_values(pairs().filter(|(x, _)| *x > 0.0), scale),
+            negative: ExponentialBuckets::from_values(
+                pairs().filter(|(x, _)| *x < 0.0).map(|(x, n)| (-x, n)),
+                scale,
+            ),
+            count: self.count() as u64,
+            sum: self.iter().map(|bin| bin.mean * bin.count as f64).sum(),
+            min: (!self.is_empty()).then_some(self.min),
+            max: (!self.is_empty()).then_some(self.max),
+        }
+    }
+
+    /// Convert from the [OpenTelemetry exponential histogram] data model, see [`StreamHist::to_otel_exponential`].
+    ///
+    /// Each of the non-empty buckets becomes a bin with the mean in the middle of the bucket, clamped to the `min`
+    /// and `max` if they are known, and the zeros become a bin with the mean equal to zero. The histogram has
+    /// as many bins as the non-empty buckets, it can be reduced with [`StreamHist::resize`].
+    ///
+    /// [OpenTelemetry exponential histogram]: https://opentelemetry.io/docs/specs/otel/metrics/data-model/#exponentialhistogram
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::InvalidParts`] if the `scale` is not between `-10` and `20`, the indexes of the buckets
+    /// do not fit in `i32`, the `count` is not equal to the total count of the buckets or the total overflows `u64`,
+    /// or the `min` or `max` are not numbers or do not bound the buckets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Bin, ExponentialBuckets, ExponentialHistogram, StreamHist};
+    ///
+    /// let otel = ExponentialHistogram {
+    ///     scale: 0,
+    ///     zero_count: 0,
+    ///     positive: ExponentialBuckets { offset: 0, bucket_counts: vec![3, 0, 4] },
+    ///     negative: ExponentialBuckets::default(),
+    ///     count: 7,
+    ///     sum: 25.0,
+    ///     min: None,
+    ///     max: None,
+    /// };
+    /// let hist = StreamHist::from_otel_exponential(&otel).unwrap();
+    /// assert_eq!(hist.count(), 7.0);
+    /// assert_eq!(hist.bins(), &[Bin::new(1.5, 3), Bin::new(6.0, 4)]);
+    /// assert_eq!((hist.min(), hist.max()), (1.0, 8.0));
+    /// ```
+    pub fn from_otel_exponential(hist: &ExponentialHistogram) -> Result<StreamHist, Error> {
+        if !SCALES.contains(&hist.scale) {
+            return Err(Error::InvalidParts("the scale is not between -10 and 20"));
+        }
+        if !hist.positive.in_range() || !hist.negative.in_range() {
+            return Err(Error::InvalidParts(
+                "the indexes of the buckets are out of range",
+            ));
+        }
+        let total = [&hist.positive, &hist.negative]
+            .iter()
+            .flat_map(|buckets| buckets.bucket_counts.iter())
+            .try_fold(hist.zero_count, |acc, count| acc.checked_add(*count))
+            .ok_or(Error::InvalidParts("the sum of the counts overflows"))?;
+        if total != hist.count {
+            return Err(Error::InvalidParts(
+                "the count is not equal to the counts of the buckets",
+            ));
+        }
+
+        let positive = hist.positive.iter(hist.scale);
+        let negative = hist
+            .negative
+            .iter(hist.scale)
+            .map(|(lower, upper, count)| (-upper, -lower, count));
+        let zero = (hist.zero_count > 0).then_some((0.0, 0.0, hist.zero_count));
+        let buckets: Vec<(f64, f64, u64)> = negative.chain(zero).chain(positive).collect();
+
+        let (Some(first), Some(last)) = (buckets.first(), buckets.last()) else {
+            return Ok(StreamHist::default());
+        };
+        let min = hist.min.unwrap_or(first.0);
+        let max = hist.max.unwrap_or(last.1);
+        if !min.is_finite() || !max.is_finite() || min > max {
+            return Err(Error::InvalidParts("min or max is not a number"));
+        }
+        // the bucket bounds are exclusive, so min and max cannot be outside of the buckets
+        if min > first.1 || (max <= last.0 && last.0 > 0.0) || max < last.0 {
+            return Err(Error::InvalidParts(
+                "the bins are outside of the min and max range",
+            ));
+        }
+        let bins = to_bins(
+            buckets
+                .into_iter()
+                .map(|(lower, upper, count)| (((lower + upper) / 2.0).clamp(min, max), count)),
+        )?;
+        let size = bins.len();
+        StreamHist::try_from_parts(bins, min, max, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bucket_index, lower_bound, ExponentialBuckets, ExponentialHistogram};
+    use crate::bins::Bin;
+    use crate::hist::StreamHist;
+    use test_case::test_case;
+
+    #[test_case(1.0, 0, -1 ; "upper bound is inclusive")]
+    #[test_case(1.5, 0, 0)]
+    #[test_case(2.0, 0, 0 ; "power of two")]
+    #[test_case(1024.0, 3, 79)]
+    #[test_case(0.1, 2, -14)]
+    #[test_case(1e6, -2, 4)]
+    fn index(value: f64, scale: i32, expected: i32) {
+        assert_eq!(bucket_index(value, scale), expected);
+        assert!(lower_bound(expected, scale) < value);
+        assert!(value <= lower_bound(expected + 1, scale));
+    }
+
+    #[test]
+    fn roundtrip() {
+        let hist: StreamHist = (1..=1000).map(|x| x as f64 / 10.0).collect();
+        let otel = hist.to_otel_exponential(4);
+        assert_eq!(otel.count, 1000);
+        assert!((otel.sum - 50050.0).abs() < 1e-6);
+        assert_eq!((otel.min, otel.max), (Some(0.1), Some(100.0)));
+
+        let restored = StreamHist::from_otel_exponential(&otel).unwrap();
+        assert_eq!(restored.count(), 1000.0);
+        assert_eq!((restored.min(), restored.max()), (0.1, 100.0));
+        // the width of the buckets is about 4.4% of the values
+        for prob in [0.1, 0.5, 0.9, 0.99] {
+            let (expected, result) = (hist.quantile(prob), restored.quantile(prob));
+            assert!(
+                (result - expected).abs() / expected < 0.05,
+                "{result} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn negative() {
+        let hist = StreamHist::from(vec![Bin::new(-5.0, 1), Bin::new(-1.5, 2), Bin::new(0.0, 3)]);
+        let otel = hist.to_otel_exponential(0);
+        assert_eq!(otel.negative.offset, 0);
+        assert_eq!(otel.negative.bucket_counts, vec![2, 0, 1]);
+        assert!(otel.positive.bucket_counts.is_empty());
+
+        let restored = StreamHist::from_otel_exponential(&otel).unwrap();
+        assert_eq!(
+            restored.bins(),
+            &[Bin::new(-5.0, 1), Bin::new(-1.5, 2), Bin::new(0.0, 3)]
+        );
+        assert_eq!(restored.count(), 6.0);
+    }
+
+    #[test]
+    fn invalid() {
+        let valid = StreamHist::from(vec![1.0, 2.0, 3.0]).to_otel_exponential(1);
+        assert!(StreamHist::from_otel_exponential(&valid).is_ok());
+        for hist in [
+            ExponentialHistogram {
+                scale: 21,
+                ..valid.clone()
+            },
+            ExponentialHistogram {
+                count: 4,
+                ..valid.clone()
+            },
+            ExponentialHistogram {
+                min: Some(f64::NAN),
+                ..valid.clone()
+            },
+            ExponentialHistogram {
+                max: Some(1.0),
+                ..valid.clone()
+            },
+        ] {
+            assert!(
+                StreamHist::from_otel_exponential(&hist).is_err(),
+                "{hist:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_range() {
+        let buckets = |offset, bucket_counts| ExponentialBuckets {
+            offset,
+            bucket_counts,
+        };
+        let hist = |positive, count| ExponentialHistogram {
+            scale: 0,
+            zero_count: 0,
+            positive,
+            negative: ExponentialBuckets::default(),
+            count,
+            sum: 0.0,
+            min: None,
+            max: None,
+        };
+        for offset in [i32::MAX, i32::MAX - 1] {
+            assert!(
+                StreamHist::from_otel_exponential(&hist(buckets(offset, vec![1, 1]), 2)).is_err()
+            );
+        }
+        let overflow = hist(buckets(0, vec![u64::MAX, 1]), 0);
+        assert!(StreamHist::from_otel_exponential(&overflow).is_err());
+        let overflow = ExponentialHistogram {
+            zero_count: u64::MAX,
+            ..hist(buckets(0, vec![1]), 0)
+        };
+        assert!(StreamHist::from_otel_exponential(&overflow).is_err());
+    }
+
+    #[test]
+    fn empty() {
+        let otel = StreamHist::default().to_otel_exponential(0);
+        assert_eq!((otel.count, otel.min, otel.max), (0, None, None));
+        assert_eq!(
+            StreamHist::from_otel_exponential(&otel),
+            Ok(StreamHist::default())
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_scale() {
+        StreamHist::from(vec![1.0]).to_otel_exponential(-11);
+    }
+}
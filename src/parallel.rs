@@ -0,0 +1,130 @@
+use crate::bins::Bin;
+use crate::hist::StreamHist;
+use crate::sample::IntoSample;
+use rayon::prelude::*;
+
+impl<T: IntoSample + Send> ParallelExtend<T> for StreamHist {
+    /// Insert the values to the histogram in parallel.
+    ///
+    /// Each of the threads builds a partial histogram of the same `size` as this histogram,
+    /// then the partial histograms are merged using the [`StreamHist::merge`] procedure.
+    ///
+    /// # Panics
+    ///
+    /// All the `values` need to be a numbers. It will panic on any `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    /// use rayon::prelude::*;
+    ///
+    /// let mut hist = StreamHist::with_capacity(10);
+    /// hist.par_extend((0..10_000).into_par_iter().map(|x| x as f64));
+    ///
+    /// assert_eq!(hist.count(), 10_000.0);
+    /// assert_eq!(hist.bins().len(), 10);
+    /// ```
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        let size = self.size;
+        let other = par_iter
+            .into_par_iter()
+            .fold(
+                || StreamHist::with_capacity(size),
+                |mut hist, value| {
+                    hist.insert(value);
+                    hist
+                },
+            )
+            .reduce(
+                || StreamHist::with_capacity(size),
+                |mut hist, other| {
+                    hist.merge(other);
+                    hist
+                },
+            );
+        self.merge(other);
+    }
+}
+
+impl<T: IntoSample + Send> FromParallelIterator<T> for StreamHist {
+    /// Initialize histogram from the values in parallel.
+    ///
+    /// The same as initializing the histogram from a vector of values, the histogram has as many bins as the values. To create a histogram with a limited number of bins,
+    /// use [`ParallelExtend::par_extend`] on a histogram initialized with [`StreamHist::with_capacity`].
+    ///
+    /// # Panics
+    ///
+    /// All the `values` need to be a numbers. It will panic on any `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    /// use rayon::prelude::*;
+    ///
+    /// let data = vec![5.0, 1.0, 3.0, 4.0, 2.0];
+    /// let hist: StreamHist = data.par_iter().copied().collect();
+    ///
+    /// assert_eq!(hist, StreamHist::from(data));
+    /// ```
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        let mut bins: Vec<Bin> = par_iter
+            .into_par_iter()
+            .map(|value| Bin::from(value.into_sample()))
+            .collect();
+        bins.par_sort();
+        StreamHist::from(bins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hist::StreamHist;
+    use rayon::prelude::*;
+
+    #[test]
+    fn par_extend() {
+        let data: Vec<f64> = (0..1000).map(|x| (x % 100) as f64).collect();
+
+        let mut hist = StreamHist::with_capacity(10);
+        hist.par_extend(data.par_iter().copied());
+        assert_eq!(hist.count(), 1000.0);
+        assert_eq!(hist.bins.len(), 10);
+        assert_eq!(hist.size, 10);
+        assert_eq!(hist.min, 0.0);
+        assert_eq!(hist.max, 99.0);
+        assert!((hist.mean() - 49.5).abs() < 1e-9);
+
+        // extending again keeps the previous data
+        hist.par_extend(data.par_iter().copied());
+        assert_eq!(hist.count(), 2000.0);
+        assert_eq!(hist.bins.len(), 10);
+    }
+
+    #[test]
+    fn par_extend_empty() {
+        let mut hist = StreamHist::with_capacity(10);
+        hist.par_extend(Vec::<f64>::new());
+        assert_eq!(hist, StreamHist::with_capacity(10));
+    }
+
+    #[test]
+    fn from_par_iter() {
+        let data: Vec<f64> = (0..1000).rev().map(|x| x as f64).collect();
+        let hist: StreamHist = data.par_iter().copied().collect();
+        assert_eq!(hist, StreamHist::from(data.clone()));
+
+        let hist: StreamHist = Vec::<f64>::new().into_par_iter().collect();
+        assert_eq!(hist, StreamHist::default());
+
+        let hist: StreamHist = (0..1000_u64).into_par_iter().collect();
+        assert_eq!(hist, StreamHist::from(data));
+    }
+}
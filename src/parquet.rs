@@ -0,0 +1,180 @@
+//! Storing the histograms in [Apache Parquet] files, alongside the tabular data.
+//!
+//! The histograms are stored in a binary column, each serialized to the MessagePack format
+//! (see [`StreamHist::write_msgpack`]), so for example a histogram can be stored per each group of the data.
+//!
+//! [Apache Parquet]: https://parquet.apache.org/
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate tempdir;
+//! use arrow_array::{ArrayRef, RecordBatch, StringArray};
+//! use histr::parquet::{append_histogram_column, read_histogram_column, read_parquet, write_parquet};
+//! use histr::StreamHist;
+//! use std::fs::File;
+//! use std::sync::Arc;
+//! use tempdir::TempDir;
+//!
+//! let groups = Arc::new(StringArray::from(vec!["a", "b"])) as ArrayRef;
+//! let batch = RecordBatch::try_from_iter(vec![("group", groups)]).unwrap();
+//! let hists = vec![
+//!     StreamHist::from(vec![1.0, 2.0, 3.0]),
+//!     StreamHist::from(vec![10.0, 20.0]),
+//! ];
+//! let batch = append_histogram_column(&batch, "hist", &hists).unwrap();
+//!
+//! let temp_dir = TempDir::new("example").unwrap();
+//! let file_path = temp_dir.path().join("hists.parquet");
+//! write_parquet(File::create(&file_path).unwrap(), &batch).unwrap();
+//!
+//! let batches = read_parquet(File::open(&file_path).unwrap()).unwrap();
+//! let restored = read_histogram_column(&batches[0], "hist").unwrap();
+//! assert_eq!(restored, vec![Some(hists[0].clone()), Some(hists[1].clone())]);
+//! ```
+
+use crate::hist::StreamHist;
+use arrow_array::{Array, ArrayRef, BinaryArray, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+use parquet::file::reader::ChunkReader;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Add the column with the `name` holding the histograms, one per each row of the `batch`.
+///
+/// # Errors
+///
+/// It returns an error if the number of the histograms differs from the number of the rows,
+/// or the column with the `name` already exists.
+pub fn append_histogram_column(
+    batch: &RecordBatch,
+    name: &str,
+    hists: &[StreamHist],
+) -> Result<RecordBatch, ArrowError> {
+    if batch.schema_ref().column_with_name(name).is_some() {
+        return Err(ArrowError::SchemaError(format!(
+            "column {name} already exists"
+        )));
+    }
+    let values = hists
+        .iter()
+        .map(|hist| rmp_serde::to_vec(hist).map_err(|err| ArrowError::ExternalError(Box::new(err))))
+        .collect::<Result<Vec<_>, _>>()?;
+    let array = BinaryArray::from_iter_values(values);
+
+    let mut fields: Vec<Field> = batch
+        .schema_ref()
+        .fields()
+        .iter()
+        .map(|field| field.as_ref().clone())
+        .collect();
+    fields.push(Field::new(name, DataType::Binary, false));
+    let schema = Schema::new_with_metadata(fields, batch.schema_ref().metadata().clone());
+
+    let mut columns = batch.columns().to_vec();
+    columns.push(Arc::new(array) as ArrayRef);
+    RecordBatch::try_new(Arc::new(schema), columns)
+}
+
+/// Read the histograms from the column with the `name`, see [`append_histogram_column`].
+/// The null values are returned as `None`.
+///
+/// # Errors
+///
+/// It returns an error if the column is missing, is not a binary column, or any of the histograms
+/// cannot be deserialized.
+pub fn read_histogram_column(
+    batch: &RecordBatch,
+    name: &str,
+) -> Result<Vec<Option<StreamHist>>, ArrowError> {
+    let array = batch
+        .column_by_name(name)
+        .ok_or_else(|| ArrowError::SchemaError(format!("column {name} is missing")))?
+        .as_any()
+        .downcast_ref::<BinaryArray>()
+        .ok_or_else(|| ArrowError::SchemaError(format!("column {name} has invalid type")))?;
+    array
+        .iter()
+        .map(|value| {
+            value
+                .map(rmp_serde::from_slice::<StreamHist>)
+                .transpose()
+                .map_err(|err| ArrowError::ExternalError(Box::new(err)))
+        })
+        .collect()
+}
+
+/// Write the `batch` to the Parquet file using the writer, with the default settings.
+pub fn write_parquet<W>(writer: W, batch: &RecordBatch) -> Result<(), ParquetError>
+where
+    W: Write + Send,
+{
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Read all the record batches from the Parquet file, e.g. a [`std::fs::File`].
+pub fn read_parquet<R>(reader: R) -> Result<Vec<RecordBatch>, ParquetError>
+where
+    R: ChunkReader + 'static,
+{
+    ParquetRecordBatchReaderBuilder::try_new(reader)?
+        .build()?
+        .map(|batch| batch.map_err(ParquetError::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_histogram_column, read_histogram_column, read_parquet, write_parquet};
+    use crate::hist::StreamHist;
+    use arrow_array::{ArrayRef, BinaryArray, Int32Array, RecordBatch};
+    use std::fs::File;
+    use std::sync::Arc;
+    use tempdir::TempDir;
+
+    #[test]
+    fn write_read_file() {
+        let temp_dir = TempDir::new("tests").unwrap();
+        let file_path = temp_dir.path().join("hists.parquet");
+
+        let ids = Arc::new(Int32Array::from(vec![1, 2, 3])) as ArrayRef;
+        let batch = RecordBatch::try_from_iter(vec![("id", ids)]).unwrap();
+        let hists = vec![
+            StreamHist::from(vec![1.0, 2.0]),
+            StreamHist::with_capacity(5),
+            StreamHist::from(vec![-1.0, 0.5, 0.5, 8.0]),
+        ];
+        let batch = append_histogram_column(&batch, "hist", &hists).unwrap();
+        write_parquet(File::create(&file_path).unwrap(), &batch).unwrap();
+
+        let batches = read_parquet(File::open(&file_path).unwrap()).unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_columns(), 2);
+        let restored: Vec<StreamHist> = read_histogram_column(&batches[0], "hist")
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(restored, hists);
+    }
+
+    #[test]
+    fn invalid() {
+        let ids = Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef;
+        let batch = RecordBatch::try_from_iter(vec![("id", ids)]).unwrap();
+        assert!(append_histogram_column(&batch, "hist", &[StreamHist::default()]).is_err());
+        assert!(append_histogram_column(&batch, "id", &[]).is_err());
+        assert!(read_histogram_column(&batch, "hist").is_err());
+        assert!(read_histogram_column(&batch, "id").is_err());
+
+        let garbage = Arc::new(BinaryArray::from_vec(vec![b"abc"])) as ArrayRef;
+        let batch = RecordBatch::try_from_iter(vec![("hist", garbage)]).unwrap();
+        assert!(read_histogram_column(&batch, "hist").is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Plotting the histograms and kernel densities using the [`plotters`] crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use histr::{KernelDensity, StreamHist};
+//! use plotters::prelude::*;
+//!
+//! let mut hist = StreamHist::from(vec![1.0, 2.0, 2.0, 3.0, 3.5, 4.0, 7.0]);
+//! hist.resize(4);
+//! let kde = KernelDensity::from(hist.clone());
+//!
+//! let mut svg = String::new();
+//! {
+//!     let root = SVGBackend::with_string(&mut svg, (640, 480)).into_drawing_area();
+//!     let mut chart = ChartBuilder::on(&root)
+//!         .build_cartesian_2d(0.0..8.0, 0.0..4.0)
+//!         .unwrap();
+//!     hist.draw(&mut chart).unwrap();
+//!
+//!     let mut chart = ChartBuilder::on(&root)
+//!         .build_cartesian_2d(0.0..8.0, 0.0..0.5)
+//!         .unwrap();
+//!     kde.draw(&mut chart).unwrap();
+//! }
+//! assert!(svg.contains("<rect"));
+//! ```
+
+use crate::density::KernelDensity;
+use crate::hist::StreamHist;
+use plotters::coord::types::RangedCoordf64;
+use plotters::prelude::*;
+
+/// Chart with numeric axes, as created by `ChartBuilder::build_cartesian_2d` for `f64` ranges.
+pub type Chart<'a, DB> = ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>;
+
+/// The number of points at which the kernel density is evaluated when drawing it.
+const DENSITY_POINTS: usize = 500;
+
+impl StreamHist {
+    /// Draw the histogram as bars with the heights equal to the counts of the bins.
+    ///
+    /// The bars are centered at the bin means and span between the midpoints to the neighboring bins
+    /// (the first and the last bar start at the smallest and end at the largest observed value).
+    pub fn draw<DB: DrawingBackend>(
+        &self,
+        chart: &mut Chart<'_, DB>,
+    ) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+        self.draw_with_style(chart, BLUE.mix(0.5).filled())
+    }
+
+    /// Draw the histogram using the given style, see [`StreamHist::draw`] for details.
+    pub fn draw_with_style<DB: DrawingBackend>(
+        &self,
+        chart: &mut Chart<'_, DB>,
+        style: impl Into<ShapeStyle>,
+    ) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+        let style = style.into();
+        chart.draw_series(
+            self.iter()
+                .zip(self.bin_edges())
+                .map(|(bin, (left, right))| {
+                    Rectangle::new([(left, 0.0), (right, bin.count as f64)], style)
+                }),
+        )?;
+        Ok(())
+    }
+}
+
+impl KernelDensity {
+    /// Draw the density as a line spanning the whole range of the x-axis of the chart.
+    ///
+    /// # Errors
+    ///
+    /// Besides the errors of the backend, it returns `DrawingAreaErrorKind::LayoutError` if the range
+    /// of the x-axis is empty, reversed, or not finite.
+    pub fn draw<DB: DrawingBackend>(
+        &self,
+        chart: &mut Chart<'_, DB>,
+    ) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+        self.draw_with_style(chart, RED.stroke_width(2))
+    }
+
+    /// Draw the density using the given style, see [`KernelDensity::draw`] for details.
+    pub fn draw_with_style<DB: DrawingBackend>(
+        &self,
+        chart: &mut Chart<'_, DB>,
+        style: impl Into<ShapeStyle>,
+    ) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+        let range = chart.x_range();
+        if !(range.start.is_finite() && range.end.is_finite() && range.start < range.end) {
+            return Err(DrawingAreaErrorKind::LayoutError);
+        }
+        let points = self.evaluate_grid(range.start, range.end, DENSITY_POINTS);
+        chart.draw_series(LineSeries::new(points, style))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::density::KernelDensity;
+    use crate::hist::StreamHist;
+    use plotters::prelude::*;
+
+    #[test]
+    fn draw() {
+        let hist = StreamHist::from(vec![1.0, 2.0, 2.0, 3.0, 7.0]);
+        let kde = KernelDensity::from(hist.clone());
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (640, 480)).into_drawing_area();
+            let mut chart = ChartBuilder::on(&root)
+                .build_cartesian_2d(0.0..8.0, 0.0..3.0)
+                .unwrap();
+            hist.draw(&mut chart).unwrap();
+            kde.draw(&mut chart).unwrap();
+        }
+        assert_eq!(svg.matches("<rect").count(), hist.bins().len());
+        assert!(svg.contains("<polyline"));
+    }
+
+    #[test]
+    fn invalid_range() {
+        let kde = KernelDensity::from(StreamHist::from(vec![1.0, 2.0, 3.0]));
+        for (from, to) in [(8.0, 0.0), (1.0, 1.0), (0.0, f64::INFINITY)] {
+            let mut svg = String::new();
+            let root = SVGBackend::with_string(&mut svg, (640, 480)).into_drawing_area();
+            let mut chart = ChartBuilder::on(&root)
+                .build_cartesian_2d(from..to, 0.0..1.0)
+                .unwrap();
+            assert!(matches!(
+                kde.draw(&mut chart),
+                Err(DrawingAreaErrorKind::LayoutError)
+            ));
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Integration with the [Polars] dataframes.
+//!
+//! Besides initializing [`StreamHist`] from a [`Series`], the [`quantiles`] function can be used as a
+//! user-defined function in Polars expressions to summarize the groups of a dataframe, for example:
+//!
+//! ```ignore
+//! use polars::prelude::*;
+//!
+//! df.lazy()
+//!     .group_by([col("endpoint")])
+//!     .agg([col("latency").apply(
+//!         |c| histr::polars::quantiles(&c, 64, &[0.5, 0.99]),
+//!         |_, field| Ok(Field::new(field.name().clone(), DataType::List(Box::new(DataType::Float64)))),
+//!     )])
+//! ```
+//!
+//! [Polars]: https://pola.rs/
+
+use crate::hist::StreamHist;
+use polars::prelude::*;
+
+impl StreamHist {
+    /// Initialize histogram from the values of the [`Series`], the null values are skipped.
+    ///
+    /// The same as initializing the histogram from a vector of values, the histogram has as many bins as the values.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the series cannot be cast to `Float64`.
+    ///
+    /// # Panics
+    ///
+    /// All the non-null values need to be a numbers. It will panic on any `f64::NAN`, `f64::INFINITY`,
+    /// or `f64::NEG_INFINITY`.
+    pub fn from_series(series: &Series) -> PolarsResult<Self> {
+        let values = series.strict_cast(&DataType::Float64)?;
+        let values: Vec<f64> = values.f64()?.into_iter().flatten().collect();
+        Ok(StreamHist::from(values))
+    }
+
+    /// Insert the values of the [`Series`] to the histogram, the null values are skipped.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the series cannot be cast to `Float64`.
+    ///
+    /// # Panics
+    ///
+    /// All the non-null values need to be a numbers. It will panic on any `f64::NAN`, `f64::INFINITY`,
+    /// or `f64::NEG_INFINITY`.
+    pub fn extend_from_series(&mut self, series: &Series) -> PolarsResult<()> {
+        let values = series.strict_cast(&DataType::Float64)?;
+        values
+            .f64()?
+            .into_iter()
+            .flatten()
+            .for_each(|value| self.insert(value));
+        Ok(())
+    }
+}
+
+/// Build the histogram with the number of bins equal to `size` from the values of the `column` and return
+/// its [`StreamHist::quantile`]s for the probabilities `probs` as a single-row list column.
+///
+/// The null and non-finite values are skipped.
+///
+/// # Errors
+///
+/// It returns an error if the column cannot be cast to `Float64` or any of the `probs` is not a valid probability.
+pub fn quantiles(column: &Column, size: usize, probs: &[f64]) -> PolarsResult<Column> {
+    if let Some(prob) = probs.iter().find(|prob| !(0.0..=1.0).contains(*prob)) {
+        polars_bail!(InvalidOperation: "{} is not a valid probability", prob);
+    }
+    let values = column.strict_cast(&DataType::Float64)?;
+    let mut hist = StreamHist::with_capacity(size);
+    values
+        .f64()?
+        .into_iter()
+        .flatten()
+        .filter(|value| value.is_finite())
+        .for_each(|value| hist.insert(value));
+
+    let result = hist.quantiles(probs);
+    let list = Series::new(column.name().clone(), result).implode()?;
+    Ok(list.into_series().into_column())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::quantiles;
+    use crate::hist::StreamHist;
+    use polars::prelude::*;
+
+    #[test]
+    fn from_series() {
+        let series = Series::new("x".into(), [Some(3.0), None, Some(1.0), Some(2.0)]);
+        assert_eq!(
+            StreamHist::from_series(&series).unwrap(),
+            StreamHist::from(vec![1.0, 2.0, 3.0])
+        );
+
+        let series = Series::new("x".into(), [3i32, 1, 2]);
+        assert_eq!(
+            StreamHist::from_series(&series).unwrap(),
+            StreamHist::from(vec![1.0, 2.0, 3.0])
+        );
+
+        let series = Series::new("x".into(), ["a", "b"]);
+        assert!(StreamHist::from_series(&series).is_err());
+    }
+
+    #[test]
+    fn extend_from_series() {
+        let mut hist = StreamHist::with_capacity(2);
+        hist.extend_from_series(&Series::new("x".into(), [1.0, 2.0, 10.0]))
+            .unwrap();
+        assert_eq!(hist.count(), 3.0);
+        assert_eq!(hist.bins.len(), 2);
+    }
+
+    #[test]
+    fn quantiles_column() {
+        let column = Column::new("x".into(), [1.0, 2.0, 3.0, 4.0, 5.0]);
+        let result = quantiles(&column, 10, &[0.0, 0.5, 1.0]).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.name().as_str(), "x");
+
+        let values = result.list().unwrap().get_as_series(0).unwrap();
+        let values: Vec<Option<f64>> = values.f64().unwrap().into_iter().collect();
+        assert_eq!(values, vec![Some(1.0), Some(3.0), Some(5.0)]);
+
+        assert!(quantiles(&column, 10, &[2.0]).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! Integration with the [`prometheus_client`] crate.
+//!
+//! [`PrometheusHistogram`] wraps a [`StreamHist`] so it can be registered in the [`prometheus_client::registry::Registry`]
+//! and exposed as a Prometheus histogram. The counts for the Prometheus buckets are approximated using
+//! [`StreamHist::counts_for_bounds`] at the scrape time.
+//!
+//! # Examples
+//!
+//! ```
+//! use histr::prometheus::{Buckets, PrometheusHistogram};
+//! use prometheus_client::encoding::text::encode;
+//! use prometheus_client::registry::Registry;
+//!
+//! let latency = PrometheusHistogram::with_capacity(64, Buckets::Fixed(vec![10.0, 50.0, 200.0]));
+//! let mut registry = Registry::default();
+//! registry.register("latency", "Request latency", latency.clone());
+//!
+//! for value in [5.0, 20.0, 40.0, 100.0, 500.0] {
+//!     latency.observe(value);
+//! }
+//!
+//! let mut buffer = String::new();
+//! encode(&mut buffer, &registry).unwrap();
+//! assert!(buffer.contains("latency_count 5"));
+//! ```
+
+use crate::hist::StreamHist;
+use crate::lock;
+use prometheus_client::encoding::{EncodeMetric, MetricEncoder, NoLabelSet};
+use prometheus_client::metrics::{MetricType, TypedMetric};
+use std::sync::{Arc, Mutex};
+
+/// Upper bounds of the Prometheus histogram buckets.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Buckets {
+    /// Fixed upper bounds.
+    Fixed(Vec<f64>),
+    /// Upper bounds given by the [`StreamHist::quantile`]s for the probabilities, recalculated at each scrape.
+    Quantiles(Vec<f64>),
+}
+
+/// [`StreamHist`] exposed as a Prometheus histogram.
+///
+/// It can be cloned cheaply, the clones share the same histogram.
+#[derive(Debug, Clone)]
+pub struct PrometheusHistogram {
+    hist: Arc<Mutex<StreamHist>>,
+    buckets: Buckets,
+}
+
+impl PrometheusHistogram {
+    /// Initialize the histogram with the number of bins equal to `size`.
+    ///
+    /// # Panics
+    ///
+    /// The probabilities for [`Buckets::Quantiles`] need to be between `0.0` and `1.0` (inclusive).
+    pub fn with_capacity(size: usize, buckets: Buckets) -> Self {
+        PrometheusHistogram::from_hist(StreamHist::with_capacity(size), buckets)
+    }
+
+    /// Expose an existing histogram.
+    ///
+    /// # Panics
+    ///
+    /// The probabilities for [`Buckets::Quantiles`] need to be between `0.0` and `1.0` (inclusive).
+    pub fn from_hist(hist: StreamHist, buckets: Buckets) -> Self {
+        if let Buckets::Quantiles(ref probs) = buckets {
+            for prob in probs {
+                assert!(
+                    (0.0..=1.0).contains(prob),
+                    "{prob} is not a valid probability"
+                );
+            }
+        }
+        PrometheusHistogram {
+            hist: Arc::new(Mutex::new(hist)),
+            buckets,
+        }
+    }
+
+    /// Insert the `value` to the histogram, the values that are not numbers are ignored.
+    pub fn observe(&self, value: f64) {
+        if value.is_finite() {
+            lock(&self.hist).insert(value);
+        }
+    }
+
+    /// Copy of the underlying histogram.
+    pub fn snapshot(&self) -> StreamHist {
+        lock(&self.hist).clone()
+    }
+
+    /// The upper bounds of the buckets and the counts of the values in them (not cumulative, as expected by the
+    /// [`MetricEncoder`]), the last bucket is `+Inf` (encoded as `f64::MAX`).
+    fn bucket_counts(hist: &StreamHist, buckets: &Buckets) -> Vec<(f64, u64)> {
+        let mut bounds: Vec<f64> = match buckets {
+            Buckets::Fixed(bounds) => bounds.clone(),
+            Buckets::Quantiles(probs) if hist.is_empty() => vec![0.0; probs.len()],
+            Buckets::Quantiles(probs) => hist.quantiles(probs),
+        };
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+
+        bounds.retain(|bound| bound.is_finite() && *bound < f64::MAX);
+
+        let total = hist.count() as u64;
+        let (mut cumulative, mut prev) = (0.0, 0);
+        let counts = hist.counts_for_bounds(&bounds);
+        bounds
+            .into_iter()
+            .chain([f64::MAX])
+            .zip(counts)
+            .map(|(bound, count)| {
+                cumulative += count;
+                let rounded = if bound == f64::MAX {
+                    total
+                } else {
+                    (cumulative.round() as u64).min(total)
+                };
+                let count = rounded - prev;
+                prev = rounded;
+                (bound, count)
+            })
+            .collect()
+    }
+}
+
+impl TypedMetric for PrometheusHistogram {
+    const TYPE: MetricType = MetricType::Histogram;
+}
+
+impl EncodeMetric for PrometheusHistogram {
+    fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
+        let hist = self.snapshot();
+        let buckets = PrometheusHistogram::bucket_counts(&hist, &self.buckets);
+        let count = hist.count();
+        let sum = if hist.is_empty() {
+            0.0
+        } else {
+            hist.mean() * count
+        };
+        encoder.encode_histogram::<NoLabelSet>(sum, count as u64, &buckets, None)
+    }
+
+    fn metric_type(&self) -> MetricType {
+        Self::TYPE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Buckets, PrometheusHistogram};
+    use crate::hist::StreamHist;
+    use prometheus_client::encoding::text::encode;
+    use prometheus_client::registry::Registry;
+
+    #[test]
+    fn bucket_counts() {
+        let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(
+            PrometheusHistogram::bucket_counts(&hist, &Buckets::Fixed(vec![3.0, 0.0, 10.0])),
+            vec![(0.0, 0), (3.0, 3), (10.0, 2), (f64::MAX, 0)]
+        );
+        assert_eq!(
+            PrometheusHistogram::bucket_counts(&hist, &Buckets::Quantiles(vec![0.5, 1.0])),
+            vec![(3.0, 3), (5.0, 2), (f64::MAX, 0)]
+        );
+        assert_eq!(
+            PrometheusHistogram::bucket_counts(
+                &StreamHist::default(),
+                &Buckets::Quantiles(vec![0.5])
+            ),
+            vec![(0.0, 0), (f64::MAX, 0)]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_quantiles() {
+        PrometheusHistogram::with_capacity(10, Buckets::Quantiles(vec![0.5, 2.0]));
+    }
+
+    #[test]
+    fn encode_text() {
+        let hist = PrometheusHistogram::with_capacity(10, Buckets::Fixed(vec![2.5]));
+        let mut registry = Registry::default();
+        registry.register("values", "Some values", hist.clone());
+        for value in [1.0, 2.0, 3.0, f64::NAN] {
+            hist.observe(value);
+        }
+
+        let mut buffer = String::new();
+        encode(&mut buffer, &registry).unwrap();
+        assert_eq!(
+            buffer,
+            "# HELP values Some values.\n\
+             # TYPE values histogram\n\
+             values_sum 6.0\n\
+             values_count 3\n\
+             values_bucket{le=\"2.5\"} 2\n\
+             values_bucket{le=\"+Inf\"} 3\n\
+             # EOF\n"
+        );
+    }
+}
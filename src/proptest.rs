@@ -0,0 +1,75 @@
+//! [`proptest`] strategies generating valid histograms for property-based testing.
+//!
+//! # Examples
+//!
+//! ```
+//! use histr::StreamHist;
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(hist: StreamHist)| {
+//!     prop_assert!(hist.bins().len() <= hist.capacity());
+//!     prop_assert!(hist.is_empty() || hist.min() <= hist.max());
+//! });
+//! ```
+
+use crate::bins::Bin;
+use crate::hist::StreamHist;
+use proptest::prelude::*;
+
+/// Range of the generated values, narrow enough for the statistics not to overflow.
+const VALUES: std::ops::Range<f64> = -1e6..1e6;
+
+impl Arbitrary for Bin {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generate a bin with a finite mean and a positive count.
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (VALUES, 1..1000u64)
+            .prop_map(|(mean, count)| Bin::new(mean, count))
+            .boxed()
+    }
+}
+
+impl Arbitrary for StreamHist {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generate a histogram by inserting finite values to a histogram of a random size,
+    /// so the bins are sorted and the `min` and `max` are consistent with them.
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (1..64usize, prop::collection::vec(VALUES, 0..256))
+            .prop_map(|(size, values)| {
+                let mut hist = StreamHist::with_capacity(size);
+                values.into_iter().for_each(|value| hist.insert(value));
+                hist
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bins::Bin;
+    use crate::hist::StreamHist;
+    use crate::is_sorted;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn valid_bin(bin: Bin) {
+            prop_assert!(bin.mean.is_finite());
+            prop_assert!(bin.count > 0);
+        }
+
+        #[test]
+        fn valid_hist(hist: StreamHist) {
+            prop_assert!(hist.bins.len() <= hist.size);
+            prop_assert!(is_sorted(&hist.bins));
+            if !hist.is_empty() {
+                prop_assert!(hist.min <= hist.bins.first().unwrap().mean);
+                prop_assert!(hist.bins.last().unwrap().mean <= hist.max);
+            }
+        }
+    }
+}
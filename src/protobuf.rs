@@ -0,0 +1,114 @@
+use crate::hist::StreamHist;
+use crate::serde::HistRepr;
+use prost::Message;
+use std::error::Error;
+
+/// The message defined in the `proto/histr.proto` schema, so the histograms can be read in other languages
+/// using the code generated from it.
+#[derive(Clone, PartialEq, Message)]
+struct ProtoHist {
+    #[prost(uint32, tag = "1")]
+    version: u32,
+    #[prost(double, repeated, tag = "2")]
+    means: Vec<f64>,
+    #[prost(uint64, repeated, tag = "3")]
+    counts: Vec<u64>,
+    #[prost(double, optional, tag = "4")]
+    min: Option<f64>,
+    #[prost(double, optional, tag = "5")]
+    max: Option<f64>,
+    #[prost(uint64, tag = "6")]
+    size: u64,
+}
+
+impl StreamHist {
+    /// Serialize the histogram to the [Protocol Buffers] message.
+    ///
+    /// The schema of the message is defined in the `proto/histr.proto` file in the repository,
+    /// and it has the same fields as the format described in [`StreamHist::from_json`].
+    ///
+    /// [Protocol Buffers]: https://protobuf.dev/
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![2.0, 5.0, 1.0, 3.0, 4.0, 1.0, 2.5]);
+    /// let bytes = hist.to_proto_bytes();
+    ///
+    /// assert_eq!(StreamHist::from_proto_bytes(&bytes).unwrap(), hist);
+    /// ```
+    pub fn to_proto_bytes(&self) -> Vec<u8> {
+        let repr = HistRepr::from(self);
+        ProtoHist {
+            version: repr.version,
+            means: repr.means,
+            counts: repr.counts,
+            min: repr.min,
+            max: repr.max,
+            size: repr.size as u64,
+        }
+        .encode_to_vec()
+    }
+
+    /// Read the histogram from the [Protocol Buffers] message, see [`StreamHist::to_proto_bytes`].
+    ///
+    /// [Protocol Buffers]: https://protobuf.dev/
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the message cannot be decoded, or the histogram is not valid,
+    /// see [`StreamHist::try_from_parts`].
+    pub fn from_proto_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let proto = ProtoHist::decode(bytes)?;
+        let repr = HistRepr {
+            version: proto.version,
+            means: proto.means,
+            counts: proto.counts,
+            min: proto.min,
+            max: proto.max,
+            size: usize::try_from(proto.size)?,
+        };
+        Ok(StreamHist::try_from(repr)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProtoHist;
+    use crate::hist::StreamHist;
+    use prost::Message;
+
+    #[test]
+    fn roundtrip() {
+        for hist in [
+            StreamHist::from(vec![2.0, 5.0, 1.0, 3.0, 4.0, 1.0, 2.5]),
+            StreamHist::with_capacity(5),
+        ] {
+            let bytes = hist.to_proto_bytes();
+            assert_eq!(StreamHist::from_proto_bytes(&bytes).unwrap(), hist);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(StreamHist::from_proto_bytes(&[0xff]).is_err());
+
+        let unsorted = ProtoHist {
+            version: 1,
+            means: vec![2.0, 1.0],
+            counts: vec![1, 1],
+            min: Some(1.0),
+            max: Some(2.0),
+            size: 2,
+        };
+        assert!(StreamHist::from_proto_bytes(&unsorted.encode_to_vec()).is_err());
+
+        let no_version = ProtoHist {
+            version: 0,
+            ..unsorted
+        };
+        assert!(StreamHist::from_proto_bytes(&no_version.encode_to_vec()).is_err());
+    }
+}
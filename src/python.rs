@@ -0,0 +1,357 @@
+//! Python bindings built with [PyO3].
+//!
+//! The module exposes the `StreamHist` and `KernelDensity` classes. The values can be passed to and from Python
+//! as numpy arrays. To build the Python package, use [maturin] with the `python` feature
+//! (see `pyproject.toml`).
+//!
+//! ```python
+//! import numpy as np
+//! from histr import StreamHist, KernelDensity
+//!
+//! hist = StreamHist(10)
+//! hist.insert_array(np.random.normal(size=1000))
+//! print(hist.mean(), hist.quantile(0.99))
+//!
+//! means, counts = hist.to_numpy()
+//! kde = KernelDensity(hist)
+//! density = kde.density_array(np.linspace(-3, 3, 100))
+//! ```
+//!
+//! [PyO3]: https://pyo3.rs/
+//! [maturin]: https://www.maturin.rs/
+
+use crate::bins::Bin;
+use crate::density::KernelDensity;
+use crate::hist::StreamHist;
+use numpy::{IntoPyArray, PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Streaming histogram.
+#[pyclass(name = "StreamHist", module = "histr")]
+#[derive(Debug, Clone)]
+struct PyStreamHist {
+    hist: StreamHist,
+}
+
+#[pymethods]
+impl PyStreamHist {
+    /// Initialize an empty histogram with the number of bins equal to `size`.
+    #[new]
+    #[pyo3(signature = (size = 64))]
+    fn new(size: usize) -> Self {
+        PyStreamHist {
+            hist: StreamHist::with_capacity(size),
+        }
+    }
+
+    /// Initialize the histogram from the numpy arrays of bin means and counts. The means need to be sorted
+    /// and the counts need to be positive.
+    #[staticmethod]
+    fn from_numpy(
+        means: PyReadonlyArray1<'_, f64>,
+        counts: PyReadonlyArray1<'_, u64>,
+    ) -> PyResult<Self> {
+        let (means, counts) = (means.as_array(), counts.as_array());
+        let hist = hist_from_parts(
+            &means.iter().copied().collect::<Vec<_>>(),
+            &counts.iter().copied().collect::<Vec<_>>(),
+        )?;
+        Ok(PyStreamHist { hist })
+    }
+
+    /// Read the histogram from a JSON string.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let hist =
+            StreamHist::from_json(json).map_err(|err| PyValueError::new_err(err.to_string()))?;
+        Ok(PyStreamHist { hist })
+    }
+
+    /// Transform the histogram to a JSON string.
+    fn to_json(&self) -> String {
+        self.hist.to_json()
+    }
+
+    /// Numpy arrays of the bin means and counts.
+    fn to_numpy<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> (Bound<'py, PyArray1<f64>>, Bound<'py, PyArray1<u64>>) {
+        let (means, counts): (Vec<f64>, Vec<u64>) = self.hist.iter().map(|bin| bin.into()).unzip();
+        (means.into_pyarray(py), counts.into_pyarray(py))
+    }
+
+    /// Insert a new point to the histogram.
+    fn insert(&mut self, value: f64) -> PyResult<()> {
+        check_number(value)?;
+        self.hist.insert(value);
+        Ok(())
+    }
+
+    /// Insert all the values from the numpy array to the histogram.
+    fn insert_array(&mut self, values: PyReadonlyArray1<'_, f64>) -> PyResult<()> {
+        let values = values.as_array();
+        for value in values.iter() {
+            check_number(*value)?;
+        }
+        for value in values.iter() {
+            self.hist.insert(*value);
+        }
+        Ok(())
+    }
+
+    /// Merge the other histogram into this one.
+    fn merge(&mut self, other: &PyStreamHist) {
+        self.hist.merge(other.hist.clone());
+    }
+
+    /// Adjust the number of bins in histogram.
+    fn resize(&mut self, size: usize) {
+        self.hist.resize(size);
+    }
+
+    /// Upper bound for the number of bins.
+    #[getter]
+    fn size(&self) -> usize {
+        self.hist.size
+    }
+
+    /// Smallest observed value.
+    #[getter]
+    fn min(&self) -> f64 {
+        self.hist.min
+    }
+
+    /// Largest observed value.
+    #[getter]
+    fn max(&self) -> f64 {
+        self.hist.max
+    }
+
+    /// The total count of all the values used to create the histogram.
+    fn count(&self) -> f64 {
+        self.hist.count()
+    }
+
+    /// Approximate mean of the data.
+    fn mean(&self) -> f64 {
+        self.hist.mean()
+    }
+
+    /// Approximate variance of the data.
+    fn variance(&self) -> f64 {
+        self.hist.variance()
+    }
+
+    /// Standard deviation of the data.
+    fn stdev(&self) -> f64 {
+        self.hist.stdev()
+    }
+
+    /// Approximate median of the data.
+    fn median(&self) -> f64 {
+        self.hist.median()
+    }
+
+    /// Approximate sample quantile of the data for a given probability `prob`.
+    fn quantile(&self, prob: f64) -> PyResult<f64> {
+        check_probability(prob)?;
+        Ok(self.hist.quantile(prob))
+    }
+
+    /// Approximate empirical cumulative distribution function of the data for a given `value`.
+    fn cdf(&self, value: f64) -> f64 {
+        self.hist.cdf(value)
+    }
+
+    /// The number of bins.
+    fn __len__(&self) -> usize {
+        self.hist.bins.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "StreamHist(size={}, bins={}, count={})",
+            self.hist.size,
+            self.hist.bins.len(),
+            self.hist.count()
+        )
+    }
+}
+
+/// Weighted kernel density estimator for the histogram.
+#[pyclass(name = "KernelDensity", module = "histr")]
+#[derive(Debug, Clone)]
+struct PyKernelDensity {
+    kde: KernelDensity,
+}
+
+#[pymethods]
+impl PyKernelDensity {
+    /// Initialize kernel density estimator from the streaming histogram.
+    #[new]
+    fn new(hist: &PyStreamHist) -> Self {
+        PyKernelDensity {
+            kde: KernelDensity::from(hist.hist.clone()),
+        }
+    }
+
+    /// Bandwidth of the kernels in the kernel density estimator.
+    #[getter]
+    fn get_bandwidth(&self) -> f64 {
+        self.kde.bandwidth
+    }
+
+    #[setter]
+    fn set_bandwidth(&mut self, bandwidth: f64) -> PyResult<()> {
+        if !bandwidth.is_finite() || bandwidth <= 0.0 {
+            return Err(PyValueError::new_err(format!(
+                "{bandwidth} is not a valid bandwidth"
+            )));
+        }
+        self.kde.bandwidth = bandwidth;
+        Ok(())
+    }
+
+    /// Evaluate weighted kernel density estimator at the `value`.
+    fn density(&self, value: f64) -> f64 {
+        self.kde.density(value)
+    }
+
+    /// Evaluate weighted kernel density estimator at all the values from the numpy array.
+    fn density_array<'py>(
+        &self,
+        py: Python<'py>,
+        values: PyReadonlyArray1<'py, f64>,
+    ) -> Bound<'py, PyArray1<f64>> {
+        let densities: Vec<f64> = values
+            .as_array()
+            .iter()
+            .map(|value| self.kde.density(*value))
+            .collect();
+        densities.into_pyarray(py)
+    }
+}
+
+/// Build the histogram from the bins, validated as in [`StreamHist::try_from_parts`].
+fn hist_from_parts(means: &[f64], counts: &[u64]) -> PyResult<StreamHist> {
+    if means.len() != counts.len() {
+        return Err(PyValueError::new_err(
+            "means and counts need to have the same length",
+        ));
+    }
+    let mut bins = Vec::with_capacity(means.len());
+    for (mean, count) in means.iter().zip(counts) {
+        check_number(*mean)?;
+        bins.push(Bin::new(*mean, *count));
+    }
+    let (Some(first), Some(last)) = (bins.first(), bins.last()) else {
+        return Ok(StreamHist::default());
+    };
+    let (min, max, size) = (first.mean, last.mean, bins.len());
+    StreamHist::try_from_parts(bins, min, max, size)
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+fn check_number(value: f64) -> PyResult<()> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!("{value} is not a number")))
+    }
+}
+
+fn check_probability(prob: f64) -> PyResult<()> {
+    if (0.0..=1.0).contains(&prob) {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!(
+            "{prob} is not a valid probability"
+        )))
+    }
+}
+
+/// Streaming histograms.
+#[pymodule]
+fn histr(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyStreamHist>()?;
+    m.add_class::<PyKernelDensity>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hist_from_parts, histr, PyKernelDensity, PyStreamHist};
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+
+    #[test]
+    fn from_parts() {
+        let hist = hist_from_parts(&[1.0, 2.0, 5.0], &[2, 1, 3]).unwrap();
+        assert_eq!(hist.count(), 6.0);
+        assert_eq!(hist.min(), 1.0);
+        assert_eq!(hist.max(), 5.0);
+        assert_eq!(hist_from_parts(&[], &[]).unwrap().count(), 0.0);
+
+        assert!(hist_from_parts(&[1.0, 2.0], &[1]).is_err());
+        assert!(hist_from_parts(&[2.0, 1.0], &[1, 1]).is_err());
+        assert!(hist_from_parts(&[1.0, f64::NAN], &[1, 1]).is_err());
+        assert!(hist_from_parts(&[1.0, f64::INFINITY], &[1, 1]).is_err());
+        assert!(hist_from_parts(&[1.0, 2.0], &[1, 0]).is_err());
+    }
+
+    #[test]
+    fn wrappers() {
+        let mut hist = PyStreamHist::new(10);
+        for i in 0..100 {
+            hist.insert(i as f64).unwrap();
+        }
+        assert!(hist.insert(f64::NAN).is_err());
+        assert_eq!(hist.count(), 100.0);
+        assert_eq!(hist.__len__(), 10);
+        assert!(hist.quantile(2.0).is_err());
+        assert_eq!(
+            PyStreamHist::from_json(&hist.to_json()).unwrap().hist,
+            hist.hist
+        );
+
+        let mut kde = PyKernelDensity::new(&hist);
+        kde.set_bandwidth(2.0).unwrap();
+        assert_eq!(kde.get_bandwidth(), 2.0);
+        for bandwidth in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(kde.set_bandwidth(bandwidth).is_err());
+        }
+        assert_eq!(kde.get_bandwidth(), 2.0);
+    }
+
+    #[test]
+    fn module() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "histr").unwrap();
+            histr(&module).unwrap();
+            let locals = PyDict::new(py);
+            locals.set_item("histr", module).unwrap();
+            py.run(
+                c"
+hist = histr.StreamHist(10)
+for i in range(100):
+    hist.insert(float(i))
+assert hist.count() == 100.0
+assert len(hist) == 10
+assert histr.StreamHist.from_json(hist.to_json()).count() == 100.0
+kde = histr.KernelDensity(hist)
+try:
+    kde.bandwidth = -1.0
+    raise AssertionError('the bandwidth was not validated')
+except ValueError:
+    pass
+",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+    }
+}
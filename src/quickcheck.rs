@@ -0,0 +1,120 @@
+//! [`quickcheck`] generators of valid histograms for property-based testing.
+//!
+//! # Examples
+//!
+//! ```
+//! use histr::StreamHist;
+//! use quickcheck::quickcheck;
+//!
+//! fn bins_fit(hist: StreamHist) -> bool {
+//!     hist.bins().len() <= hist.capacity()
+//! }
+//! quickcheck(bins_fit as fn(StreamHist) -> bool);
+//! ```
+
+use crate::bins::Bin;
+use crate::hist::StreamHist;
+use quickcheck::{Arbitrary, Gen};
+
+/// Largest absolute value of the generated values, small enough for the statistics not to overflow.
+const MAX_ABS_VALUE: f64 = 1e6;
+
+/// Generate a finite value within the [`MAX_ABS_VALUE`] range.
+fn value(g: &mut Gen) -> f64 {
+    let value = f64::arbitrary(g);
+    if value.is_finite() {
+        value % MAX_ABS_VALUE
+    } else {
+        0.0
+    }
+}
+
+impl Arbitrary for Bin {
+    /// Generate a bin with a finite mean and a positive count.
+    fn arbitrary(g: &mut Gen) -> Self {
+        Bin::new(value(g), u64::arbitrary(g) % 1000 + 1)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let count = self.count;
+        Box::new(
+            (self.mean, self.count - 1)
+                .shrink()
+                .filter(|(mean, _)| mean.is_finite())
+                .map(move |(mean, c)| Bin::new(mean, c.min(count - 1) + 1)),
+        )
+    }
+}
+
+impl Arbitrary for StreamHist {
+    /// Generate a histogram by inserting finite values to a histogram of a random size,
+    /// so the bins are sorted and the `min` and `max` are consistent with them.
+    fn arbitrary(g: &mut Gen) -> Self {
+        // the size of the generator can be zero
+        let max_size = g.size().max(1);
+        let size = usize::arbitrary(g) % max_size + 1;
+        let mut hist = StreamHist::with_capacity(size);
+        for _ in 0..usize::arbitrary(g) % (4 * max_size) {
+            hist.insert(value(g));
+        }
+        hist
+    }
+
+    /// Shrink by merging the bins into the smaller histograms, and finally to an empty one.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        if self.is_empty() {
+            return quickcheck::empty_shrinker();
+        }
+        let hist = self.clone();
+        let empty = StreamHist::with_capacity(self.size);
+        Box::new(
+            (1..self.bins.len())
+                .rev()
+                .map(move |size| {
+                    let mut hist = hist.clone();
+                    hist.resize(size);
+                    hist
+                })
+                .chain(std::iter::once(empty)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bins::Bin;
+    use crate::hist::StreamHist;
+    use crate::is_sorted;
+    use quickcheck::{quickcheck, Arbitrary, Gen};
+
+    quickcheck! {
+        fn valid_bin(bin: Bin) -> bool {
+            bin.mean.is_finite() && bin.count > 0
+        }
+
+        fn valid_hist(hist: StreamHist) -> bool {
+            hist.bins.len() <= hist.size
+                && is_sorted(&hist.bins)
+                && (hist.is_empty()
+                    || hist.min <= hist.bins.first().unwrap().mean
+                        && hist.bins.last().unwrap().mean <= hist.max)
+        }
+    }
+
+    #[test]
+    fn zero_size_generator() {
+        let hist = StreamHist::arbitrary(&mut Gen::new(0));
+        assert!(hist.capacity() >= 1);
+    }
+
+    #[test]
+    fn shrink_is_valid() {
+        let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        let shrunk: Vec<StreamHist> = hist.shrink().collect();
+        assert_eq!(shrunk.len(), 5);
+        assert!(shrunk.iter().all(|h| h.count() == 5.0 || h.is_empty()));
+
+        let bin = Bin::new(3.5, 10);
+        assert!(bin.shrink().all(|b| b.mean.is_finite() && b.count > 0));
+    }
+}
@@ -0,0 +1,150 @@
+//! Integration with the [`metrics`] crate.
+//!
+//! [`StreamHistRecorder`] is a [`metrics::Recorder`] that feeds the values recorded with the
+//! [`metrics::histogram!`] macro to [`StreamHist`]s, one histogram per metric key (name and labels).
+//! Counters and gauges are ignored. The histograms can be read using the [`StreamHistHandle`].
+//!
+//! # Examples
+//!
+//! ```
+//! use histr::recorder::StreamHistRecorder;
+//!
+//! let recorder = StreamHistRecorder::with_capacity(10);
+//! let handle = recorder.handle();
+//!
+//! metrics::with_local_recorder(&recorder, || {
+//!     metrics::histogram!("latency").record(1.5);
+//!     metrics::histogram!("latency").record(2.5);
+//! });
+//!
+//! let snapshot = handle.snapshot();
+//! let hist = &snapshot[&metrics::Key::from_name("latency")];
+//! assert_eq!(hist.count(), 2.0);
+//! assert_eq!(hist.mean(), 2.0);
+//! ```
+
+use crate::hist::StreamHist;
+use crate::lock;
+use metrics::{
+    Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// [`metrics::Recorder`] recording the histograms as [`StreamHist`]s.
+#[derive(Debug, Clone)]
+pub struct StreamHistRecorder {
+    size: usize,
+    handle: StreamHistHandle,
+}
+
+impl StreamHistRecorder {
+    /// Initialize the recorder, each of the recorded histograms will have the number of bins equal to `size`.
+    pub fn with_capacity(size: usize) -> Self {
+        StreamHistRecorder {
+            size,
+            handle: StreamHistHandle::default(),
+        }
+    }
+
+    /// Handle for reading the recorded histograms.
+    pub fn handle(&self) -> StreamHistHandle {
+        self.handle.clone()
+    }
+
+    /// Install the recorder as the global recorder and return the handle for reading the histograms.
+    pub fn install(self) -> Result<StreamHistHandle, metrics::SetRecorderError<Self>> {
+        let handle = self.handle();
+        metrics::set_global_recorder(self)?;
+        Ok(handle)
+    }
+}
+
+impl Recorder for StreamHistRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, _: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::noop()
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        let hist = lock(&self.handle.hists)
+            .entry(key.clone())
+            .or_insert_with(|| {
+                Arc::new(SharedHist(Mutex::new(StreamHist::with_capacity(self.size))))
+            })
+            .clone();
+        Histogram::from_arc(hist)
+    }
+}
+
+/// Handle for reading the histograms recorded by the [`StreamHistRecorder`].
+#[derive(Debug, Clone, Default)]
+pub struct StreamHistHandle {
+    hists: Arc<Mutex<HashMap<Key, Arc<SharedHist>>>>,
+}
+
+impl StreamHistHandle {
+    /// Copies of all the recorded histograms.
+    pub fn snapshot(&self) -> HashMap<Key, StreamHist> {
+        lock(&self.hists)
+            .iter()
+            .map(|(key, hist)| (key.clone(), lock(&hist.0).clone()))
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+struct SharedHist(Mutex<StreamHist>);
+
+impl HistogramFn for SharedHist {
+    /// Insert the value to the histogram, the values that are not numbers are ignored.
+    fn record(&self, value: f64) {
+        if value.is_finite() {
+            lock(&self.0).insert(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamHistRecorder;
+    use crate::hist::StreamHist;
+    use metrics::{Key, Label};
+
+    #[test]
+    fn record() {
+        let recorder = StreamHistRecorder::with_capacity(3);
+        let handle = recorder.handle();
+
+        metrics::with_local_recorder(&recorder, || {
+            for i in 1..=5 {
+                metrics::histogram!("latency", "endpoint" => "a").record(i as f64);
+            }
+            metrics::histogram!("latency", "endpoint" => "b").record(10.0);
+            metrics::histogram!("latency", "endpoint" => "b").record(f64::NAN);
+            metrics::counter!("requests").increment(1);
+        });
+
+        let snapshot = handle.snapshot();
+        assert_eq!(snapshot.len(), 2);
+
+        let mut expected = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        expected.resize(3);
+        let key = Key::from_parts("latency", vec![Label::new("endpoint", "a")]);
+        assert_eq!(snapshot[&key], expected);
+
+        let mut expected = StreamHist::from(vec![10.0]);
+        expected.resize(3);
+        let key = Key::from_parts("latency", vec![Label::new("endpoint", "b")]);
+        assert_eq!(snapshot[&key], expected);
+    }
+}
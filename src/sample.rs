@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+/// Values that can be inserted to the [`StreamHist`](crate::StreamHist) histogram.
+///
+/// The numeric types are converted to `f64` using the `as` casts, so the large integers
+/// lose precision. The [`Duration`]s are converted to seconds.
+///
+/// # Examples
+///
+/// ```
+/// use histr::StreamHist;
+/// use std::time::{Duration, Instant};
+///
+/// let mut hist = StreamHist::with_capacity(10);
+/// let start = Instant::now();
+/// // ...
+/// hist.insert(start.elapsed());
+/// hist.insert(Duration::from_millis(1500));
+/// hist.insert(3_u64);
+///
+/// assert_eq!(hist.count(), 3.0);
+/// assert_eq!(hist.max(), 3.0);
+/// ```
+pub trait IntoSample {
+    /// Convert the value to `f64`.
+    fn into_sample(self) -> f64;
+}
+
+macro_rules! impl_into_sample {
+    ($($t:ty),*) => {
+        $(
+            impl IntoSample for $t {
+                #[inline]
+                fn into_sample(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_into_sample!(f64, f32, i32, i64, u32, u64, usize);
+
+impl IntoSample for Duration {
+    /// The duration in seconds.
+    #[inline]
+    fn into_sample(self) -> f64 {
+        self.as_secs_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntoSample;
+    use std::time::Duration;
+
+    #[test]
+    fn into_sample() {
+        assert_eq!(1.5_f64.into_sample(), 1.5);
+        assert_eq!(1.5_f32.into_sample(), 1.5);
+        assert_eq!((-3_i64).into_sample(), -3.0);
+        assert_eq!(3_u64.into_sample(), 3.0);
+        assert_eq!(Duration::from_millis(2500).into_sample(), 2.5);
+        assert_eq!(Duration::from_nanos(1).into_sample(), 1e-9);
+    }
+}
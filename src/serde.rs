@@ -1,27 +1,91 @@
 extern crate serde;
 
 use crate::bins::Bin;
+use crate::error::Error as HistError;
+use crate::fingerprint::Fnv1a;
 use crate::hist::StreamHist;
-use serde::{Deserialize, Serialize};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::error::Error;
 use std::io::{Read, Write};
 use std::iter::zip;
 
+/// Version of the serialization format, stored with the serialized histograms.
+const FORMAT_VERSION: u32 = 1;
+
+/// The serialization format of the [`StreamHist`], shared by JSON, MessagePack, and other serde formats.
+///
+/// The `min` and `max` are optional, since JSON does not support `NaN`s used for the empty histograms.
 // See: https://rust-by-example-ext.com/serde/json.html
 #[derive(Serialize, Deserialize, Debug)]
-struct HistJson {
+pub(crate) struct HistRepr {
+    pub(crate) version: u32,
+    pub(crate) means: Vec<f64>,
+    pub(crate) counts: Vec<u64>,
+    pub(crate) min: Option<f64>,
+    pub(crate) max: Option<f64>,
+    pub(crate) size: usize,
+}
+
+/// The JSON format used before the [`HistRepr`], without the version and the size.
+#[derive(Deserialize, Debug)]
+struct LegacyJson {
     means: Vec<f64>,
     counts: Vec<u64>,
     min: Option<f64>,
     max: Option<f64>,
 }
 
+/// The fields of the [`StreamHist`], as they were serialized to MessagePack before the [`HistRepr`].
+#[derive(Serialize, Deserialize, Debug)]
+struct LegacyParts {
+    bins: Vec<Bin>,
+    min: f64,
+    max: f64,
+    size: usize,
+}
+
+/// Any of the supported serialization formats, the current one is tried first.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum AnyFormat {
+    Current(HistRepr),
+    LegacyJson(LegacyJson),
+    LegacyParts(LegacyParts),
+}
+
+impl Serialize for StreamHist {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        HistRepr::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for StreamHist {
+    /// Deserialize the histogram, validating it as in [`StreamHist::try_from_parts`].
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hist = match AnyFormat::deserialize(deserializer)? {
+            AnyFormat::Current(repr) => StreamHist::try_from(repr),
+            AnyFormat::LegacyJson(json) => StreamHist::try_from(json),
+            AnyFormat::LegacyParts(parts) => {
+                StreamHist::try_from_parts(parts.bins, parts.min, parts.max, parts.size)
+            }
+        };
+        hist.map_err(D::Error::custom)
+    }
+}
+
 impl StreamHist {
     /// Read the histogram from a JSON string.
     ///
-    /// The JSON needs to contain two numeric arrays for `"means"` and `"counts"`, and optional fields for
-    /// `min` and `max` (can be `null` as in the example in [`StreamHist::to_json`]).
-    /// When `min` and `max` are not given, they are set to smallest and largest bin means respectively.
+    /// The JSON contains the format `"version"`, two numeric arrays for the `"means"` and `"counts"` of the sorted
+    /// bins, the `"min"` and `"max"` (can be `null` as in the example in [`StreamHist::to_json`]), and the `"size"`.
+    /// When `min` and `max` are not given, they are set to smallest and largest bin means respectively. The same
+    /// format is used for all the serde formats, e.g. MessagePack. The histogram is validated the same as in
+    /// [`StreamHist::try_from_parts`].
+    ///
+    /// The JSONs written by the older versions of the library, without the `"version"` and `"size"` fields,
+    /// are also accepted. In such a case, the bins do not need to be sorted, and the size is equal to the number
+    /// of the bins.
     ///
     /// # Examples
     ///
@@ -32,16 +96,41 @@ impl StreamHist {
     /// assert_eq!(
     ///     StreamHist::from_json(
     ///         r#"{
+    ///             "version": 1,
+    ///             "means":  [1,2,3],
+    ///             "counts": [3,4,2],
+    ///             "min": 0.5,
+    ///             "max": 3,
+    ///             "size": 5
+    ///         }"#
+    ///     )
+    ///     .unwrap(),
+    ///     StreamHist::try_from_parts(vec![Bin::new(1.0, 3), Bin::new(2.0, 4), Bin::new(3.0, 2)], 0.5, 3.0, 5)
+    ///         .unwrap()
+    /// );
+    ///
+    /// // the legacy format
+    /// assert_eq!(
+    ///     StreamHist::from_json(
+    ///         r#"{
     ///             "means":  [3,1,2],
     ///             "counts": [2,3,4]
     ///         }"#
-    ///     ),
+    ///     )
+    ///     .unwrap(),
     ///     StreamHist::from(vec![Bin::new(1.0, 3), Bin::new(2.0, 4), Bin::new(3.0, 2)])
     /// );
+    ///
+    /// // the counts are missing
+    /// assert!(StreamHist::from_json(r#"{"means": [1,2], "counts": [3]}"#).is_err());
     /// ```
-    pub fn from_json(json: &str) -> Self {
-        let h: HistJson = serde_json::from_str(json).unwrap();
-        StreamHist::from(h)
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the JSON is malformed, does not match the format, or the histogram is not valid,
+    /// e.g. the `means` and `counts` have different lengths or any of the counts is zero.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
     }
 
     /// Transform the histogram to a JSON string.
@@ -54,27 +143,37 @@ impl StreamHist {
     /// use histr::StreamHist;
     ///
     /// let hist = StreamHist::default();
-    /// assert_eq!(hist.to_json(), r#"{"means":[],"counts":[],"min":null,"max":null}"#);
+    /// assert_eq!(
+    ///     hist.to_json(),
+    ///     r#"{"version":1,"means":[],"counts":[],"min":null,"max":null,"size":0}"#
+    /// );
     /// ```
     pub fn to_json(&self) -> String {
-        let h = HistJson::from(self);
-        serde_json::to_string(&h).unwrap()
+        serde_json::to_string(self).unwrap()
     }
 
     /// Read histogram from JSON using a reader.
     ///
     /// See [`StreamHist::from_json`] for more details.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err)
+    )]
     pub fn read_json<R>(reader: R) -> Result<Self, Box<dyn Error>>
     where
         R: Read,
     {
-        let json: HistJson = serde_json::from_reader(reader).map_err(Box::new)?;
-        Ok(StreamHist::from(json))
+        let hist = serde_json::from_reader(reader).map_err(Box::new)?;
+        Ok(hist)
     }
 
     /// Write histogram to JSON using a writer.
     ///
     /// See [`StreamHist::from_json`] for more details.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(bins = self.bins.len()))
+    )]
     pub fn write_json<W>(&self, writer: &mut W) -> Result<(), Box<dyn Error>>
     where
         W: Write,
@@ -87,6 +186,9 @@ impl StreamHist {
     ///
     /// [MessagePack]: https://msgpack.org/
     ///
+    /// If the data was written with [`StreamHist::write_msgpack_with_checksum`], the checksum is verified,
+    /// and it returns an error when it does not match the content.
+    ///
     /// # Examples
     /// ```
     /// extern crate tempdir;
@@ -110,17 +212,35 @@ impl StreamHist {
     ///
     /// assert_eq!(orig_hist, read_hist);
     /// ```
-    pub fn read_msgpack<R>(reader: R) -> Result<Self, Box<dyn Error>>
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err)
+    )]
+    pub fn read_msgpack<R>(mut reader: R) -> Result<Self, Box<dyn Error>>
     where
         R: Read,
     {
-        let hist = rmp_serde::decode::from_read(reader).map_err(Box::new)?;
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+        if let Ok((hist, checksum)) = rmp_serde::from_slice::<(StreamHist, u64)>(&buffer) {
+            if hist.msgpack_checksum()? != checksum && hist.legacy_msgpack_checksum()? != checksum {
+                return Err(
+                    "the histogram does not match its checksum, the data is corrupted".into(),
+                );
+            }
+            return Ok(hist);
+        }
+        let hist = rmp_serde::from_slice(&buffer).map_err(Box::new)?;
         Ok(hist)
     }
 
     /// Write histogram to [MessagePack] format using a writer.
     ///
     /// [MessagePack]: https://msgpack.org/
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(bins = self.bins.len()))
+    )]
     pub fn write_msgpack<W>(&self, writer: &mut W) -> Result<(), Box<dyn Error>>
     where
         W: Write,
@@ -128,40 +248,129 @@ impl StreamHist {
         rmp_serde::encode::write(writer, self).map_err(Box::new)?;
         Ok(())
     }
-}
 
-impl From<HistJson> for StreamHist {
-    fn from(h: HistJson) -> Self {
-        let mut bins: Vec<Bin> = zip(h.means, h.counts)
-            .map(|(m, c)| Bin::new(m, c))
-            .collect();
-        bins.sort();
-        let mut hist = StreamHist::from(bins);
-        if let Some(min) = h.min {
-            hist.min = min;
-        }
-        if let Some(max) = h.max {
-            hist.max = max;
-        }
-        hist
+    /// Write histogram to [MessagePack] format together with its checksum using a writer.
+    ///
+    /// The checksum is verified by [`StreamHist::read_msgpack`], so the corruption of the stored
+    /// histograms is reported as an error rather than being silently ignored.
+    ///
+    /// [MessagePack]: https://msgpack.org/
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![2.0, 5.0, 1.0, 3.0, 4.0, 1.0, 2.5]);
+    /// let mut buffer = Vec::new();
+    /// hist.write_msgpack_with_checksum(&mut buffer).unwrap();
+    /// assert_eq!(StreamHist::read_msgpack(buffer.as_slice()).unwrap(), hist);
+    ///
+    /// // corrupt the data
+    /// buffer[5] ^= 0xff;
+    /// assert!(StreamHist::read_msgpack(buffer.as_slice()).is_err());
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(bins = self.bins.len()))
+    )]
+    pub fn write_msgpack_with_checksum<W>(&self, writer: &mut W) -> Result<(), Box<dyn Error>>
+    where
+        W: Write,
+    {
+        let checksum = self.msgpack_checksum()?;
+        rmp_serde::encode::write(writer, &(self, checksum)).map_err(Box::new)?;
+        Ok(())
+    }
+
+    /// Checksum of the histogram serialized to MessagePack.
+    fn msgpack_checksum(&self) -> Result<u64, Box<dyn Error>> {
+        let mut hasher = Fnv1a::default();
+        hasher.write(&rmp_serde::to_vec(self)?);
+        Ok(hasher.finish())
+    }
+
+    /// Checksum of the histogram serialized to the MessagePack format used by the older versions of the library.
+    fn legacy_msgpack_checksum(&self) -> Result<u64, Box<dyn Error>> {
+        let parts = LegacyParts {
+            bins: self.bins.clone(),
+            min: self.min,
+            max: self.max,
+            size: self.size,
+        };
+        let mut hasher = Fnv1a::default();
+        hasher.write(&rmp_serde::to_vec(&parts)?);
+        Ok(hasher.finish())
     }
 }
 
-impl From<&StreamHist> for HistJson {
+impl From<&StreamHist> for HistRepr {
     fn from(h: &StreamHist) -> Self {
         let (means, counts) = h.iter().map(|bin| bin.into()).unzip();
-        HistJson {
+        HistRepr {
+            version: FORMAT_VERSION,
             means,
             counts,
             min: if h.min.is_nan() { None } else { Some(h.min) },
             max: if h.max.is_nan() { None } else { Some(h.max) },
+            size: h.size,
         }
     }
 }
 
+impl TryFrom<HistRepr> for StreamHist {
+    type Error = HistError;
+
+    fn try_from(h: HistRepr) -> Result<Self, Self::Error> {
+        if h.version != FORMAT_VERSION {
+            return Err(HistError::InvalidParts("unsupported format version"));
+        }
+        let bins = to_bins(h.means, h.counts)?;
+        let (min, max) = min_max(&bins, h.min, h.max);
+        StreamHist::try_from_parts(bins, min, max, h.size)
+    }
+}
+
+impl TryFrom<LegacyJson> for StreamHist {
+    type Error = HistError;
+
+    /// The legacy format did not require the bins to be sorted, and the size was equal to the number of bins.
+    fn try_from(h: LegacyJson) -> Result<Self, Self::Error> {
+        let mut bins = to_bins(h.means, h.counts)?;
+        bins.sort();
+        let (min, max) = min_max(&bins, h.min, h.max);
+        let size = bins.len();
+        StreamHist::try_from_parts(bins, min, max, size)
+    }
+}
+
+/// Zip the `means` and `counts` to bins, validating the means.
+fn to_bins(means: Vec<f64>, counts: Vec<u64>) -> Result<Vec<Bin>, HistError> {
+    if means.len() != counts.len() {
+        return Err(HistError::InvalidParts(
+            "the means and counts have different lengths",
+        ));
+    }
+    if means.iter().any(|mean| !mean.is_finite()) {
+        return Err(HistError::InvalidParts(
+            "the means of the bins are not numbers",
+        ));
+    }
+    Ok(zip(means, counts).map(|(m, c)| Bin::new(m, c)).collect())
+}
+
+/// The `min` and `max`, or the smallest and largest means of the sorted `bins` when they are not given.
+fn min_max(bins: &[Bin], min: Option<f64>, max: Option<f64>) -> (f64, f64) {
+    (
+        min.or(bins.first().map(|bin| bin.mean)).unwrap_or(f64::NAN),
+        max.or(bins.last().map(|bin| bin.mean)).unwrap_or(f64::NAN),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     extern crate tempdir;
+    use super::LegacyParts;
     use crate::bins::Bin;
     use crate::hist::StreamHist;
     use std::fs::File;
@@ -170,11 +379,12 @@ mod tests {
     #[test]
     fn from_json() {
         assert_eq!(
-            StreamHist::from_json("{\"means\":[],\"counts\":[]}"),
+            StreamHist::from_json("{\"means\":[],\"counts\":[]}").unwrap(),
             StreamHist::default()
         );
         assert_eq!(
-            StreamHist::from_json("{\"means\":[],\"counts\":[],\"min\":null,\"max\":null}"),
+            StreamHist::from_json("{\"means\":[],\"counts\":[],\"min\":null,\"max\":null}")
+                .unwrap(),
             StreamHist::default()
         );
 
@@ -184,7 +394,8 @@ mod tests {
                     \"means\":  [3,1,2],
                     \"counts\": [2,3,4]
                 }"
-            ),
+            )
+            .unwrap(),
             StreamHist::from(vec![Bin::new(1.0, 3), Bin::new(2.0, 4), Bin::new(3.0, 2)])
         );
 
@@ -196,28 +407,117 @@ mod tests {
                     \"min\": 0,
                     \"max\": 5
                 }"
-            ),
-            StreamHist {
-                bins: vec![Bin::new(1.0, 3), Bin::new(2.0, 4), Bin::new(3.0, 2)],
-                min: 0.0,
-                max: 5.0,
-                size: 3,
-            }
+            )
+            .unwrap(),
+            StreamHist::from_parts(
+                vec![Bin::new(1.0, 3), Bin::new(2.0, 4), Bin::new(3.0, 2)],
+                0.0,
+                5.0,
+                3
+            )
         );
+
+        assert!(StreamHist::from_json("").is_err());
+        assert!(StreamHist::from_json("{\"means\":[1,2]}").is_err());
+        assert!(StreamHist::from_json("{\"means\":[1,2],\"counts\":[1]}").is_err());
+        assert!(StreamHist::from_json("{\"means\":[1,2],\"counts\":[1,0]}").is_err());
+        assert!(StreamHist::from_json("{\"means\":[1,\"a\"],\"counts\":[1,1]}").is_err());
     }
 
     #[test]
     fn to_json() {
         assert_eq!(
             StreamHist::with_capacity(5).to_json(),
-            "{\"means\":[],\"counts\":[],\"min\":null,\"max\":null}"
+            "{\"version\":1,\"means\":[],\"counts\":[],\"min\":null,\"max\":null,\"size\":5}"
         );
         assert_eq!(
             StreamHist::from(vec![Bin::new(1.0, 3), Bin::new(2.0, 4), Bin::new(3.0, 2)]).to_json(),
-            String::from("{\"means\":[1.0,2.0,3.0],\"counts\":[3,4,2],\"min\":1.0,\"max\":3.0}")
+            String::from(
+                "{\"version\":1,\"means\":[1.0,2.0,3.0],\"counts\":[3,4,2],\"min\":1.0,\"max\":3.0,\"size\":3}"
+            )
         );
     }
 
+    #[test]
+    fn validation() {
+        for json in [
+            // unsorted
+            r#"{"version":1,"means":[2,1],"counts":[1,1],"min":1,"max":2,"size":2}"#,
+            // different lengths
+            r#"{"version":1,"means":[1,2],"counts":[1],"min":1,"max":2,"size":2}"#,
+            // zero count
+            r#"{"version":1,"means":[1,2],"counts":[1,0],"min":1,"max":2,"size":2}"#,
+            // more bins than size
+            r#"{"version":1,"means":[1,2],"counts":[1,1],"min":1,"max":2,"size":1}"#,
+            // outside of min and max
+            r#"{"version":1,"means":[1,2],"counts":[1,1],"min":1.5,"max":2,"size":2}"#,
+            // unknown version
+            r#"{"version":2,"means":[],"counts":[],"min":null,"max":null,"size":2}"#,
+            // legacy formats
+            r#"{"means":[1,2],"counts":[1],"min":1,"max":2}"#,
+            r#"{"bins":[{"mean":1.0,"count":1}],"min":1.0,"max":1.0,"size":0}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<StreamHist>(json).is_err(),
+                "{json} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn huge_size() {
+        for size in [1usize << 60, usize::MAX] {
+            let json = format!(
+                r#"{{"version":1,"means":[],"counts":[],"min":null,"max":null,"size":{size}}}"#
+            );
+            let mut hist = StreamHist::from_json(&json).unwrap();
+            assert_eq!(hist.capacity(), size);
+            assert!(hist.bins.capacity() <= 1);
+            hist.insert(1.0);
+            assert!(!hist.freeze().is_empty());
+
+            let json = format!(
+                r#"{{"version":1,"means":[1],"counts":[2],"min":1,"max":1,"size":{size}}}"#
+            );
+            assert_eq!(StreamHist::from_json(&json).unwrap().count(), 2.0);
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut hist = StreamHist::with_capacity(5);
+        for value in [2.0, 5.0, 1.0, 3.0, 4.0, 1.0, 2.5, -7.0] {
+            hist.insert(value);
+        }
+        for hist in [hist, StreamHist::with_capacity(3)] {
+            let json = serde_json::to_string(&hist).unwrap();
+            assert_eq!(serde_json::from_str::<StreamHist>(&json).unwrap(), hist);
+            let msgpack = rmp_serde::to_vec(&hist).unwrap();
+            assert_eq!(rmp_serde::from_slice::<StreamHist>(&msgpack).unwrap(), hist);
+        }
+    }
+
+    #[test]
+    fn legacy_formats() {
+        let hist = StreamHist::from_parts(vec![Bin::new(1.0, 3), Bin::new(2.5, 1)], 0.0, 3.0, 5);
+        let parts = LegacyParts {
+            bins: hist.bins.clone(),
+            min: hist.min,
+            max: hist.max,
+            size: hist.size,
+        };
+
+        let json = serde_json::to_string(&parts).unwrap();
+        assert_eq!(serde_json::from_str::<StreamHist>(&json).unwrap(), hist);
+
+        let msgpack = rmp_serde::to_vec(&parts).unwrap();
+        assert_eq!(StreamHist::read_msgpack(msgpack.as_slice()).unwrap(), hist);
+
+        let checksum = hist.legacy_msgpack_checksum().unwrap();
+        let msgpack = rmp_serde::to_vec(&(&parts, checksum)).unwrap();
+        assert_eq!(StreamHist::read_msgpack(msgpack.as_slice()).unwrap(), hist);
+    }
+
     #[test]
     fn write_read_json() {
         let temp_dir = TempDir::new("tests").unwrap();
@@ -251,4 +551,26 @@ mod tests {
             StreamHist::read_msgpack(file_to_read).expect("failed reading the file")
         );
     }
+
+    #[test]
+    fn msgpack_checksum() {
+        let hist = StreamHist::from(vec![2.0, 5.0, 1.0, 3.0, 4.0, 1.0, 2.5]);
+        let mut buffer = Vec::new();
+        hist.write_msgpack_with_checksum(&mut buffer)
+            .expect("failed writing");
+        assert_eq!(
+            hist,
+            StreamHist::read_msgpack(buffer.as_slice()).expect("failed reading")
+        );
+
+        // flipping any of the bits of the histogram is detected
+        for i in 0..buffer.len() {
+            let mut corrupted = buffer.clone();
+            corrupted[i] ^= 0x01;
+            assert!(
+                StreamHist::read_msgpack(corrupted.as_slice()).is_err(),
+                "corruption of byte {i} was not detected"
+            );
+        }
+    }
 }
@@ -0,0 +1,224 @@
+use crate::bins::Bin;
+use crate::hist::{trim_bins, weighted_mean, MergePolicy, StreamHist};
+use serde::{Deserialize, Serialize};
+
+/// Streaming histogram with signed counts, supporting the [turnstile model] where the values can be retracted.
+///
+/// The values are added using [`SignedHist::insert`] and retracted using [`SignedHist::delete`]. The deleted
+/// values do not need to match the bins exactly, a deletion creates a bin with a negative count that is merged
+/// with the closest bins, so the counts can transiently go negative. The bins are merged using the same procedure
+/// as in [`StreamHist::insert`], with the means weighted by the absolute counts, so that they stay between the
+/// means of the merged bins. The bins whose counts drop to zero are removed.
+///
+/// Use [`SignedHist::clamp_nonnegative`] to get a [`StreamHist`] for reporting.
+///
+/// [turnstile model]: https://en.wikipedia.org/wiki/Streaming_algorithm#Models
+///
+/// # Examples
+///
+/// ```
+/// use histr::SignedHist;
+///
+/// let mut hist = SignedHist::with_capacity(10);
+/// for i in 1..=10 {
+///     hist.insert(i as f64);
+/// }
+/// for i in 6..=10 {
+///     hist.delete(i as f64);
+/// }
+/// assert_eq!(hist.count(), 5);
+///
+/// let snapshot = hist.clamp_nonnegative();
+/// assert_eq!(snapshot.count(), 5.0);
+/// assert_eq!(snapshot.mean(), 3.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedHist {
+    /// Sorted `(mean, count)` pairs.
+    bins: Vec<(f64, i64)>,
+    /// Smallest inserted or deleted value.
+    min: f64,
+    /// Largest inserted or deleted value.
+    max: f64,
+    /// Upper bound for the number of bins.
+    size: usize,
+}
+
+impl SignedHist {
+    /// Initialize an empty histogram with the number of bins equal to `size`.
+    pub fn with_capacity(size: usize) -> Self {
+        SignedHist {
+            bins: Vec::with_capacity(size + 1),
+            min: f64::NAN,
+            max: f64::NAN,
+            size,
+        }
+    }
+
+    /// Insert a new point to the histogram.
+    ///
+    /// # Panics
+    ///
+    /// The `value` needs to be a number. It will panic on `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    pub fn insert(&mut self, value: f64) {
+        self.update(value, 1)
+    }
+
+    /// Retract a point from the histogram.
+    ///
+    /// # Panics
+    ///
+    /// The `value` needs to be a number. It will panic on `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    pub fn delete(&mut self, value: f64) {
+        self.update(value, -1)
+    }
+
+    fn update(&mut self, value: f64, count: i64) {
+        assert!(value.is_finite(), "{value} is not a number");
+        let value = value + 0.0;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+
+        let idx = self.bins.partition_point(|(mean, _)| *mean < value);
+        match self.bins.get_mut(idx) {
+            Some((mean, c)) if *mean == value => {
+                *c += count;
+                if *c == 0 {
+                    self.bins.remove(idx);
+                }
+            }
+            _ => {
+                self.bins.insert(idx, (value, count));
+                self.trim();
+            }
+        }
+    }
+
+    /// Trim the histogram to have size not larger than `size`, the merged bins with the counts
+    /// that cancel out are dropped.
+    fn trim(&mut self) {
+        trim_bins(
+            &mut self.bins,
+            self.size,
+            MergePolicy::default(),
+            |(mean, count)| (*mean, count.unsigned_abs() as f64),
+            |(m1, c1), (m2, c2)| {
+                let count = c1 + c2;
+                let mean = weighted_mean(
+                    (m1, c1.unsigned_abs() as f64),
+                    (m2, c2.unsigned_abs() as f64),
+                );
+                (count != 0).then_some((mean, count))
+            },
+        );
+    }
+
+    /// The net count of the values, inserted minus deleted.
+    pub fn count(&self) -> i64 {
+        self.bins.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Returns `true` if the histogram contains no data.
+    pub fn is_empty(&self) -> bool {
+        self.bins.is_empty()
+    }
+
+    /// Iterate over the `(mean, count)` pairs of the bins.
+    pub fn iter(&self) -> impl Iterator<Item = (f64, i64)> + '_ {
+        self.bins.iter().copied()
+    }
+
+    /// Convert to [`StreamHist`] by dropping the bins with negative counts.
+    ///
+    /// The `min` and `max` of the result are the smallest and largest values that were inserted
+    /// or deleted, so they bound the remaining values but are not necessarily equal to them.
+    pub fn clamp_nonnegative(&self) -> StreamHist {
+        let bins: Vec<Bin> = self
+            .bins
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(mean, count)| Bin::new(*mean, *count as u64))
+            .collect();
+        if bins.is_empty() {
+            return StreamHist::with_capacity(self.size);
+        }
+        StreamHist::from_parts(bins, self.min, self.max, self.size)
+    }
+}
+
+impl From<&StreamHist> for SignedHist {
+    fn from(hist: &StreamHist) -> Self {
+        SignedHist {
+            bins: hist
+                .iter()
+                .map(|bin| (bin.mean, bin.count as i64))
+                .collect(),
+            min: hist.min,
+            max: hist.max,
+            size: hist.size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignedHist;
+    use crate::hist::StreamHist;
+    use test_case::test_case;
+
+    #[test]
+    fn insert_delete() {
+        let mut hist = SignedHist::with_capacity(3);
+        hist.insert(1.0);
+        hist.insert(2.0);
+        hist.delete(2.0);
+        assert_eq!(hist.iter().collect::<Vec<_>>(), vec![(1.0, 1)]);
+
+        hist.delete(1.0);
+        assert!(hist.is_empty());
+        assert_eq!(hist.count(), 0);
+
+        hist.delete(5.0);
+        assert_eq!(hist.count(), -1);
+        assert!(hist.clamp_nonnegative().is_empty());
+
+        hist.insert(5.0);
+        assert!(hist.is_empty());
+    }
+
+    #[test]
+    fn merging() {
+        let mut hist = SignedHist::with_capacity(2);
+        for value in [1.0, 1.0, 1.0, 10.0] {
+            hist.insert(value);
+        }
+        hist.delete(2.0);
+        // the negative bin is merged with the closest one, the mean stays between the merged bins
+        assert_eq!(hist.iter().collect::<Vec<_>>(), vec![(1.25, 2), (10.0, 1)]);
+        assert_eq!(hist.count(), 3);
+    }
+
+    #[test]
+    fn clamp_nonnegative() {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let mut hist = SignedHist::from(&StreamHist::from(values.clone()));
+        assert_eq!(hist.clamp_nonnegative(), StreamHist::from(values));
+
+        hist.delete(5.0);
+        hist.delete(4.0);
+        hist.delete(6.0);
+        let snapshot = hist.clamp_nonnegative();
+        assert_eq!(snapshot.count(), 3.0);
+        assert_eq!(snapshot.mean(), 2.0);
+        assert_eq!(snapshot.min, 1.0);
+        assert_eq!(snapshot.max, 6.0);
+        assert_eq!(hist.count(), 2);
+    }
+
+    #[test_case(f64::NAN ; "NaN")]
+    #[test_case(f64::INFINITY ; "infinity")]
+    #[should_panic]
+    fn delete_invalid(value: f64) {
+        SignedHist::with_capacity(3).delete(value);
+    }
+}
@@ -0,0 +1,126 @@
+//! Publishing the snapshots of the histograms without locking.
+//!
+//! The [`SnapshotCell`] holds the latest published snapshot of a histogram. The writer (e.g. the ingestion
+//! thread) periodically replaces it with a fresh copy, while the readers load it without waiting for the writer
+//! or each other, so they always see a consistent, though possibly slightly outdated, histogram.
+//!
+//! # Examples
+//!
+//! ```
+//! use histr::collector::Aggregator;
+//! use histr::snapshot::SnapshotCell;
+//! use histr::StreamHist;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! let cell = Arc::new(SnapshotCell::default());
+//! let publisher = cell.clone();
+//! let (recorder, aggregator) = Aggregator::spawn(
+//!     StreamHist::with_capacity(10),
+//!     Duration::from_secs(1),
+//!     move |hist| publisher.publish(hist),
+//! );
+//!
+//! recorder.record(1.0);
+//! // readers on other threads see the latest published snapshot
+//! let snapshot = cell.load();
+//! assert!(snapshot.count() <= 1.0);
+//! # aggregator.finish();
+//! ```
+
+use crate::hist::StreamHist;
+use arc_swap::ArcSwap;
+use std::sync::Arc;
+
+/// Cell holding the latest snapshot of a histogram, readers [`load`](SnapshotCell::load) it wait-free.
+#[derive(Debug)]
+pub struct SnapshotCell {
+    snapshot: ArcSwap<StreamHist>,
+}
+
+impl SnapshotCell {
+    /// Initialize the cell with `hist` as the first snapshot.
+    pub fn new(hist: StreamHist) -> Self {
+        SnapshotCell {
+            snapshot: ArcSwap::from_pointee(hist),
+        }
+    }
+
+    /// Replace the snapshot with the `hist`.
+    ///
+    /// The readers that already loaded the previous snapshot keep it, it is freed when the last of them drops it.
+    pub fn store(&self, hist: StreamHist) {
+        self.snapshot.store(Arc::new(hist));
+    }
+
+    /// Replace the snapshot with a copy of the `hist`, see [`StreamHist::snapshot`].
+    pub fn publish(&self, hist: &StreamHist) {
+        self.store(hist.snapshot());
+    }
+
+    /// Load the latest snapshot.
+    pub fn load(&self) -> Arc<StreamHist> {
+        self.snapshot.load_full()
+    }
+}
+
+impl Default for SnapshotCell {
+    /// Initialize the cell with an empty histogram.
+    fn default() -> Self {
+        SnapshotCell::new(StreamHist::default())
+    }
+}
+
+impl From<StreamHist> for SnapshotCell {
+    fn from(hist: StreamHist) -> Self {
+        SnapshotCell::new(hist)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotCell;
+    use crate::hist::StreamHist;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn store_and_load() {
+        let cell = SnapshotCell::default();
+        assert!(cell.load().is_empty());
+
+        let old = cell.load();
+        cell.store(StreamHist::from(vec![1.0, 2.0]));
+        assert!(old.is_empty());
+        assert_eq!(cell.load().count(), 2.0);
+    }
+
+    #[test]
+    fn concurrent_readers() {
+        let cell = Arc::new(SnapshotCell::new(StreamHist::with_capacity(5)));
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let cell = cell.clone();
+                thread::spawn(move || {
+                    let mut last = 0.0;
+                    for _ in 0..1000 {
+                        let count = cell.load().count();
+                        // the snapshots are consistent and published in order
+                        assert!(count >= last);
+                        last = count;
+                    }
+                })
+            })
+            .collect();
+
+        let mut hist = StreamHist::with_capacity(5);
+        for i in 0..1000 {
+            hist.insert(i as f64);
+            cell.publish(&hist);
+        }
+        readers.into_iter().for_each(|t| t.join().unwrap());
+
+        assert_eq!(cell.load().count(), 1000.0);
+    }
+}
@@ -1,10 +1,32 @@
 use crate::bins::{sum_counts, Bin};
 use crate::hist::StreamHist;
 
+/// The number of the quantiles averaged by [`StreamHist::trimmed_mean`].
+const TRIMMED_MEAN_POINTS: usize = 1000;
+
+/// How the quantiles are interpolated between the bins, see [`StreamHist::quantile_with`].
+///
+/// All the methods find the pair of the neighboring bins using the same cumulative count as the "sum"
+/// procedure, where half of the count of each bin lies on each side of its mean, and differ only in how
+/// the value is picked between the means of the bins.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuantileInterpolation {
+    /// The "uniform" procedure described by Ben-Haim and Tom-Tov (2010), inverting the trapezoid
+    /// approximation of the counts used by [`StreamHist::count_by`], as in [`StreamHist::quantile`].
+    #[default]
+    PaperTrapezoid,
+    /// Linear interpolation of the cumulative count between the bin means, as the linear interpolation
+    /// of the sample quantiles in R (type 7) and NumPy.
+    Linear,
+    /// The midpoint between the means of the neighboring bins, as the `"midpoint"` method in NumPy.
+    Midpoint,
+}
+
 impl StreamHist {
     /// Approximate mean of the data.
     ///
     /// Calculates [weighted mean] of the bins weighting their means by the counts.
+    /// It is updated with the histogram, so calculating it does not need to iterate over the bins.
     ///
     /// [weighted mean]: https://en.wikipedia.org/wiki/Weighted_arithmetic_mean
     ///
@@ -23,14 +45,13 @@ impl StreamHist {
         if self.is_empty() {
             return f64::NAN;
         }
-        self.iter()
-            .fold(0.0, |acc, x| acc + x.mean * x.count as f64)
-            / self.count()
+        self.totals.mean
     }
 
     /// Approximate variance of the data.
     ///
     /// Calculates [weighted variance] of the bins weighting them by their counts.
+    /// It is updated with the histogram, so calculating it does not need to iterate over the bins.
     ///
     /// [weighted variance]: https://en.wikipedia.org/wiki/Weighted_arithmetic_mean#Weighted_sample_variance
     ///
@@ -49,10 +70,7 @@ impl StreamHist {
         if self.is_empty() {
             return f64::NAN;
         }
-        let m = self.mean();
-        self.iter()
-            .fold(0.0, |acc, x| acc + x.count as f64 * (x.mean - m).powi(2))
-            / self.count()
+        self.totals.m2 / self.count()
     }
 
     /// Standard deviation of the data.
@@ -62,39 +80,169 @@ impl StreamHist {
         self.variance().sqrt()
     }
 
-    /// Approximate count of the number of values since the `value`.
+    /// Approximate [winsorized mean] of the data.
     ///
-    /// It uses the "sum" procedure described by Ben-Haim and Tom-Tov (2010).
+    /// The bin means smaller than the `alpha` quantile, or larger than the `1 - alpha` quantile,
+    /// are replaced with those quantiles before calculating the [`StreamHist::mean`], so the few
+    /// extreme values in the tails do not distort it.
     ///
-    /// # NaN propagation
+    /// [winsorized mean]: https://en.wikipedia.org/wiki/Winsorized_mean
     ///
-    /// If the `value` is `f64::NAN`, it will return `f64::NAN`.
-    pub fn count_by(&self, value: f64) -> f64 {
-        if value.is_nan() {
+    /// # Panics
+    ///
+    /// `alpha` needs to be between `0.0` (inclusive) and `0.5` (exclusive), otherwise it panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let mut values: Vec<f64> = (1..100).map(|x| x as f64).collect();
+    /// values.push(1e6);
+    /// let hist = StreamHist::from(values);
+    ///
+    /// assert!(hist.mean() > 10000.0);
+    /// assert!(hist.winsorized_mean(0.05) < 100.0);
+    /// ```
+    pub fn winsorized_mean(&self, alpha: f64) -> f64 {
+        let bins = self.winsorized_bins(alpha);
+        let count = self.count();
+        bins.iter().fold(0.0, |acc, (mean, n)| acc + mean * n) / count
+    }
+
+    /// Approximate winsorized variance of the data.
+    ///
+    /// The same as the [`StreamHist::variance`] calculated after clamping the bin means as
+    /// in the [`StreamHist::winsorized_mean`].
+    ///
+    /// # Panics
+    ///
+    /// `alpha` needs to be between `0.0` (inclusive) and `0.5` (exclusive), otherwise it panics.
+    pub fn winsorized_variance(&self, alpha: f64) -> f64 {
+        let bins = self.winsorized_bins(alpha);
+        let count = self.count();
+        let m = bins.iter().fold(0.0, |acc, (mean, n)| acc + mean * n) / count;
+        bins.iter()
+            .fold(0.0, |acc, (mean, n)| acc + n * (mean - m).powi(2))
+            / count
+    }
+
+    /// Approximate [trimmed mean] of the data, the mean of the distribution restricted to the values between
+    /// the `lower` and `upper` quantiles.
+    ///
+    /// It is calculated by averaging the interpolated [`StreamHist::quantile`]s at the equally spaced
+    /// probabilities between `lower` and `upper`, so unlike the [`StreamHist::winsorized_mean`], the values
+    /// inside the bins that are partially excluded are accounted for. For `lower` equal to `0.25` and
+    /// `upper` equal to `0.75` it is the interquartile mean.
+    ///
+    /// It returns `f64::NAN` for an empty histogram.
+    ///
+    /// [trimmed mean]: https://en.wikipedia.org/wiki/Truncated_mean
+    ///
+    /// # Panics
+    ///
+    /// `lower` and `upper` need to be probability values, with `lower` smaller than `upper`, otherwise it panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let mut values: Vec<f64> = (1..100).map(|x| x as f64).collect();
+    /// values.push(1e6);
+    /// let hist = StreamHist::from(values);
+    ///
+    /// assert!(hist.mean() > 10000.0);
+    /// assert!((hist.trimmed_mean(0.05, 0.95) - 50.0).abs() < 1.0);
+    /// ```
+    pub fn trimmed_mean(&self, lower: f64, upper: f64) -> f64 {
+        assert!(
+            (0.0..=1.0).contains(&lower) && (0.0..=1.0).contains(&upper) && lower < upper,
+            "{lower} and {upper} need to be probabilities, with {lower} smaller than {upper}"
+        );
+        if self.is_empty() {
             return f64::NAN;
         }
-        if self.is_empty() || value <= self.min {
-            return 0.0;
-        }
-        if value > self.max {
-            return self.count();
+        // the midpoint rule for the integral of the quantile function
+        let step = (upper - lower) / TRIMMED_MEAN_POINTS as f64;
+        let probs: Vec<f64> = (0..TRIMMED_MEAN_POINTS)
+            .map(|i| lower + step * (i as f64 + 0.5))
+            .collect();
+        self.quantiles(&probs).iter().sum::<f64>() / TRIMMED_MEAN_POINTS as f64
+    }
+
+    /// The `(mean, count)` pairs of the bins with the means clamped to the `alpha` and `1 - alpha` quantiles.
+    fn winsorized_bins(&self, alpha: f64) -> Vec<(f64, f64)> {
+        assert!(
+            (0.0..0.5).contains(&alpha),
+            "{alpha} needs to be between 0 and 0.5"
+        );
+        if self.is_empty() {
+            return Vec::new();
         }
+        let (lower, upper) = (self.quantile(alpha), self.quantile(1.0 - alpha));
+        self.iter()
+            .map(|bin| (bin.mean.clamp(lower, upper), bin.count as f64))
+            .collect()
+    }
 
-        // Algorithm 3: Sum Procedure from Ben-Haim & Tom-Tov (2010), p. 852
-        let idx = self.partition_point(value);
-        let sum = sum_counts(&self.bins[..idx.saturating_sub(1)]) as f64;
+    /// The [standard score] of the `value`, its difference from the mean in the units of the standard deviation.
+    ///
+    /// [standard score]: https://en.wikipedia.org/wiki/Standard_score
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 3.0]);
+    /// assert_eq!(hist.z_score(4.0), 2.0);
+    /// ```
+    pub fn z_score(&self, value: f64) -> f64 {
+        (value - self.mean()) / self.stdev()
+    }
 
-        let (left, right) = self.neighbors(idx);
-        let (pi, mi) = (left.mean, left.count as f64);
-        let (pj, mj) = (right.mean, right.count as f64);
+    /// Transform the histogram to have zero mean and unit variance, by transforming the bin means,
+    /// `min`, and `max` to their [`StreamHist::z_score`]s.
+    ///
+    /// It returns `None` for an empty histogram or when its variance is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![10.0, 20.0, 30.0, 40.0]).standardize().unwrap();
+    /// assert_eq!(hist.mean(), 0.0);
+    /// assert!((hist.variance() - 1.0).abs() < 1e-12);
+    /// ```
+    pub fn standardize(&self) -> Option<StreamHist> {
+        let (mean, stdev) = (self.mean(), self.stdev());
+        if self.is_empty() || stdev == 0.0 {
+            return None;
+        }
+        let z = |value: f64| (value - mean) / stdev;
+        let bins = self
+            .iter()
+            .map(|bin| Bin::new(z(bin.mean), bin.count))
+            .collect();
+        Some(StreamHist::from_parts(
+            bins,
+            z(self.min),
+            z(self.max),
+            self.size,
+        ))
+    }
 
-        let s = if pj - pi <= 0.0 {
-            0.0
-        } else {
-            let mb = mi + (mj - mi) / (pj - pi) * (value - pi);
-            (mi + mb) / 2.0 * (value - pi) / (pj - pi)
-        };
-        sum + mi / 2.0 + s
+    /// Approximate count of the number of values since the `value`.
+    ///
+    /// It uses the "sum" procedure described by Ben-Haim and Tom-Tov (2010).
+    ///
+    /// # NaN propagation
+    ///
+    /// If the `value` is `f64::NAN`, it will return `f64::NAN`.
+    pub fn count_by(&self, value: f64) -> f64 {
+        count_by(&self.bins, self.min, self.max, self.count(), value)
     }
 
     /// Approximate empirical cumulative distribution function of the data for a given `value`.
@@ -117,6 +265,133 @@ impl StreamHist {
         self.count_by(value) / self.count()
     }
 
+    /// Approximate count of the values between `lo` and `hi`.
+    ///
+    /// It is the difference of the [`StreamHist::count_by`] counts at `hi` and `lo`, so the values
+    /// equal to `lo` are excluded, and the values equal to `hi` are included. It returns zero when
+    /// `lo` is not smaller than `hi`.
+    ///
+    /// # NaN propagation
+    ///
+    /// If `lo` or `hi` is `f64::NAN`, it will return `f64::NAN`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    /// assert_eq!(hist.count_between(0.0, 10.0), 5.0);
+    /// assert_eq!(hist.count_between(2.0, 4.0), 2.0);
+    /// assert_eq!(hist.count_between(4.0, 2.0), 0.0);
+    /// ```
+    pub fn count_between(&self, lo: f64, hi: f64) -> f64 {
+        if lo.is_nan() || hi.is_nan() {
+            return f64::NAN;
+        }
+        if lo >= hi {
+            return 0.0;
+        }
+        (self.count_by(hi) - self.count_by(lo)).max(0.0)
+    }
+
+    /// Approximate fraction of the values between `lo` and `hi`.
+    ///
+    /// The result of [`StreamHist::count_between`] divided by the total [`StreamHist::count`].
+    ///
+    /// # NaN propagation
+    ///
+    /// If `lo` or `hi` is `f64::NAN`, it will return `f64::NAN`.
+    pub fn cdf_between(&self, lo: f64, hi: f64) -> f64 {
+        self.count_between(lo, hi) / self.count()
+    }
+
+    /// Approximate probability density function of the data for a given `value`.
+    ///
+    /// It is the derivative of the [`StreamHist::cdf`], so it is piecewise linear between the bin means.
+    /// It is zero outside of the `min` and `max` range. When the first (or last) bin mean is equal to the
+    /// `min` (or `max`), the interpolated cumulative distribution jumps there by half of the bin's count,
+    /// and those point masses are not included in the density.
+    ///
+    /// It returns `f64::NAN` for an empty histogram.
+    ///
+    /// # NaN propagation
+    ///
+    /// If the `value` is `f64::NAN`, it will return `f64::NAN`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 2.5, 3.0, 5.0]);
+    /// assert!(hist.pdf(2.5) > hist.pdf(4.0));
+    /// assert_eq!(hist.pdf(6.0), 0.0);
+    /// ```
+    pub fn pdf(&self, value: f64) -> f64 {
+        if value.is_nan() || self.is_empty() {
+            return f64::NAN;
+        }
+        if value < self.min || value > self.max {
+            return 0.0;
+        }
+
+        self.pdf_at(value, self.partition_point(value))
+    }
+
+    /// The [`StreamHist::pdf`] evaluated on the grid of `points` equally spaced values from `from` to `to`.
+    ///
+    /// Returns the `(value, density)` pairs. The bins are iterated once for all the points, so it is faster
+    /// than calling [`StreamHist::pdf`] for each of them, e.g. when plotting the density.
+    ///
+    /// # Panics
+    ///
+    /// It panics if `from` is larger than `to`, or any of them is `f64::NAN`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 2.5, 3.0, 5.0]);
+    /// let grid = hist.pdf_grid(0.0, 6.0, 7);
+    /// assert_eq!(grid.len(), 7);
+    /// assert_eq!(grid[0], (0.0, 0.0));
+    /// assert_eq!(grid[4], (4.0, hist.pdf(4.0)));
+    /// ```
+    pub fn pdf_grid(&self, from: f64, to: f64, points: usize) -> Vec<(f64, f64)> {
+        let mut idx = 0;
+        crate::grid(from, to, points)
+            .map(|value| {
+                if self.is_empty() {
+                    return (value, f64::NAN);
+                }
+                if value < self.min || value > self.max {
+                    return (value, 0.0);
+                }
+                // the values are increasing, so the search continues from the previous index
+                while idx < self.bins.len() && self.bins[idx].mean < value {
+                    idx += 1;
+                }
+                (value, self.pdf_at(value, idx))
+            })
+            .collect()
+    }
+
+    /// The [`StreamHist::pdf`] at the `value` given its [`StreamHist::partition_point`] `idx`.
+    #[inline]
+    fn pdf_at(&self, value: f64, idx: usize) -> f64 {
+        let (left, right) = self.neighbors(idx);
+        let (pi, mi) = (left.mean, left.count as f64);
+        let (pj, mj) = (right.mean, right.count as f64);
+        if pj - pi <= 0.0 {
+            return 0.0;
+        }
+        // derivative of the trapezoid area used in count_by
+        let mb = mi + (mj - mi) / (pj - pi) * (value - pi);
+        mb / (pj - pi) / self.count()
+    }
+
     /// Approximate sample quantile of the data for a given probability `prob`.
     ///
     /// It uses the "uniform" procedure described by Ben-Haim and Tom-Tov (2010).
@@ -137,70 +412,137 @@ impl StreamHist {
     /// assert_eq!(hist.quantile(0.5), 3.0);
     /// ```
     pub fn quantile(&self, prob: f64) -> f64 {
+        assert!(
+            (0.0..=1.0).contains(&prob),
+            "{prob} is not a valid probability"
+        );
+        self.quantile_with(prob, QuantileInterpolation::default())
+    }
+
+    /// Approximate sample quantile of the data for a given probability `prob`, interpolated using the `method`.
+    ///
+    /// It is the same as [`StreamHist::quantile`] for the [`QuantileInterpolation::PaperTrapezoid`] method.
+    /// The other methods can be used to match the results of the other implementations.
+    ///
+    /// It will return `f64::NAN` for an empty histogram.
+    ///
+    /// # Panics
+    ///
+    /// `prob` needs to be a probability value between `0.0` and `1.0` (inclusive),
+    /// otherwise it panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Bin, QuantileInterpolation, StreamHist};
+    ///
+    /// let hist = StreamHist::from(vec![Bin::new(1.0, 2), Bin::new(5.0, 6)]);
+    /// assert_eq!(hist.quantile_with(0.5, QuantileInterpolation::Linear), 4.0);
+    /// assert_eq!(hist.quantile_with(0.5, QuantileInterpolation::Midpoint), 3.0);
+    /// assert!(hist.quantile_with(0.5, QuantileInterpolation::PaperTrapezoid) > 4.0);
+    /// ```
+    pub fn quantile_with(&self, prob: f64, method: QuantileInterpolation) -> f64 {
+        assert!(
+            (0.0..=1.0).contains(&prob),
+            "{prob} is not a valid probability"
+        );
+        quantile(&self.bins, self.min, self.max, self.count(), prob, method)
+    }
+
+    /// Approximate sample quantiles of the data for many probabilities `probs`.
+    ///
+    /// The same as calling [`StreamHist::quantile`] for each of the `probs`, but the quantiles are found in a
+    /// single pass over the bins, so it is faster when many quantiles are needed, e.g. for the monitoring metrics.
+    /// The results are in the same order as the `probs`, which do not need to be sorted.
+    ///
+    /// # Panics
+    ///
+    /// All the `probs` need to be probability values between `0.0` and `1.0` (inclusive), otherwise it panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    /// assert_eq!(hist.quantiles(&[0.5, 0.0, 1.0]), vec![3.0, 1.0, 5.0]);
+    /// ```
+    pub fn quantiles(&self, probs: &[f64]) -> Vec<f64> {
+        if let Some(prob) = probs.iter().find(|prob| !(0.0..=1.0).contains(*prob)) {
+            panic!("{prob} is not a valid probability");
+        }
+        quantiles(&self.bins, self.min, self.max, self.count(), probs)
+    }
+
+    /// Bounds for the sample quantile of the data for a given probability `prob`, see [`StreamHist::quantile`].
+    ///
+    /// The values summarized by a bin lie between the means of its neighboring bins, since the bins are created
+    /// by merging the closest ones. The bounds are the means of the neighbors of the bin containing the value
+    /// at the rank of the quantile, with `min` and `max` used for the first and the last bin, so they contain
+    /// the [`StreamHist::quantile`] estimate. The wide bounds mean that the histogram needs more bins to
+    /// estimate the quantile precisely. For `prob` equal to `0.0` or `1.0`, both bounds are equal to the exact
+    /// `min` or `max`.
+    ///
+    /// It will return `f64::NAN`s for an empty histogram.
+    ///
+    /// # Panics
+    ///
+    /// `prob` needs to be a probability value between `0.0` and `1.0` (inclusive),
+    /// otherwise it panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Bin, StreamHist};
+    ///
+    /// let hist = StreamHist::from(vec![Bin::new(1.0, 2), Bin::new(5.0, 4), Bin::new(8.0, 2)]);
+    /// let (lower, upper) = hist.quantile_bounds(0.5);
+    /// assert_eq!((lower, upper), (1.0, 8.0));
+    /// assert!(lower <= hist.quantile(0.5) && hist.quantile(0.5) <= upper);
+    /// ```
+    pub fn quantile_bounds(&self, prob: f64) -> (f64, f64) {
         assert!(
             (0.0..=1.0).contains(&prob),
             "{prob} is not a valid probability"
         );
         if self.is_empty() {
-            return f64::NAN;
+            return (f64::NAN, f64::NAN);
         }
         if prob == 0.0 {
-            return self.min;
+            return (self.min, self.min);
         }
         if prob == 1.0 {
-            return self.max;
+            return (self.max, self.max);
         }
 
-        // Algorithm 4: Uniform Procedure from Ben-Haim & Tom-Tov (2010), p. 853
-        let count = prob * self.count();
-        let (idx, sum) = self.find_cumulative_count_by(count);
-
-        let (left, right) = self.neighbors(idx);
-        let (pi, mi) = (left.mean, left.count as f64);
-        let (pj, mj) = (right.mean, right.count as f64);
-
-        let d = count - sum;
-        let a = mj - mi;
-        if a == 0.0 {
-            return pi + (pj - pi) * (d / mi);
-        }
-        let b = 2.0 * mi;
-        let c = -2.0 * d;
-        let z = (-b + (b.powi(2) - 4.0 * a * c).sqrt()) / (2.0 * a);
-        pi + (pj - pi) * z
+        let rank = prob * self.count();
+        let mut sum = 0.0;
+        let idx = self
+            .bins
+            .iter()
+            .position(|bin| {
+                sum += bin.count as f64;
+                sum >= rank
+            })
+            .unwrap_or(self.bins.len() - 1);
+        let lower = match idx {
+            0 => self.min,
+            _ => self.bins[idx - 1].mean,
+        };
+        let upper = self.bins.get(idx + 1).map_or(self.max, |bin| bin.mean);
+        (lower, upper)
     }
 
     /// Find an index of the cumulative sum of counts, return the index and the sum.
-    fn find_cumulative_count_by(&self, value: f64) -> (usize, f64) {
-        debug_assert!(!value.is_nan());
-        let mut idx = 0;
-        let mut sum = 0.0;
-        let mut prev = 0.0;
-        for bin in self.iter() {
-            let this = bin.count as f64 / 2.0;
-            // compare to the midpoint between the two bins
-            if sum + this + prev > value {
-                break;
-            }
-            sum += prev + this;
-            prev = this;
-            idx += 1;
-        }
-        (idx, sum)
+    #[inline]
+    pub(crate) fn find_cumulative_count_by(&self, value: f64) -> (usize, f64) {
+        find_cumulative_count_by(&self.bins, value)
     }
 
     /// Returns the bins at indexes `index-1` and `index`.
     #[inline]
-    fn neighbors(&self, index: usize) -> (Bin, Bin) {
-        if index == 0 {
-            let first = Bin::new(self.min, 0);
-            (first, self.bins.first().cloned().unwrap_or(first))
-        } else if index >= self.bins.len() {
-            let last = Bin::new(self.max, 0);
-            (self.bins.last().cloned().unwrap_or(last), last)
-        } else {
-            (self.bins[index - 1], self.bins[index])
-        }
+    pub(crate) fn neighbors(&self, index: usize) -> (Bin, Bin) {
+        neighbors(&self.bins, self.min, self.max, index)
     }
 
     /// Approximate median of the data.
@@ -218,10 +560,298 @@ impl StreamHist {
     pub fn median(&self) -> f64 {
         self.quantile(0.5)
     }
+
+    /// The bins with counts normalized to densities.
+    ///
+    /// Returns the `(mean, density)` pairs, where the densities are the counts divided by the total count
+    /// and the width of the bin, so they integrate to one and can be compared between the histograms
+    /// with different sample sizes and numbers of bins. The bins span between the midpoints to the neighboring
+    /// bins, the first one starts at `min` and the last one ends at `max`. The density of a bin with zero
+    /// width (e.g. the only bin of a histogram) is `f64::INFINITY`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::{Bin, StreamHist};
+    ///
+    /// let hist = StreamHist::from(vec![Bin::new(1.0, 1), Bin::new(2.0, 2), Bin::new(3.0, 1)]);
+    /// assert_eq!(
+    ///     hist.normalized_bins(),
+    ///     vec![(1.0, 0.5), (2.0, 0.5), (3.0, 0.5)]
+    /// );
+    /// ```
+    pub fn normalized_bins(&self) -> Vec<(f64, f64)> {
+        let total = self.count();
+        self.iter()
+            .zip(self.bin_edges())
+            .map(|(bin, (left, right))| (bin.mean, bin.count as f64 / total / (right - left)))
+            .collect()
+    }
+
+    /// Approximate counts of the values in the buckets with the upper `bounds`, e.g. the SLA thresholds.
+    ///
+    /// Returns one count more than the `bounds`: the values up to the first bound, between the consecutive bounds
+    /// (excluding the lower and including the upper one), and above the last bound, so they sum to
+    /// the total [`StreamHist::count`]. The counts are estimated using the [`StreamHist::count_by`] "sum" procedure
+    /// and are not rounded.
+    ///
+    /// # Panics
+    ///
+    /// The `bounds` need to be sorted in increasing order and not `f64::NAN`, otherwise it panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    /// assert_eq!(hist.counts_for_bounds(&[0.0, 3.0, 10.0]), vec![0.0, 2.5, 2.5, 0.0]);
+    /// ```
+    pub fn counts_for_bounds(&self, bounds: &[f64]) -> Vec<f64> {
+        assert!(
+            bounds.windows(2).all(|w| w[0] < w[1]) && !bounds.iter().any(|x| x.is_nan()),
+            "the bounds need to be sorted in increasing order"
+        );
+        let total = self.count_by(f64::INFINITY);
+        let mut prev = 0.0;
+        bounds
+            .iter()
+            .map(|bound| self.count_by(*bound).min(total))
+            .chain([total])
+            .map(|cum| {
+                let count = (cum - prev).max(0.0);
+                prev = prev.max(cum);
+                count
+            })
+            .collect()
+    }
+
+    /// Project the bins onto `n` equal-width buckets spanning from `min` to `max`, as in the classical histogram.
+    ///
+    /// Returns the `(lower, upper, count)` triples, where the counts are estimated using the [`StreamHist::count_by`]
+    /// "sum" procedure, so they sum to the total [`StreamHist::count`], with the values equal to `min` in the first
+    /// bucket and the values equal to `max` in the last one. The counts are not rounded. It returns no buckets for
+    /// an empty histogram or when `n` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    /// let buckets = hist.to_fixed_bins(2);
+    /// assert_eq!(buckets, vec![(1.0, 3.0, 2.5), (3.0, 5.0, 2.5)]);
+    /// ```
+    pub fn to_fixed_bins(&self, n: usize) -> Vec<(f64, f64, f64)> {
+        if self.is_empty() || n == 0 {
+            return Vec::new();
+        }
+        let total = self.count();
+        let edges: Vec<f64> = crate::grid(self.min, self.max, n + 1).collect();
+        let mut prev = 0.0;
+        edges
+            .windows(2)
+            .enumerate()
+            .map(|(i, w)| {
+                let cum = if i + 1 == n {
+                    total
+                } else {
+                    self.count_by(w[1]).min(total)
+                };
+                let count = (cum - prev).max(0.0);
+                prev = cum;
+                (w[0], w[1], count)
+            })
+            .collect()
+    }
+
+    /// The left and right edges of the bins, as the midpoints to the neighboring bins, with the first
+    /// bin starting at `min` and the last ending at `max`.
+    pub(crate) fn bin_edges(&self) -> Vec<(f64, f64)> {
+        let mut edges = Vec::with_capacity(self.bins.len());
+        let mut left = self.min;
+        for (i, bin) in self.bins.iter().enumerate() {
+            let right = match self.bins.get(i + 1) {
+                Some(next) => (bin.mean + next.mean) / 2.0,
+                None => self.max,
+            };
+            edges.push((left, right));
+            left = right;
+        }
+        edges
+    }
+}
+
+/// Approximate count of the number of values since the `value`, see [`StreamHist::count_by`].
+///
+/// The procedures are implemented for the sorted `bins`, so they are shared by the histograms with
+/// the different storage of the bins, the `count` is the total count of the bins.
+pub(crate) fn count_by(bins: &[Bin], min: f64, max: f64, count: f64, value: f64) -> f64 {
+    if value.is_nan() {
+        return f64::NAN;
+    }
+    if bins.is_empty() || value <= min {
+        return 0.0;
+    }
+    if value > max {
+        return count;
+    }
+
+    // Algorithm 3: Sum Procedure from Ben-Haim & Tom-Tov (2010), p. 852
+    let idx = bins.partition_point(|bin| bin.mean < value);
+    let sum = sum_counts(&bins[..idx.saturating_sub(1)]) as f64;
+
+    let (left, right) = neighbors(bins, min, max, idx);
+    let (pi, mi) = (left.mean, left.count as f64);
+    let (pj, mj) = (right.mean, right.count as f64);
+
+    let s = if pj - pi <= 0.0 {
+        0.0
+    } else {
+        let mb = mi + (mj - mi) / (pj - pi) * (value - pi);
+        (mi + mb) / 2.0 * (value - pi) / (pj - pi)
+    };
+    sum + mi / 2.0 + s
+}
+
+/// Approximate sample quantile of the data, see [`StreamHist::quantile`] and [`count_by`].
+pub(crate) fn quantile(
+    bins: &[Bin],
+    min: f64,
+    max: f64,
+    count: f64,
+    prob: f64,
+    method: QuantileInterpolation,
+) -> f64 {
+    if bins.is_empty() {
+        return f64::NAN;
+    }
+    if prob == 0.0 {
+        return min;
+    }
+    if prob == 1.0 {
+        return max;
+    }
+
+    // Algorithm 4: Uniform Procedure from Ben-Haim & Tom-Tov (2010), p. 853
+    let count = prob * count;
+    let (idx, sum) = find_cumulative_count_by(bins, count);
+    interpolate_quantile(bins, min, max, count, idx, sum, method)
+}
+
+/// Approximate sample quantiles for many probabilities, see [`StreamHist::quantiles`].
+pub(crate) fn quantiles(bins: &[Bin], min: f64, max: f64, count: f64, probs: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..probs.len()).collect();
+    order.sort_by(|a, b| probs[*a].total_cmp(&probs[*b]));
+
+    let mut result = vec![f64::NAN; probs.len()];
+    if bins.is_empty() {
+        return result;
+    }
+    let (mut idx, mut sum, mut prev) = (0, 0.0, 0.0);
+    for i in order {
+        let prob = probs[i];
+        result[i] = if prob == 0.0 {
+            min
+        } else if prob == 1.0 {
+            max
+        } else {
+            // continue the search of find_cumulative_count_by from the previous quantile
+            let target = prob * count;
+            while let Some(bin) = bins.get(idx) {
+                let this = bin.count as f64 / 2.0;
+                if sum + this + prev > target {
+                    break;
+                }
+                sum += prev + this;
+                prev = this;
+                idx += 1;
+            }
+            interpolate_quantile(
+                bins,
+                min,
+                max,
+                target,
+                idx,
+                sum,
+                QuantileInterpolation::default(),
+            )
+        };
+    }
+    result
+}
+
+/// Find the value where the interpolated cumulative count reaches `count`, given the `index` and the cumulative
+/// `sum` found by [`find_cumulative_count_by`].
+#[inline]
+fn interpolate_quantile(
+    bins: &[Bin],
+    min: f64,
+    max: f64,
+    count: f64,
+    idx: usize,
+    sum: f64,
+    method: QuantileInterpolation,
+) -> f64 {
+    let (left, right) = neighbors(bins, min, max, idx);
+    let (pi, mi) = (left.mean, left.count as f64);
+    let (pj, mj) = (right.mean, right.count as f64);
+
+    let d = count - sum;
+    let z = match method {
+        QuantileInterpolation::PaperTrapezoid => {
+            // the root of (mj - mi) z^2 + 2 mi z - 2 d = 0, in the form that does not lose the precision
+            // when the counts are close to each other
+            let root = (mi.powi(2) + 2.0 * (mj - mi) * d).max(0.0).sqrt();
+            2.0 * d / (mi + root)
+        }
+        QuantileInterpolation::Linear => d / ((mi + mj) / 2.0),
+        QuantileInterpolation::Midpoint if d > 0.0 => 0.5,
+        QuantileInterpolation::Midpoint => 0.0,
+    };
+    if !z.is_finite() {
+        return pi;
+    }
+    pi + (pj - pi) * z.clamp(0.0, 1.0)
+}
+
+/// Find an index of the cumulative sum of counts, return the index and the sum.
+fn find_cumulative_count_by(bins: &[Bin], value: f64) -> (usize, f64) {
+    debug_assert!(!value.is_nan());
+    let mut idx = 0;
+    let mut sum = 0.0;
+    let mut prev = 0.0;
+    for bin in bins {
+        let this = bin.count as f64 / 2.0;
+        // compare to the midpoint between the two bins
+        if sum + this + prev > value {
+            break;
+        }
+        sum += prev + this;
+        prev = this;
+        idx += 1;
+    }
+    (idx, sum)
+}
+
+/// Returns the bins at indexes `index-1` and `index`, using `min` and `max` as the bins with zero counts
+/// before the first and after the last bin.
+#[inline]
+fn neighbors(bins: &[Bin], min: f64, max: f64, index: usize) -> (Bin, Bin) {
+    if index == 0 {
+        let first = Bin::new(min, 0);
+        (first, bins.first().cloned().unwrap_or(first))
+    } else if index >= bins.len() {
+        let last = Bin::new(max, 0);
+        (bins.last().cloned().unwrap_or(last), last)
+    } else {
+        (bins[index - 1], bins[index])
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::QuantileInterpolation;
     use crate::bins::Bin;
     use crate::hist::StreamHist;
     use test_case::test_case;
@@ -238,6 +868,257 @@ mod tests {
         assert!(hist.cdf(f64::NAN).is_nan());
     }
 
+    #[test]
+    fn count_between() {
+        let mut hist = StreamHist::with_capacity(5);
+        for i in 0..100 {
+            hist.insert((i % 20) as f64);
+        }
+        assert_eq!(hist.count_between(f64::NEG_INFINITY, f64::INFINITY), 100.0);
+        assert_eq!(hist.count_between(-10.0, hist.min), 0.0);
+        assert_eq!(hist.count_between(5.0, 5.0), 0.0);
+        assert!(hist.count_between(f64::NAN, 1.0).is_nan());
+        assert!(hist.count_between(1.0, f64::NAN).is_nan());
+
+        let (lo, mid, hi) = (2.5, 8.0, 14.0);
+        let total = hist.count_between(lo, mid) + hist.count_between(mid, hi);
+        assert!((hist.count_between(lo, hi) - total).abs() < 1e-9);
+        assert_eq!(hist.cdf_between(lo, hi), hist.count_between(lo, hi) / 100.0);
+
+        assert_eq!(StreamHist::default().count_between(0.0, 1.0), 0.0);
+        assert!(StreamHist::default().cdf_between(0.0, 1.0).is_nan());
+    }
+
+    #[test]
+    fn normalized_bins() {
+        assert!(StreamHist::default().normalized_bins().is_empty());
+        assert_eq!(
+            StreamHist::from(vec![2.0]).normalized_bins(),
+            vec![(2.0, f64::INFINITY)]
+        );
+
+        let hist = StreamHist::from_parts(
+            vec![Bin::new(2.0, 3), Bin::new(4.0, 1), Bin::new(8.0, 4)],
+            1.0,
+            9.0,
+            3,
+        );
+        assert_eq!(hist.bin_edges(), vec![(1.0, 3.0), (3.0, 6.0), (6.0, 9.0)]);
+        let densities = hist.normalized_bins();
+        assert_eq!(
+            densities,
+            vec![(2.0, 3.0 / 16.0), (4.0, 1.0 / 24.0), (8.0, 1.0 / 6.0)]
+        );
+
+        // densities integrate to one
+        let total: f64 = densities
+            .iter()
+            .zip(hist.bin_edges())
+            .map(|((_, density), (left, right))| density * (right - left))
+            .sum();
+        assert!((total - 1.0).abs() < 1e-12);
+
+        // the same distribution with a different sample size
+        let doubled = StreamHist::from_parts(
+            vec![Bin::new(2.0, 6), Bin::new(4.0, 2), Bin::new(8.0, 8)],
+            1.0,
+            9.0,
+            3,
+        );
+        assert_eq!(doubled.normalized_bins(), densities);
+    }
+
+    #[test]
+    fn counts_for_bounds() {
+        let mut hist = StreamHist::with_capacity(10);
+        for i in 0..1000 {
+            hist.insert(((i * 17) % 101) as f64);
+        }
+        let bounds = [-5.0, 10.0, 50.0, 50.5, 200.0];
+        let counts = hist.counts_for_bounds(&bounds);
+        assert_eq!(counts.len(), bounds.len() + 1);
+        assert_eq!(counts[0], 0.0);
+        assert_eq!(counts[5], 0.0);
+        assert!((counts.iter().sum::<f64>() - 1000.0).abs() < 1e-9);
+        for (i, w) in bounds.windows(2).enumerate() {
+            assert!((counts[i + 1] - hist.count_between(w[0], w[1])).abs() < 1e-9);
+        }
+
+        assert_eq!(hist.counts_for_bounds(&[]), vec![1000.0]);
+        assert_eq!(
+            StreamHist::default().counts_for_bounds(&[1.0, 2.0]),
+            vec![0.0; 3]
+        );
+    }
+
+    #[test_case(&[2.0, 1.0] ; "not sorted")]
+    #[test_case(&[1.0, 1.0] ; "duplicated")]
+    #[test_case(&[f64::NAN] ; "nan")]
+    #[should_panic]
+    fn counts_for_bounds_invalid(bounds: &[f64]) {
+        StreamHist::from(vec![1.0, 2.0]).counts_for_bounds(bounds);
+    }
+
+    #[test]
+    fn to_fixed_bins() {
+        assert!(StreamHist::default().to_fixed_bins(5).is_empty());
+        assert!(StreamHist::from(vec![1.0]).to_fixed_bins(0).is_empty());
+        assert_eq!(
+            StreamHist::from(vec![2.0, 2.0]).to_fixed_bins(1),
+            vec![(2.0, 2.0, 2.0)]
+        );
+
+        let mut hist = StreamHist::with_capacity(10);
+        for i in 0..1000 {
+            hist.insert(((i * 17) % 101) as f64);
+        }
+        let buckets = hist.to_fixed_bins(20);
+        assert_eq!(buckets.len(), 20);
+        assert_eq!((buckets[0].0, buckets[19].1), (hist.min, hist.max));
+        let total: f64 = buckets.iter().map(|(_, _, count)| count).sum();
+        assert!((total - 1000.0).abs() < 1e-9);
+        for (i, (lower, upper, count)) in buckets.iter().enumerate() {
+            assert!((upper - lower - 5.0).abs() < 1e-9);
+            if i > 0 && i < 19 {
+                assert!((count - hist.count_between(*lower, *upper)).abs() < 1e-9);
+            }
+            // roughly uniform
+            assert!((count - 50.0).abs() < 20.0, "{count}");
+        }
+    }
+
+    #[test]
+    fn pdf() {
+        assert!(StreamHist::default().pdf(1.0).is_nan());
+
+        let hist = StreamHist::from_parts(
+            vec![Bin::new(2.0, 3), Bin::new(4.0, 1), Bin::new(8.0, 4)],
+            1.0,
+            9.0,
+            3,
+        );
+        assert!(hist.pdf(f64::NAN).is_nan());
+        assert_eq!(hist.pdf(0.0), 0.0);
+        assert_eq!(hist.pdf(10.0), 0.0);
+        assert_eq!(hist.pdf(1.0), 0.0);
+        assert_eq!(hist.pdf(3.0), 2.0 / 2.0 / 8.0);
+
+        // it is the derivative of the cdf
+        let h = 1e-6;
+        for x in [1.5, 2.5, 3.9, 4.1, 6.0, 8.5] {
+            let derivative = (hist.cdf(x + h) - hist.cdf(x - h)) / (2.0 * h);
+            assert!((hist.pdf(x) - derivative).abs() < 1e-6, "{x}");
+        }
+
+        // no point masses at the edges, so it integrates to one
+        let n = 100_000;
+        let dx = (hist.max - hist.min) / n as f64;
+        let total: f64 = (0..n)
+            .map(|i| hist.pdf(hist.min + (i as f64 + 0.5) * dx) * dx)
+            .sum();
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pdf_grid() {
+        let mut hist = StreamHist::with_capacity(5);
+        for i in 0..100 {
+            hist.insert(((i * 13) % 37) as f64);
+        }
+        let grid = hist.pdf_grid(-5.0, 40.0, 451);
+        assert_eq!(grid.len(), 451);
+        for (x, density) in grid {
+            assert_eq!(density, hist.pdf(x), "{x}");
+        }
+
+        assert_eq!(hist.pdf_grid(1.0, 2.0, 1), vec![(1.0, hist.pdf(1.0))]);
+        assert!(hist.pdf_grid(1.0, 2.0, 0).is_empty());
+        assert!(StreamHist::default().pdf_grid(1.0, 2.0, 2)[0].1.is_nan());
+    }
+
+    #[test]
+    #[should_panic]
+    fn pdf_grid_invalid() {
+        StreamHist::from(vec![1.0, 2.0]).pdf_grid(2.0, 1.0, 10);
+    }
+
+    #[test]
+    fn winsorized() {
+        assert!(StreamHist::default().winsorized_mean(0.1).is_nan());
+        assert!(StreamHist::default().winsorized_variance(0.1).is_nan());
+
+        let hist = StreamHist::from(vec![-100.0, 1.0, 2.0, 3.0, 4.0, 5.0, 100.0]);
+        assert_eq!(hist.winsorized_mean(0.0), hist.mean());
+        assert!((hist.winsorized_variance(0.0) - hist.variance()).abs() < 1e-9);
+
+        let (lower, upper) = (hist.quantile(0.2), hist.quantile(0.8));
+        let expected = (lower + 1.0 + 2.0 + 3.0 + 4.0 + 5.0 + upper) / 7.0;
+        assert!((hist.winsorized_mean(0.2) - expected).abs() < 1e-12);
+        assert!(hist.winsorized_variance(0.2) < hist.variance());
+        assert!(hist.winsorized_variance(0.2) > 0.0);
+    }
+
+    #[test_case(-0.1 ; "negative")]
+    #[test_case(0.5 ; "half")]
+    #[test_case(f64::NAN ; "NaN")]
+    #[should_panic]
+    fn winsorized_invalid(alpha: f64) {
+        StreamHist::from(vec![1.0, 2.0]).winsorized_mean(alpha);
+    }
+
+    #[test]
+    fn trimmed_mean() {
+        assert!(StreamHist::default().trimmed_mean(0.1, 0.9).is_nan());
+
+        let values: Vec<f64> = (0..=1000).map(|x| x as f64 / 10.0).collect();
+        let mut hist = StreamHist::from(values);
+        hist.resize(20);
+        assert!((hist.trimmed_mean(0.0, 1.0) - 50.0).abs() < 0.5);
+        assert!((hist.trimmed_mean(0.25, 0.75) - 50.0).abs() < 0.5);
+        assert!((hist.trimmed_mean(0.0, 0.5) - 25.0).abs() < 0.5);
+        assert!((hist.trimmed_mean(0.9, 1.0) - 95.0).abs() < 0.5);
+
+        // the outliers are ignored
+        hist.insert(1e9);
+        hist.insert(-1e9);
+        assert!((hist.trimmed_mean(0.1, 0.9) - 50.0).abs() < 0.5);
+    }
+
+    #[test_case(-0.1, 0.5 ; "negative")]
+    #[test_case(0.5, 1.5 ; "above one")]
+    #[test_case(0.5, 0.5 ; "equal")]
+    #[test_case(0.9, 0.1 ; "reversed")]
+    #[should_panic]
+    fn trimmed_mean_invalid(lower: f64, upper: f64) {
+        StreamHist::from(vec![1.0, 2.0]).trimmed_mean(lower, upper);
+    }
+
+    #[test]
+    fn standardize() {
+        assert_eq!(StreamHist::default().standardize(), None);
+        assert_eq!(StreamHist::from(vec![Bin::new(2.0, 5)]).standardize(), None);
+
+        let hist = StreamHist::from_parts(
+            vec![Bin::new(2.0, 3), Bin::new(4.0, 1), Bin::new(8.0, 4)],
+            1.0,
+            9.0,
+            3,
+        );
+        let standardized = hist.standardize().unwrap();
+        assert!(standardized.mean().abs() < 1e-12);
+        assert!((standardized.stdev() - 1.0).abs() < 1e-12);
+        assert_eq!(standardized.size, hist.size);
+        assert_eq!(standardized.min, hist.z_score(1.0));
+        assert_eq!(standardized.max, hist.z_score(9.0));
+        for (a, b) in standardized.iter().zip(hist.iter()) {
+            assert_eq!(a.mean, hist.z_score(b.mean));
+            assert_eq!(a.count, b.count);
+        }
+
+        // the shape of the distribution is the same
+        assert!((standardized.cdf(hist.z_score(5.0)) - hist.cdf(5.0)).abs() < 1e-12);
+    }
+
     #[test]
     fn count_by_nan() {
         let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
@@ -266,12 +1147,12 @@ mod tests {
         assert_eq!(hist.count_by(5.0), 4.5);
         assert_eq!(hist.count_by(6.0), 5.0);
 
-        let hist = StreamHist {
-            bins: vec![Bin::new(7.0, 3), Bin::from(20.0), Bin::new(34.0, 3)],
-            min: 1.0,
-            max: 37.0,
-            size: 3,
-        };
+        let hist = StreamHist::from_parts(
+            vec![Bin::new(7.0, 3), Bin::from(20.0), Bin::new(34.0, 3)],
+            1.0,
+            37.0,
+            3,
+        );
         assert_eq!(hist.count_by(0.0), 0.0);
         assert_eq!(hist.count_by(40.0), 7.0);
 
@@ -289,12 +1170,12 @@ mod tests {
 
     #[test]
     fn counts_are_monotonic() {
-        let hist = StreamHist {
-            bins: vec![Bin::new(7.0, 3), Bin::from(20.0), Bin::new(34.0, 3)],
-            min: 1.0,
-            max: 37.0,
-            size: 3,
-        };
+        let hist = StreamHist::from_parts(
+            vec![Bin::new(7.0, 3), Bin::from(20.0), Bin::new(34.0, 3)],
+            1.0,
+            37.0,
+            3,
+        );
 
         // The cumulative counts are monotonically increasing
         let mut value = 0.0;
@@ -333,14 +1214,123 @@ mod tests {
         assert!(StreamHist::with_capacity(10).median().is_nan());
     }
 
+    #[test_case(QuantileInterpolation::PaperTrapezoid)]
+    #[test_case(QuantileInterpolation::Linear)]
+    #[test_case(QuantileInterpolation::Midpoint)]
+    fn quantile_with(method: QuantileInterpolation) {
+        let mut hist = StreamHist::with_capacity(10);
+        for i in 0..1000 {
+            hist.insert(((i * 37) % 1000) as f64);
+        }
+        let mut prev = f64::NEG_INFINITY;
+        for i in 0..=100 {
+            let prob = i as f64 / 100.0;
+            let value = hist.quantile_with(prob, method);
+            // monotonic and within the quantile bounds
+            assert!(value >= prev, "{prob}");
+            let (lower, upper) = hist.quantile_bounds(prob);
+            assert!(lower <= value && value <= upper, "{prob}");
+            prev = value;
+        }
+        assert_eq!(hist.quantile_with(0.0, method), 0.0);
+        assert_eq!(hist.quantile_with(1.0, method), 999.0);
+        assert!(StreamHist::default().quantile_with(0.5, method).is_nan());
+
+        // the exact values are returned at the bin means
+        let hist = StreamHist::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(hist.quantile_with(0.5, method), 3.0);
+    }
+
     #[test]
-    fn quantiles_are_monotonic() {
-        let hist = StreamHist {
-            bins: vec![Bin::new(7.0, 3), Bin::from(20.0), Bin::new(34.0, 3)],
-            min: 1.0,
-            max: 37.0,
-            size: 3,
+    fn quantile_with_methods() {
+        let hist = StreamHist::from(vec![Bin::new(0.0, 2), Bin::new(10.0, 2)]);
+        assert_eq!(hist.quantile(0.375), 2.5);
+        assert_eq!(
+            hist.quantile_with(0.375, QuantileInterpolation::PaperTrapezoid),
+            2.5
+        );
+        assert_eq!(
+            hist.quantile_with(0.375, QuantileInterpolation::Linear),
+            2.5
+        );
+        assert_eq!(
+            hist.quantile_with(0.375, QuantileInterpolation::Midpoint),
+            5.0
+        );
+
+        // the counts differing by one in a large histogram
+        let hist = StreamHist::from(vec![Bin::new(0.0, 1 << 40), Bin::new(1.0, (1 << 40) + 1)]);
+        let value = hist.quantile(0.5);
+        let expected = hist.quantile_with(0.5, QuantileInterpolation::Linear);
+        assert!((value - expected).abs() < 1e-9, "{value} {expected}");
+    }
+
+    #[test]
+    fn quantile_bounds() {
+        let mut hist = StreamHist::with_capacity(10);
+        for i in 0..1000 {
+            hist.insert(((i * 37) % 1000) as f64);
+        }
+        for i in 1..100 {
+            let prob = i as f64 / 100.0;
+            let (lower, upper) = hist.quantile_bounds(prob);
+            let quantile = hist.quantile(prob);
+            assert!(lower <= quantile && quantile <= upper, "{prob}");
+            // the exact quantile of the data
+            let exact = (prob * 1000.0).floor();
+            assert!(lower <= exact && exact <= upper, "{prob}");
+        }
+        assert_eq!(hist.quantile_bounds(0.0), (0.0, 0.0));
+        assert_eq!(hist.quantile_bounds(1.0), (999.0, 999.0));
+
+        // more bins give tighter bounds
+        let mut large = StreamHist::with_capacity(100);
+        (0..1000).for_each(|i| large.insert(((i * 37) % 1000) as f64));
+        let width = |hist: &StreamHist| {
+            let (lower, upper) = hist.quantile_bounds(0.5);
+            upper - lower
         };
+        assert!(width(&large) < width(&hist));
+
+        let (lower, upper) = StreamHist::default().quantile_bounds(0.5);
+        assert!(lower.is_nan() && upper.is_nan());
+    }
+
+    #[test]
+    #[should_panic]
+    fn quantile_bounds_prob_invalid() {
+        StreamHist::from(vec![1.0]).quantile_bounds(1.5);
+    }
+
+    #[test]
+    fn quantiles() {
+        let mut hist = StreamHist::with_capacity(7);
+        for i in 0..200 {
+            hist.insert(((i * 37) % 101) as f64);
+        }
+        let probs = [0.99, 0.5, 0.0, 0.25, 0.5, 1.0, 0.01, 0.9, 0.75];
+        let expected: Vec<f64> = probs.iter().map(|prob| hist.quantile(*prob)).collect();
+        assert_eq!(hist.quantiles(&probs), expected);
+
+        assert!(hist.quantiles(&[]).is_empty());
+        assert!(StreamHist::default().quantiles(&[0.0, 0.5])[0].is_nan());
+        assert!(StreamHist::default().quantiles(&[0.0, 0.5])[1].is_nan());
+    }
+
+    #[test]
+    #[should_panic]
+    fn quantiles_prob_invalid() {
+        StreamHist::from(vec![1.0, 2.0]).quantiles(&[0.5, 1.5]);
+    }
+
+    #[test]
+    fn quantiles_are_monotonic() {
+        let hist = StreamHist::from_parts(
+            vec![Bin::new(7.0, 3), Bin::from(20.0), Bin::new(34.0, 3)],
+            1.0,
+            37.0,
+            3,
+        );
         // Quantiles are monotonically increasing
         let mut prob = 0.0;
         let mut prev_value = 0.0;
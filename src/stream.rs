@@ -0,0 +1,142 @@
+//! Periodic snapshots of a shared histogram as an asynchronous [`Stream`].
+//!
+//! # Examples
+//!
+//! ```
+//! use futures_core::Stream;
+//! use histr::stream::Snapshots;
+//! use histr::StreamHist;
+//! use std::pin::pin;
+//! use std::sync::{Arc, Mutex};
+//! use std::time::Duration;
+//!
+//! # tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(async {
+//! let hist = Arc::new(Mutex::new(StreamHist::with_capacity(10)));
+//! let mut snapshots = pin!(Snapshots::new(hist.clone(), Duration::from_millis(10)));
+//!
+//! hist.lock().unwrap().insert(1.0);
+//! let snapshot = std::future::poll_fn(|cx| snapshots.as_mut().poll_next(cx)).await;
+//! assert_eq!(snapshot.unwrap().count(), 1.0);
+//! # });
+//! ```
+
+use crate::hist::StreamHist;
+use crate::lock;
+use futures_core::Stream;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{self, Instant, Interval, MissedTickBehavior};
+
+/// [`Stream`] emitting the snapshots of a shared histogram every period.
+///
+/// The snapshots are either the copies of the whole histogram ([`Snapshots::new`]), or the deltas containing only
+/// the values recorded since the previous snapshot ([`Snapshots::deltas`]). The stream never ends.
+#[derive(Debug)]
+pub struct Snapshots {
+    hist: Arc<Mutex<StreamHist>>,
+    interval: Interval,
+    delta: bool,
+}
+
+impl Snapshots {
+    /// Emit a copy of the `hist` every `period`, the first one after the `period` elapses.
+    ///
+    /// # Panics
+    ///
+    /// It panics if `period` is zero, or when called outside of the Tokio runtime with the time driver enabled.
+    pub fn new(hist: Arc<Mutex<StreamHist>>, period: Duration) -> Self {
+        let mut interval = time::interval_at(Instant::now() + period, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Snapshots {
+            hist,
+            interval,
+            delta: false,
+        }
+    }
+
+    /// Emit the `hist` every `period` and replace it with an empty histogram of the same size,
+    /// so each snapshot contains only the values recorded since the previous one.
+    ///
+    /// # Panics
+    ///
+    /// It panics if `period` is zero, or when called outside of the Tokio runtime with the time driver enabled.
+    pub fn deltas(hist: Arc<Mutex<StreamHist>>, period: Duration) -> Self {
+        Snapshots {
+            delta: true,
+            ..Snapshots::new(hist, period)
+        }
+    }
+
+    fn snapshot(&self) -> StreamHist {
+        let mut hist = lock(&self.hist);
+        if self.delta {
+            let empty = StreamHist::with_capacity(hist.size);
+            std::mem::replace(&mut hist, empty)
+        } else {
+            hist.clone()
+        }
+    }
+}
+
+impl Stream for Snapshots {
+    type Item = StreamHist;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        match this.interval.poll_tick(cx) {
+            Poll::Ready(_) => Poll::Ready(Some(this.snapshot())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Snapshots;
+    use crate::hist::StreamHist;
+    use futures_core::Stream;
+    use std::future::poll_fn;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    async fn next(stream: &mut Snapshots) -> StreamHist {
+        poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cumulative() {
+        let hist = Arc::new(Mutex::new(StreamHist::with_capacity(5)));
+        let mut snapshots = Snapshots::new(hist.clone(), Duration::from_secs(1));
+
+        hist.lock().unwrap().insert(1.0);
+        assert_eq!(next(&mut snapshots).await.count(), 1.0);
+        hist.lock().unwrap().insert(2.0);
+        assert_eq!(next(&mut snapshots).await.count(), 2.0);
+        assert_eq!(hist.lock().unwrap().count(), 2.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn deltas() {
+        let hist = Arc::new(Mutex::new(StreamHist::with_capacity(5)));
+        let mut snapshots = Snapshots::deltas(hist.clone(), Duration::from_secs(1));
+
+        hist.lock().unwrap().insert(1.0);
+        hist.lock().unwrap().insert(2.0);
+        let snapshot = next(&mut snapshots).await;
+        assert_eq!(snapshot.count(), 2.0);
+        assert_eq!(snapshot.size, 5);
+
+        hist.lock().unwrap().insert(3.0);
+        let snapshot = next(&mut snapshots).await;
+        assert_eq!(snapshot.count(), 1.0);
+        assert_eq!(snapshot.mean(), 3.0);
+
+        assert!(next(&mut snapshots).await.is_empty());
+        assert_eq!(hist.lock().unwrap().size, 5);
+    }
+}
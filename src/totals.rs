@@ -0,0 +1,150 @@
+use crate::bins::{sum_counts, Bin};
+
+/// Running aggregates of the bins of a histogram, so that the count, mean, and variance
+/// do not need to be calculated by iterating over all the bins.
+///
+/// Instead of the weighted sums of means and their squares, it keeps the mean and the sum of
+/// squared differences from the mean, which are updated using the numerically stable
+/// [Welford's algorithm] and its extension by Chan et al. for merging.
+///
+/// [Welford's algorithm]: https://en.wikipedia.org/wiki/Algorithms_for_calculating_variance#Welford's_online_algorithm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Totals {
+    pub(crate) count: u64,
+    pub(crate) mean: f64,
+    /// Sum of squared differences of the means of the bins from the overall mean, weighted by the counts.
+    pub(crate) m2: f64,
+}
+
+impl Totals {
+    /// Calculate the aggregates of the `bins` from scratch.
+    pub(crate) fn from_bins(bins: &[Bin]) -> Self {
+        let count = sum_counts(bins);
+        if count == 0 {
+            return Totals::default();
+        }
+        let mean = bins
+            .iter()
+            .fold(0.0, |acc, bin| acc + bin.mean * bin.count as f64)
+            / count as f64;
+        let m2 = bins.iter().fold(0.0, |acc, bin| {
+            acc + bin.count as f64 * (bin.mean - mean).powi(2)
+        });
+        Totals { count, mean, m2 }
+    }
+
+    /// Update the aggregates with a new `value` observed `count` times.
+    #[inline]
+    pub(crate) fn insert(&mut self, value: f64, count: u64) {
+        self.count += count;
+        let delta = value - self.mean;
+        self.mean += delta * count as f64 / self.count as f64;
+        self.m2 += count as f64 * delta * (value - self.mean);
+    }
+
+    /// Update the aggregates after a `value` observed `count` times was removed, reverting [`Totals::insert`].
+    #[inline]
+    pub(crate) fn remove(&mut self, value: f64, count: u64) {
+        if count >= self.count {
+            *self = Totals::default();
+            return;
+        }
+        let mean = self.mean;
+        self.count -= count;
+        self.mean -= (value - mean) * count as f64 / self.count as f64;
+        self.m2 = (self.m2 - count as f64 * (value - mean) * (value - self.mean)).max(0.0);
+    }
+
+    /// Update the aggregates after the two bins were merged into one.
+    ///
+    /// The total count and the mean do not change, but the variance between the bins decreases.
+    #[inline]
+    pub(crate) fn merge_bins(&mut self, left: &Bin, right: &Bin) {
+        let total = left.count + right.count;
+        if total == 0 {
+            return;
+        }
+        let weight = left.count as f64 * right.count as f64 / total as f64;
+        self.m2 = (self.m2 - weight * (right.mean - left.mean).powi(2)).max(0.0);
+    }
+
+    /// Combine the aggregates of two histograms.
+    pub(crate) fn merge(&mut self, other: &Totals) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        let weight = self.count as f64 * other.count as f64 / count as f64;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta.powi(2) * weight;
+        self.count = count;
+    }
+}
+
+impl Default for Totals {
+    fn default() -> Self {
+        Totals {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Totals;
+    use crate::bins::Bin;
+
+    fn assert_close(a: Totals, b: Totals) {
+        assert_eq!(a.count, b.count);
+        assert!((a.mean - b.mean).abs() < 1e-9, "{a:?} != {b:?}");
+        assert!((a.m2 - b.m2).abs() < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn insert() {
+        let values = [1.0, 5.0, 2.0, 2.0, 8.0];
+        let mut totals = Totals::default();
+        values.iter().for_each(|x| totals.insert(*x, 1));
+
+        let bins: Vec<Bin> = values.iter().map(|x| Bin::from(*x)).collect();
+        assert_close(totals, Totals::from_bins(&bins));
+        assert_eq!(totals.mean, 3.6);
+
+        let mut weighted = Totals::default();
+        weighted.insert(2.0, 2);
+        weighted.insert(1.0, 1);
+        weighted.insert(5.0, 0);
+        assert_close(
+            weighted,
+            Totals::from_bins(&[Bin::new(2.0, 2), Bin::from(1.0)]),
+        );
+    }
+
+    #[test]
+    fn merge_bins() {
+        let (a, b, c) = (Bin::new(1.0, 2), Bin::new(2.0, 3), Bin::new(6.0, 1));
+        let mut totals = Totals::from_bins(&[a, b, c]);
+        totals.merge_bins(&a, &b);
+        assert_close(totals, Totals::from_bins(&[a + b, c]));
+    }
+
+    #[test]
+    fn merge() {
+        let left = [Bin::new(1.0, 2), Bin::new(2.0, 3)];
+        let right = [Bin::new(-4.0, 1), Bin::new(6.0, 4)];
+        let mut totals = Totals::from_bins(&left);
+        totals.merge(&Totals::from_bins(&right));
+        assert_close(totals, Totals::from_bins(&[left, right].concat()));
+
+        let mut empty = Totals::default();
+        empty.merge(&Totals::from_bins(&left));
+        assert_close(empty, Totals::from_bins(&left));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::bins::Bin;
+use crate::error::Error;
+use crate::hist::StreamHist;
+
+impl StreamHist {
+    /// Multiply the values summarized by the histogram by the `factor`, e.g. to convert seconds to milliseconds,
+    /// see [`StreamHist::map`].
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::InvalidParts`] if the scaled values are not numbers (e.g. when `factor` is infinite).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let seconds = StreamHist::from(vec![0.5, 1.0, 1.5]);
+    /// let millis = seconds.scale(1000.0).unwrap();
+    /// assert_eq!(millis.mean(), 1000.0);
+    /// assert_eq!((millis.min(), millis.max()), (500.0, 1500.0));
+    /// ```
+    pub fn scale(&self, factor: f64) -> Result<StreamHist, Error> {
+        self.map(|x| x * factor)
+    }
+
+    /// Add the `offset` to the values summarized by the histogram, see [`StreamHist::map`].
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::InvalidParts`] if the shifted values are not numbers (e.g. when `offset` is infinite).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 2.0, 3.0]).shift(-2.0).unwrap();
+    /// assert_eq!(hist.mean(), 0.0);
+    /// assert_eq!(hist.variance(), StreamHist::from(vec![1.0, 2.0, 3.0]).variance());
+    /// ```
+    pub fn shift(&self, offset: f64) -> Result<StreamHist, Error> {
+        self.map(|x| x + offset)
+    }
+
+    /// Transform the histogram by applying the monotonic function `f` to the bin means, `min`, and `max`.
+    ///
+    /// The function can be increasing or decreasing (e.g. `|x| -x`), in the latter case the order of the bins
+    /// is reversed. The bins with the means that become equal (e.g. for a function that is constant in some range)
+    /// are combined. The transformation is exact only for the linear functions, since the other functions would
+    /// also change the distribution of the values within the bins.
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::InvalidParts`] if:
+    /// * the transformed values are not numbers (e.g. the logarithm of a negative value),
+    /// * the function is not monotonic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist = StreamHist::from(vec![1.0, 10.0, 100.0]);
+    /// let log = hist.map(f64::log10).unwrap();
+    /// assert_eq!(log.mean(), 1.0);
+    ///
+    /// let negated = hist.map(|x| -x).unwrap();
+    /// assert_eq!((negated.min(), negated.max()), (-100.0, -1.0));
+    ///
+    /// assert!(hist.map(|x| (x - 10.0).abs()).is_err());
+    /// assert!(hist.map(|x| (x - 2.0).ln()).is_err());
+    /// ```
+    pub fn map<F: Fn(f64) -> f64>(&self, f: F) -> Result<StreamHist, Error> {
+        let (Some(first), Some(last)) = (self.bins.first(), self.bins.last()) else {
+            return Ok(StreamHist::with_capacity(self.size));
+        };
+        let (min, max) = (f(self.min), f(self.max));
+        let mut bins: Vec<Bin> = self
+            .iter()
+            // `Bin::new` would panic on the values that are not numbers, they are validated below
+            .map(|bin| Bin {
+                mean: f(bin.mean) + 0.0,
+                count: bin.count,
+            })
+            .collect();
+        if f(first.mean) > f(last.mean) || min > max {
+            bins.reverse();
+        }
+        bins.dedup_by(|next, prev| {
+            let same = next.mean == prev.mean;
+            if same {
+                prev.count += next.count;
+            }
+            same
+        });
+        StreamHist::try_from_parts(bins, min.min(max), min.max(max), self.size).map_err(|err| {
+            match err {
+                Error::InvalidParts("the bins are not sorted") => {
+                    Error::InvalidParts("the function is not monotonic")
+                }
+                err => err,
+            }
+        })
+    }
+
+    /// Approximate the distribution conditional on the values lying between `lo` and `hi` (inclusive),
+    /// e.g. the distribution of the latencies above the timeout threshold.
+    ///
+    /// As in the "sum" procedure (see [`StreamHist::count_by`]), half of the count of each bin is assumed
+    /// to be spread between its mean and the mean of each of its neighbors, with the density decreasing
+    /// linearly towards the neighbor. The bins inside the range are kept, and the bins crossing its bounds
+    /// keep the parts of their counts inside the range, rounded, with the means moved inside the range.
+    /// The `min` and `max` of the result are clamped to the range, the size is preserved.
+    ///
+    /// It returns an empty histogram if the range does not overlap with the values.
+    ///
+    /// # Panics
+    ///
+    /// It panics if `lo` is larger than `hi`, or any of them is `f64::NAN`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use histr::StreamHist;
+    ///
+    /// let hist: StreamHist = (0..1000).map(|x| x as f64).collect();
+    /// let slow = hist.truncated(900.0, f64::INFINITY);
+    /// assert_eq!(slow.count(), 100.0);
+    /// assert_eq!((slow.min(), slow.max()), (900.0, 999.0));
+    /// assert!((slow.median() - 949.5).abs() < 1.0);
+    /// ```
+    pub fn truncated(&self, lo: f64, hi: f64) -> StreamHist {
+        assert!(lo <= hi, "{lo} needs to be smaller or equal to {hi}");
+        if self.is_empty() || lo > self.max || hi < self.min {
+            return StreamHist::with_capacity(self.size);
+        }
+        let (lo, hi) = (lo.max(self.min), hi.min(self.max));
+
+        let mut bins: Vec<Bin> = Vec::with_capacity(self.bins.len());
+        for (i, bin) in self.bins.iter().enumerate() {
+            let prev = i.checked_sub(1).map_or(self.min, |i| self.bins[i].mean);
+            let next = self.bins.get(i + 1).map_or(self.max, |bin| bin.mean);
+            let count = if lo <= prev && next <= hi {
+                bin.count
+            } else {
+                split_count(bin, prev, next, lo, hi)
+            };
+            if count > 0 {
+                let mean = bin.mean.clamp(lo, hi);
+                match bins.last_mut() {
+                    // the bins crossing the bounds can be moved to the same point
+                    Some(last) if last.mean == mean => last.count += count,
+                    _ => bins.push(Bin { mean, count }),
+                }
+            }
+        }
+        if bins.is_empty() {
+            return StreamHist::with_capacity(self.size);
+        }
+        StreamHist::from_parts(bins, lo, hi, self.size)
+    }
+}
+
+/// The rounded count of the part of the `bin` between `lo` and `hi`, when half of its count is spread
+/// between the mean of the `prev` bin and its own mean with the density increasing linearly,
+/// and the other half between its mean and the mean of the `next` bin with the density decreasing linearly.
+fn split_count(bin: &Bin, prev: f64, next: f64, lo: f64, hi: f64) -> u64 {
+    let inside = |x: f64| if lo <= x && x <= hi { 1.0 } else { 0.0 };
+    // the positions of the bounds relative to the interval
+    let relative = |from: f64, to: f64| {
+        (
+            ((lo - from) / (to - from)).clamp(0.0, 1.0),
+            ((hi - from) / (to - from)).clamp(0.0, 1.0),
+        )
+    };
+    // the half of the count is a point mass when the bin mean is equal to the min or max
+    let left = if prev < bin.mean {
+        let (a, b) = relative(prev, bin.mean);
+        b * b - a * a
+    } else {
+        inside(bin.mean)
+    };
+    let right = if bin.mean < next {
+        let (a, b) = relative(bin.mean, next);
+        (1.0 - a).powi(2) - (1.0 - b).powi(2)
+    } else {
+        inside(bin.mean)
+    };
+    (bin.count as f64 / 2.0 * (left + right)).round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bins::Bin;
+    use crate::error::Error;
+    use crate::hist::StreamHist;
+
+    #[test]
+    fn scale() {
+        let hist = StreamHist::from(vec![Bin::new(1.0, 2), Bin::new(2.0, 1), Bin::new(4.0, 3)]);
+
+        let scaled = hist.scale(10.0).unwrap();
+        assert_eq!(
+            scaled.bins(),
+            &[Bin::new(10.0, 2), Bin::new(20.0, 1), Bin::new(40.0, 3)]
+        );
+        assert_eq!((scaled.min(), scaled.max()), (10.0, 40.0));
+        assert!((scaled.mean() - hist.mean() * 10.0).abs() < 1e-12);
+        assert!((scaled.variance() - hist.variance() * 100.0).abs() < 1e-9);
+        assert_eq!(scaled.capacity(), hist.capacity());
+
+        let negated = hist.scale(-1.0).unwrap();
+        assert_eq!(
+            negated.bins(),
+            &[Bin::new(-4.0, 3), Bin::new(-2.0, 1), Bin::new(-1.0, 2)]
+        );
+        assert_eq!((negated.min(), negated.max()), (-4.0, -1.0));
+        assert_eq!(negated.quantile(0.5), -hist.quantile(0.5));
+
+        // all the bins are combined
+        let zero = hist.scale(0.0).unwrap();
+        assert_eq!(zero.bins(), &[Bin::new(0.0, 6)]);
+        assert_eq!(zero.count(), 6.0);
+
+        assert!(hist.scale(f64::INFINITY).is_err());
+        assert!(hist.scale(f64::NAN).is_err());
+        assert_eq!(
+            StreamHist::default().scale(2.0).unwrap(),
+            StreamHist::default()
+        );
+    }
+
+    #[test]
+    fn shift() {
+        let hist = StreamHist::from(vec![1.0, 2.0, 4.0]);
+        let shifted = hist.shift(-1.0).unwrap();
+        assert_eq!(shifted.bins(), StreamHist::from(vec![0.0, 1.0, 3.0]).bins());
+        assert_eq!((shifted.min(), shifted.max()), (0.0, 3.0));
+        assert!((shifted.variance() - hist.variance()).abs() < 1e-12);
+        assert!(hist.shift(f64::NEG_INFINITY).is_err());
+    }
+
+    #[test]
+    fn truncated() {
+        let mut hist = StreamHist::with_capacity(20);
+        for i in 0..1000 {
+            hist.insert(((i * 7919) % 1000) as f64);
+        }
+        assert_eq!(hist.truncated(f64::NEG_INFINITY, f64::INFINITY), hist);
+        assert_eq!(hist.truncated(hist.min(), hist.max()), hist);
+
+        let truncated = hist.truncated(200.0, 500.0);
+        assert_eq!(truncated.capacity(), 20);
+        assert_eq!((truncated.min(), truncated.max()), (200.0, 500.0));
+        assert!((truncated.count() - 301.0).abs() < 10.0);
+        assert!((truncated.median() - 350.0).abs() < 10.0);
+        // the total count is approximately preserved
+        let below = hist.truncated(f64::NEG_INFINITY, 200.0);
+        let above = hist.truncated(500.0, f64::INFINITY);
+        let total = below.count() + truncated.count() + above.count();
+        assert!((total - 1000.0).abs() < 10.0);
+
+        // a single point between the bins
+        let point = hist.truncated(333.3, 333.3);
+        assert!(point.count() <= 1.0);
+        assert!(hist.truncated(2000.0, 3000.0).is_empty());
+        assert!(StreamHist::default().truncated(0.0, 1.0).is_empty());
+
+        let hist = StreamHist::from(vec![Bin::new(1.0, 4), Bin::new(3.0, 4)]);
+        assert_eq!(
+            hist.truncated(1.0, 2.0).bins(),
+            &[Bin::new(1.0, 4), Bin::new(2.0, 1)]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn truncated_invalid() {
+        StreamHist::from(vec![1.0, 2.0]).truncated(2.0, 1.0);
+    }
+
+    #[test]
+    fn map() {
+        let hist = StreamHist::from(vec![1.0, 2.0, 4.0]);
+        assert_eq!(
+            hist.map(|x| 1.0 / x).unwrap().bins(),
+            StreamHist::from(vec![0.25, 0.5, 1.0]).bins()
+        );
+        assert_eq!(
+            hist.map(|x| x.powi(2) - 5.0 * x),
+            Err(Error::InvalidParts("the function is not monotonic"))
+        );
+        assert_eq!(
+            hist.map(|x| (x - 2.0).sqrt()),
+            Err(Error::InvalidParts("the means of the bins are not numbers"))
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! WebAssembly bindings built with [wasm-bindgen].
+//!
+//! The `StreamHist` class is exported to JavaScript, for example, after building the package with
+//! `wasm-pack build --features wasm` it can be used as:
+//!
+//! ```js
+//! import { StreamHist } from "histr";
+//!
+//! const hist = new StreamHist(10);
+//! hist.insertMany(new Float64Array([1.5, 2.5, 3.5]));
+//! console.log(hist.quantile(0.5));
+//! console.log(hist.toJson());
+//! ```
+//!
+//! [wasm-bindgen]: https://rustwasm.github.io/docs/wasm-bindgen/
+
+use crate::hist::StreamHist;
+use wasm_bindgen::prelude::*;
+
+/// Streaming histogram.
+#[wasm_bindgen(js_name = StreamHist)]
+#[derive(Debug, Clone)]
+pub struct WasmStreamHist {
+    hist: StreamHist,
+}
+
+#[wasm_bindgen(js_class = StreamHist)]
+impl WasmStreamHist {
+    /// Initialize an empty histogram with the number of bins equal to `size`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(size: usize) -> Self {
+        WasmStreamHist {
+            hist: StreamHist::with_capacity(size),
+        }
+    }
+
+    /// Read the histogram from a JSON string.
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<WasmStreamHist, JsError> {
+        let hist = StreamHist::from_json(json).map_err(|err| JsError::new(&err.to_string()))?;
+        Ok(WasmStreamHist { hist })
+    }
+
+    /// Transform the histogram to a JSON string.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        self.hist.to_json()
+    }
+
+    /// Insert a new point to the histogram.
+    pub fn insert(&mut self, value: f64) -> Result<(), JsError> {
+        check_number(value).map_err(|err| JsError::new(&err))?;
+        self.hist.insert(value);
+        Ok(())
+    }
+
+    /// Insert all the values to the histogram.
+    #[wasm_bindgen(js_name = insertMany)]
+    pub fn insert_many(&mut self, values: &[f64]) -> Result<(), JsError> {
+        for value in values {
+            check_number(*value).map_err(|err| JsError::new(&err))?;
+        }
+        for value in values {
+            self.hist.insert(*value);
+        }
+        Ok(())
+    }
+
+    /// Merge the other histogram into this one.
+    pub fn merge(&mut self, other: &WasmStreamHist) {
+        self.hist.merge(other.hist.clone());
+    }
+
+    /// Adjust the number of bins in histogram.
+    pub fn resize(&mut self, size: usize) {
+        self.hist.resize(size);
+    }
+
+    /// The total count of all the values used to create the histogram.
+    pub fn count(&self) -> f64 {
+        self.hist.count()
+    }
+
+    /// Smallest observed value.
+    #[wasm_bindgen(getter)]
+    pub fn min(&self) -> f64 {
+        self.hist.min
+    }
+
+    /// Largest observed value.
+    #[wasm_bindgen(getter)]
+    pub fn max(&self) -> f64 {
+        self.hist.max
+    }
+
+    /// Approximate mean of the data.
+    pub fn mean(&self) -> f64 {
+        self.hist.mean()
+    }
+
+    /// Standard deviation of the data.
+    pub fn stdev(&self) -> f64 {
+        self.hist.stdev()
+    }
+
+    /// Approximate sample quantile of the data for a given probability `prob`.
+    pub fn quantile(&self, prob: f64) -> Result<f64, JsError> {
+        check_probability(prob).map_err(|err| JsError::new(&err))?;
+        Ok(self.hist.quantile(prob))
+    }
+
+    /// Approximate empirical cumulative distribution function of the data for a given `value`.
+    pub fn cdf(&self, value: f64) -> f64 {
+        self.hist.cdf(value)
+    }
+}
+
+// the checks return the messages, since the errors can be created only when running in JavaScript
+fn check_number(value: f64) -> Result<(), String> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("{value} is not a number"))
+    }
+}
+
+fn check_probability(prob: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&prob) {
+        Ok(())
+    } else {
+        Err(format!("{prob} is not a valid probability"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_number, check_probability, WasmStreamHist};
+
+    #[test]
+    fn wrappers() {
+        let mut hist = WasmStreamHist::new(5);
+        for i in 0..100 {
+            hist.insert(i as f64).unwrap();
+        }
+        hist.insert_many(&[100.0, 101.0]).unwrap();
+        assert_eq!(hist.count(), 102.0);
+        assert_eq!((hist.min(), hist.max()), (0.0, 101.0));
+        assert_eq!(hist.quantile(0.0).unwrap(), 0.0);
+        assert_eq!(hist.quantile(1.0).unwrap(), 101.0);
+        assert_eq!(hist.quantile(0.5).unwrap(), hist.hist.quantile(0.5));
+
+        let restored = WasmStreamHist::from_json(&hist.to_json()).unwrap();
+        assert_eq!(restored.hist, hist.hist);
+
+        let mut merged = WasmStreamHist::new(5);
+        merged.merge(&restored);
+        assert_eq!(merged.count(), 102.0);
+    }
+
+    #[test]
+    fn checks() {
+        assert!(check_number(1.0).is_ok());
+        assert!(check_number(f64::NAN).is_err());
+        assert!(check_number(f64::NEG_INFINITY).is_err());
+        assert!(check_probability(0.0).is_ok());
+        assert!(check_probability(1.0).is_ok());
+        assert!(check_probability(1.5).is_err());
+        assert!(check_probability(f64::NAN).is_err());
+    }
+}
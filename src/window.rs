@@ -0,0 +1,198 @@
+use crate::{hist::StreamHist, sample::IntoSample};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Sliding time-window histogram.
+///
+/// It keeps a ring of [`StreamHist`]s, one per `interval` (e.g. one per minute), and the values are inserted
+/// to the histogram of the current interval. When the interval passes, a new histogram is started, and the
+/// histograms older than the `intervals` count are dropped. The statistics for the last $N$ intervals
+/// are calculated from the histogram returned by [`WindowedStreamHist::window`], that merges the histograms
+/// using the [`StreamHist::merge`] procedure.
+///
+/// # Examples
+///
+/// ```
+/// use histr::WindowedStreamHist;
+/// use std::time::{Duration, Instant};
+///
+/// // one histogram per minute, for the last hour
+/// let mut hist = WindowedStreamHist::new(20, Duration::from_secs(60), 60);
+/// hist.insert(1.5);
+/// hist.insert(3.7);
+///
+/// // p99 over the last five minutes
+/// let p99 = hist.window(5).quantile(0.99);
+/// assert!(p99 <= 3.7);
+///
+/// // the times can be also passed explicitly
+/// let start = Instant::now();
+/// let mut hist = WindowedStreamHist::new(20, Duration::from_secs(60), 60);
+/// hist.insert_at(1.0, start);
+/// hist.insert_at(2.0, start + Duration::from_secs(90));
+/// assert_eq!(hist.window_at(1, start + Duration::from_secs(90)).count(), 1.0);
+/// assert_eq!(hist.window_at(2, start + Duration::from_secs(90)).count(), 2.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WindowedStreamHist {
+    /// The histograms of the intervals, the current one is the last.
+    hists: VecDeque<StreamHist>,
+    /// Start of the current interval.
+    start: Option<Instant>,
+    interval: Duration,
+    intervals: usize,
+    size: usize,
+}
+
+impl WindowedStreamHist {
+    /// Initialize the windowed histogram with the histograms of `size` bins per each of the `intervals`
+    /// of the `interval` length.
+    ///
+    /// # Panics
+    ///
+    /// It panics when `interval` or `intervals` is zero.
+    pub fn new(size: usize, interval: Duration, intervals: usize) -> Self {
+        assert!(!interval.is_zero(), "interval needs to be positive");
+        assert!(intervals > 0, "number of intervals needs to be positive");
+        let mut hists = VecDeque::with_capacity(intervals);
+        hists.push_back(StreamHist::with_capacity(size));
+        WindowedStreamHist {
+            hists,
+            start: None,
+            interval,
+            intervals,
+            size,
+        }
+    }
+
+    /// Insert a new point to the histogram of the current interval.
+    ///
+    /// # Panics
+    ///
+    /// The `value` needs to be a number. It will panic on `f64::NAN`, `f64::INFINITY`, or `f64::NEG_INFINITY`.
+    pub fn insert<T: IntoSample>(&mut self, value: T) {
+        self.insert_at(value, Instant::now())
+    }
+
+    /// Insert a new point observed at the time `now`, see [`WindowedStreamHist::insert`].
+    ///
+    /// The values observed before the start of the current interval are inserted to the current interval.
+    pub fn insert_at<T: IntoSample>(&mut self, value: T, now: Instant) {
+        self.advance(now);
+        self.hists
+            .back_mut()
+            .expect("there is always the current histogram")
+            .insert(value);
+    }
+
+    /// Start new intervals if the current one ended before the time `now`, dropping the outdated ones.
+    pub fn advance(&mut self, now: Instant) {
+        let Some(start) = self.start else {
+            self.start = Some(now);
+            return;
+        };
+        let elapsed = now.saturating_duration_since(start);
+        let passed = elapsed.as_nanos() / self.interval.as_nanos();
+        if passed == 0 {
+            return;
+        }
+        for _ in 0..passed.min(self.intervals as u128) {
+            if self.hists.len() == self.intervals {
+                self.hists.pop_front();
+            }
+            self.hists.push_back(StreamHist::with_capacity(self.size));
+        }
+        // the start of the current interval, the same as moving the start by the passed intervals,
+        // but without multiplying the interval by a possibly large number
+        let offset = elapsed.as_nanos() % self.interval.as_nanos();
+        self.start = Some(now - Duration::from_nanos(offset as u64));
+    }
+
+    /// Merge the histograms of the last `n` intervals, including the current one.
+    ///
+    /// If `n` is larger than the number of the intervals, all the intervals are merged.
+    pub fn window(&mut self, n: usize) -> StreamHist {
+        self.window_at(n, Instant::now())
+    }
+
+    /// Merge the histograms of the last `n` intervals at the time `now`, see [`WindowedStreamHist::window`].
+    pub fn window_at(&mut self, n: usize, now: Instant) -> StreamHist {
+        self.advance(now);
+        self.hists.iter().rev().take(n).fold(
+            StreamHist::with_capacity(self.size),
+            |mut acc, hist| {
+                acc.merge(hist.clone());
+                acc
+            },
+        )
+    }
+
+    /// Iterate over the histograms of the intervals, from the oldest to the current one.
+    pub fn iter(&self) -> impl Iterator<Item = &StreamHist> {
+        self.hists.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WindowedStreamHist;
+    use crate::{bins::Bin, hist::StreamHist};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn long_gap() {
+        let start = Instant::now();
+        let interval = Duration::from_micros(1);
+        let mut hist = WindowedStreamHist::new(10, interval, 2);
+        hist.insert_at(1.0, start);
+
+        // more intervals passed than fit in u32
+        let passed = u32::MAX as u64 + 10;
+        let now = start + Duration::from_micros(passed) + Duration::from_nanos(300);
+        hist.insert_at(2.0, now);
+        assert_eq!(hist.start, Some(start + Duration::from_micros(passed)));
+        assert_eq!(hist.window_at(2, now).count(), 1.0);
+
+        // still the same interval
+        let now = now + Duration::from_nanos(600);
+        assert_eq!(hist.window_at(1, now).count(), 1.0);
+    }
+
+    #[test]
+    fn window() {
+        let start = Instant::now();
+        let minute = Duration::from_secs(60);
+        let mut hist = WindowedStreamHist::new(10, minute, 3);
+
+        for i in 0..5 {
+            let now = start + minute * i;
+            hist.insert_at(i as f64, now);
+            hist.insert_at(i as f64, now + minute / 2);
+        }
+        assert_eq!(hist.iter().count(), 3);
+
+        let now = start + minute * 4;
+        let mut expected = StreamHist::from(vec![Bin::new(3.0, 2), Bin::new(4.0, 2)]);
+        expected.resize(10);
+        assert_eq!(hist.window_at(2, now), expected);
+        assert_eq!(hist.window_at(3, now).count(), 6.0);
+        assert_eq!(hist.window_at(100, now).count(), 6.0);
+        assert_eq!(hist.window_at(0, now).count(), 0.0);
+
+        // the intervals passed without any inserts
+        let now = start + minute * 6;
+        assert_eq!(hist.window_at(3, now).count(), 2.0);
+        assert_eq!(hist.window_at(2, now).count(), 0.0);
+        assert!(hist.window_at(3, now + minute * 100).is_empty());
+        assert_eq!(hist.iter().count(), 3);
+    }
+
+    #[test]
+    fn past_values() {
+        let start = Instant::now() + Duration::from_secs(10);
+        let mut hist = WindowedStreamHist::new(10, Duration::from_secs(1), 2);
+        hist.insert_at(1.0, start);
+        hist.insert_at(2.0, start - Duration::from_secs(5));
+        assert_eq!(hist.window_at(1, start).count(), 2.0);
+    }
+}
@@ -17,3 +17,112 @@
 	[ "$status" -eq 0 ]
 	[ "${#lines[@]}" -eq 0 ]
 }
+
+@test "Exit with an error when PSI vs the baseline exceeds the threshold" {
+	./histr -n -o "$BATS_TEST_TMPDIR/baseline.json" data/ping_data
+	run ./histr -n --baseline "$BATS_TEST_TMPDIR/baseline.json" data/ping_data
+	[ "$status" -eq 0 ]
+	run bash -c "awk '{print \$1 * 2}' data/ping_data | ./histr -n --baseline $BATS_TEST_TMPDIR/baseline.json"
+	[ "$status" -eq 1 ]
+	run bash -c "./histr -n --baseline $BATS_TEST_TMPDIR/baseline.json < /dev/null"
+	[ "$status" -eq 1 ]
+}
+
+@test "With --validate only report the parsing results" {
+	run ./histr --validate data/ping_data
+	[ "$status" -eq 0 ]
+	[ "${lines[0]}" = "Lines read           50001" ]
+	[ "${lines[1]}" = "Accepted             50001" ]
+}
+
+@test "With --follow read stdin until it is closed" {
+	run bash -c "head -n 100 data/ping_data | ./histr --follow --refresh-interval 0s -b 5"
+	[ "$status" -eq 0 ]
+	[[ "${output}" == *"mean	count"* ]]
+}
+
+@test "With several fields print a histogram per each of them" {
+	run bash -c "printf '1 2\n3 4\n' | ./histr -f 1,2"
+	[ "$status" -eq 0 ]
+	[[ "${output}" == *"Field 1"* ]]
+	[[ "${output}" == *"Field 2"* ]]
+}
+
+@test "With several fields and --statistics print the statistics as one table" {
+	run bash -c "printf '1 2\n3 4\n' | ./histr -n -s -f 1,2"
+	[ "$status" -eq 0 ]
+	[[ "${lines[0]}" == *"Mean"*"Sample size" ]]
+	[[ "${lines[1]}" == "Field 1 "* ]]
+	[[ "${lines[2]}" == "Field 2 "* ]]
+}
+
+@test "With --csv select the column by the name in the header" {
+	run bash -c "printf 'name,\"time, ms\"\n\"a,b\",1.5\nc,2.5\n' | ./histr --csv --column 'time, ms' -s"
+	[ "$status" -eq 0 ]
+	[[ "${output}" == *"Sample size    2"* ]]
+}
+
+@test "With diff compare two saved histograms" {
+	seq 1 100 | ./histr -n -o "$BATS_TEST_TMPDIR/a.json"
+	seq 11 110 | ./histr -n -o "$BATS_TEST_TMPDIR/b.json"
+	run ./histr diff "$BATS_TEST_TMPDIR/a.json" "$BATS_TEST_TMPDIR/b.json"
+	[ "$status" -eq 0 ]
+	[[ "${output}" == *"Wasserstein        10.000"* ]]
+}
+
+@test "With merge combine the saved histograms" {
+	seq 1 100 | ./histr -n -o "$BATS_TEST_TMPDIR/a.json"
+	seq 101 200 | ./histr -n -o "$BATS_TEST_TMPDIR/b.msgpack"
+	./histr merge -b 5 "$BATS_TEST_TMPDIR/merged.json" "$BATS_TEST_TMPDIR/a.json" "$BATS_TEST_TMPDIR/b.msgpack"
+	run ./histr -i -s -l "$BATS_TEST_TMPDIR/merged.json"
+	[ "$status" -eq 0 ]
+	[[ "${output}" == *"Sample size    200"* ]]
+	[ ! -e "$BATS_TEST_TMPDIR/merged.json.tmp" ]
+}
+
+@test "With --quantile and --json print the quantiles as JSON" {
+	run bash -c "seq 1 100 | ./histr -n --json --quantile 0,1 --cdf 1000"
+	[ "$status" -eq 0 ]
+	[ "${output}" = '{"quantiles":{"0":1.0,"1":100.0},"cdf":{"1000":1.0}}' ]
+}
+
+@test "With --output-format csv print the bins as CSV" {
+	run bash -c "seq 1 10 | ./histr -b 3 --output-format csv"
+	[ "$status" -eq 0 ]
+	[ "${lines[0]}" = "mean,count" ]
+	[ "${lines[1]}" = "2.5,4" ]
+}
+
+@test "With --density print the kernel density and save the plot" {
+	run ./histr -n --density --svg "$BATS_TEST_TMPDIR/plot.svg" data/ping_data
+	[ "$status" -eq 0 ]
+	[ "${lines[0]}" = "value	density" ]
+	grep -q "<svg" "$BATS_TEST_TMPDIR/plot.svg"
+}
+
+@test "With --transform log10 use the logarithms of the values" {
+	run bash -c "printf '1\n10\n100\n' | ./histr -n -s --transform log10"
+	[ "$status" -eq 0 ]
+	[[ "${output}" == *"Max            2.000"* ]]
+}
+
+@test "With several files aggregate all of them" {
+	seq 1 10 > "$BATS_TEST_TMPDIR/a.txt"
+	seq 11 20 > "$BATS_TEST_TMPDIR/b.txt"
+	run bash -c "seq 21 30 | ./histr -n -s $BATS_TEST_TMPDIR/a.txt - $BATS_TEST_TMPDIR/b.txt"
+	[ "$status" -eq 0 ]
+	[[ "${output}" == *"Sample size    30"* ]]
+}
+
+@test "With --checkpoint-every save the output file while reading the input" {
+	run bash -c "(seq 1 5; sleep 1; cat $BATS_TEST_TMPDIR/hist.json) | ./histr -n --checkpoint-every 5 -o $BATS_TEST_TMPDIR/hist.json"
+	[ "$status" -eq 0 ]
+	[[ "${output}" == *'"counts":[1,1,1,1,1]'* ]]
+}
+
+@test "With the non-numeric values count the categories" {
+	run bash -c "printf 'a\nb\na\n' | ./histr"
+	[ "$status" -eq 0 ]
+	[ "${lines[0]}" = "value	count" ]
+	[[ "${lines[1]}" == *"a 2"* ]]
+}